@@ -0,0 +1,67 @@
+//! BioBrick RFC10 standard assembly: parts joined by the XbaI/SpeI mixed-site scar.
+
+use crate::sequence::compatibility::{check_standard, BIOBRICK_CDS_PREFIX, BIOBRICK_PREFIX, BIOBRICK_SUFFIX, STANDARDS};
+use crate::types::Biobrick;
+
+use super::Composite;
+
+pub const SCAR: &str = "TACTAGAG";
+/// Scar before a coding sequence, whose prefix ends right before its start codon.
+pub const CDS_SCAR: &str = "TACTAG";
+
+/// Range of a part without the prefix and suffix its sequence may include.
+fn insert_range(part: &Biobrick) -> std::ops::Range<usize> {
+    let sequence = part.sequence.to_uppercase();
+    let start = if sequence.starts_with(BIOBRICK_PREFIX) {
+        BIOBRICK_PREFIX.len()
+    } else if sequence.starts_with(BIOBRICK_CDS_PREFIX) && sequence[BIOBRICK_CDS_PREFIX.len()..].starts_with("ATG") {
+        BIOBRICK_CDS_PREFIX.len()
+    } else {
+        0
+    };
+    let end = if sequence.len() >= start + BIOBRICK_SUFFIX.len() && sequence.ends_with(BIOBRICK_SUFFIX) {
+        sequence.len() - BIOBRICK_SUFFIX.len()
+    } else {
+        sequence.len()
+    };
+    start..end
+}
+
+fn is_cds(part: &Biobrick, range: &std::ops::Range<usize>) -> bool {
+    part.metadata.r#type.canonical == "coding-sequence" && part.sequence[range.clone()].to_uppercase().starts_with("ATG")
+}
+
+/// RFC10 composite of `parts` in order, or a message naming the first part that is circular or
+/// has internal RFC10 sites.
+pub fn assemble(parts: &[Biobrick], id: &str, name: &str) -> Result<Biobrick, String> {
+    let rfc10 = STANDARDS.iter().find(|s| s.name == "RFC10").expect("RFC10 standard");
+    for part in parts {
+        if part.metadata.circular {
+            return Err(format!("{} is circular", part.metadata.id));
+        }
+        let report = check_standard(part, rfc10);
+        if !report.compatible {
+            let sites = report
+                .sites
+                .iter()
+                .map(|s| format!("{} at {}", s.enzyme, s.location.start))
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(format!("{} is not RFC10 compatible: {}", part.metadata.id, sites));
+        }
+    }
+
+    let mut composite = Composite::default();
+    for (i, part) in parts.iter().enumerate() {
+        let range = insert_range(part);
+        if i > 0 {
+            let scar = if is_cds(part, &range) { CDS_SCAR } else { SCAR };
+            composite.push_junction(scar, Some("BioBrick scar"), "assembly-scar");
+        }
+        composite.push_part(part, range);
+    }
+
+    let ids = parts.iter().map(|p| p.metadata.id.as_str()).collect::<Vec<_>>();
+    let description = format!("BioBrick RFC10 assembly of {}", ids.join(", "));
+    Ok(composite.build(id, name, &description, "composite", false))
+}
@@ -0,0 +1,309 @@
+//! Gibson / HiFi assembly design: PCR primers whose tails make consecutive fragments overlap.
+//! Parts too short to amplify are carried by the tails of the primers around them.
+
+use crate::ontology::find_canonical;
+use crate::sequence::primers::{binding_length, melting_temperature, primer, reverse_primer};
+use crate::sequence::reverse_complement;
+use crate::types::{Biobrick, GibsonFragment, GibsonJunction, GibsonResponse, Location};
+
+use super::Composite;
+
+/// Parts shorter than this are added through primer tails rather than amplified.
+pub const MIN_AMPLICON: usize = 60;
+pub const MAX_OVERLAP: usize = 40;
+const MIN_BINDING: usize = 18;
+const MAX_BINDING: usize = 35;
+
+pub struct Settings {
+    pub overlap: usize,
+    pub overlap_tm: f64,
+    pub primer_tm: f64,
+    pub amplify_backbone: bool,
+}
+
+struct Piece<'a> {
+    part: &'a Biobrick,
+    sequence: String,
+    amplified: bool,
+}
+
+/// Junction between two pieces, through the sequence of the bridged parts. The overlap takes
+/// `a` bases from the end of the left piece and `b` from the start of the right one.
+struct Joint<'a> {
+    left: usize,
+    right: usize,
+    bridged: Vec<&'a Biobrick>,
+    bridge: String,
+    a: usize,
+    b: usize,
+}
+
+/// Overlap shared by the fragments on both sides of `joint`: all of it when both are amplified,
+/// otherwise only the part lying in the piece that is not.
+fn overlap(pieces: &[Piece], joint: &Joint) -> String {
+    let (left, right) = (&pieces[joint.left], &pieces[joint.right]);
+    let tail = &left.sequence[left.sequence.len() - joint.a..];
+    let head = &right.sequence[..joint.b];
+    match (left.amplified, right.amplified) {
+        (true, true) => format!("{}{}{}", tail, joint.bridge, head),
+        (false, _) => tail.to_string(),
+        (_, false) => head.to_string(),
+    }
+}
+
+/// Grows the overlap of `joint` until it is `settings.overlap` long and melts at
+/// `settings.overlap_tm`, alternating sides, up to `MAX_OVERLAP`.
+fn size_overlap(pieces: &[Piece], joint: &mut Joint, settings: &Settings) {
+    let (left, right) = (&pieces[joint.left], &pieces[joint.right]);
+    let grow_a = !left.amplified || right.amplified;
+    let grow_b = !right.amplified || left.amplified;
+    loop {
+        let overlap = overlap(pieces, joint);
+        if overlap.len() >= MAX_OVERLAP
+            || (overlap.len() >= settings.overlap && melting_temperature(&overlap) >= settings.overlap_tm)
+        {
+            return;
+        }
+        let can_a = grow_a && joint.a < left.sequence.len();
+        let can_b = grow_b && joint.b < right.sequence.len();
+        if can_a && (joint.a <= joint.b || !can_b) {
+            joint.a += 1;
+        } else if can_b {
+            joint.b += 1;
+        } else {
+            return;
+        }
+    }
+}
+
+/// Circular product of `parts` in order, followed by `backbone`, with the primers to amplify
+/// each fragment and the overlaps joining them.
+pub fn design(
+    parts: &[Biobrick],
+    backbone: &Biobrick,
+    settings: &Settings,
+    id: &str,
+    name: &str,
+) -> Result<GibsonResponse, String> {
+    let mut pieces = Vec::new();
+    let mut bridged_before = Vec::new();
+    let mut pending = Vec::new();
+    for (part, is_backbone) in parts.iter().map(|p| (p, false)).chain([(backbone, true)]) {
+        if is_backbone || part.sequence.len() >= MIN_AMPLICON {
+            pieces.push(Piece {
+                part,
+                sequence: part.sequence.to_uppercase(),
+                amplified: !is_backbone || settings.amplify_backbone,
+            });
+            bridged_before.push(std::mem::take(&mut pending));
+        } else {
+            pending.push(part);
+        }
+    }
+    if !pieces.iter().any(|p| p.amplified) {
+        return Err("Every part is too short to amplify, and the backbone is not amplified".to_string());
+    }
+
+    // Leading short parts sit between the backbone and the first piece, across the origin
+    let mut joints = Vec::new();
+    for (right, bridged) in bridged_before.into_iter().enumerate() {
+        let bridge = bridged.iter().map(|p| p.sequence.to_uppercase()).collect::<String>();
+        let mut joint = Joint {
+            left: (right + pieces.len() - 1) % pieces.len(),
+            right,
+            bridged,
+            bridge,
+            a: 0,
+            b: 0,
+        };
+        size_overlap(&pieces, &mut joint, settings);
+        joints.push(joint);
+    }
+
+    let mut fragments = Vec::new();
+    for (i, piece) in pieces.iter().enumerate() {
+        let id = &piece.part.metadata.id;
+        if !piece.amplified {
+            fragments.push(GibsonFragment {
+                part: id.clone(),
+                length: piece.sequence.len(),
+                forward: None,
+                reverse: None,
+            });
+            continue;
+        }
+        let before = joints.iter().find(|j| j.right == i).expect("joint before piece");
+        let after = joints.iter().find(|j| j.left == i).expect("joint after piece");
+        let left = &pieces[before.left].sequence;
+        let head = format!("{}{}", &left[left.len() - before.a..], before.bridge);
+        let tail = format!("{}{}", after.bridge, &pieces[after.right].sequence[..after.b]);
+
+        let forward_binding = binding_length(&piece.sequence, settings.primer_tm, MIN_BINDING, MAX_BINDING);
+        let reverse_binding =
+            binding_length(&reverse_complement(&piece.sequence), settings.primer_tm, MIN_BINDING, MAX_BINDING);
+        let forward = primer(
+            &format!("{}_F", id),
+            &format!("{}{}", head, &piece.sequence[..forward_binding]),
+            forward_binding,
+            settings.primer_tm,
+        );
+        let reverse = reverse_primer(&format!("{}_R", id), &piece.sequence, reverse_binding, &tail, settings.primer_tm);
+        fragments.push(GibsonFragment {
+            part: id.clone(),
+            length: head.len() + piece.sequence.len() + tail.len(),
+            forward: Some(forward),
+            reverse: Some(reverse),
+        });
+    }
+
+    let mut composite = Composite::default();
+    let mut starts = Vec::new();
+    for part in parts.iter().chain([backbone]) {
+        if pieces.iter().any(|p| std::ptr::eq(p.part, part)) {
+            starts.push(composite.length());
+        }
+        composite.push_part(part, 0..part.sequence.len());
+    }
+    let total = composite.length();
+    let wrap = |p: usize| (p % total) as i32 + 1;
+
+    let binding_site = find_canonical("primer-binding-site").expect("primer-binding-site type");
+    let mut junctions = Vec::new();
+    for joint in &joints {
+        let (left, right) = (&pieces[joint.left], &pieces[joint.right]);
+        let left_end = starts[joint.left] + left.sequence.len();
+        let right_start = if joint.right <= joint.left { starts[joint.right] + total } else { starts[joint.right] };
+        let (low, high) = match (left.amplified, right.amplified) {
+            (true, true) => (left_end - joint.a, right_start + joint.b),
+            (false, _) => (left_end - joint.a, left_end),
+            (_, false) => (right_start, right_start + joint.b),
+        };
+        let overlap = overlap(&pieces, joint);
+        let location = Location {
+            start: wrap(low),
+            end: wrap(high - 1),
+            forward: true,
+            segments: vec![],
+            partial_start: false,
+            partial_end: false,
+        };
+        let name = format!("{}-{} overlap", left.part.metadata.id, right.part.metadata.id);
+        composite.push_feature(&name, binding_site.into(), location.start, location.end);
+        junctions.push(GibsonJunction {
+            left: left.part.metadata.id.clone(),
+            right: right.part.metadata.id.clone(),
+            bridged: joint.bridged.iter().map(|p| p.metadata.id.clone()).collect(),
+            length: overlap.len(),
+            tm: (melting_temperature(&overlap) * 10.0).round() / 10.0,
+            overlap,
+            location,
+        });
+    }
+
+    let ids = parts.iter().chain([backbone]).map(|p| p.metadata.id.as_str()).collect::<Vec<_>>();
+    let description = format!("Gibson assembly of {}", ids.join(", "));
+    Ok(GibsonResponse {
+        fragments,
+        junctions,
+        product: composite.build(id, name, &description, "plasmid", true),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequence::feature_sequence;
+    use crate::types::MetaBiobrick;
+
+    fn part(id: &str, length: usize, seed: u64) -> Biobrick {
+        let mut state = seed;
+        let sequence = (0..length)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                b"ACGT"[(state >> 33) as usize % 4] as char
+            })
+            .collect::<String>();
+        Biobrick {
+            metadata: MetaBiobrick {
+                id: id.to_string(),
+                name: id.to_string(),
+                description: String::new(),
+                creation: String::new(),
+                size: length as i32,
+                circular: false,
+                r#type: find_canonical("engineered-region").unwrap().into(),
+                authors: vec![],
+                providers: vec![],
+            },
+            sequence,
+            features: vec![],
+        }
+    }
+
+    fn settings(overlap_tm: f64, amplify_backbone: bool) -> Settings {
+        Settings { overlap: 20, overlap_tm, primer_tm: 60.0, amplify_backbone }
+    }
+
+    /// Every junction overlap is in the product, under a primer binding site feature.
+    fn check_junctions(response: &GibsonResponse) {
+        let product = &response.product;
+        for junction in &response.junctions {
+            assert_eq!(feature_sequence(&product.sequence, &junction.location), junction.overlap);
+            assert_eq!(junction.length, junction.overlap.len());
+            let name = format!("{}-{} overlap", junction.left, junction.right);
+            let feature = product.features.iter().find(|f| f.name == name).expect("overlap feature");
+            assert_eq!(feature.r#type.canonical, "primer-binding-site");
+            assert_eq!((feature.location.start, feature.location.end), (junction.location.start, junction.location.end));
+        }
+    }
+
+    #[test]
+    fn sizes_overlaps_by_length_and_tm() {
+        let parts = [part("a", 100, 1), part("b", 80, 2)];
+        let backbone = part("backbone", 200, 3);
+
+        let response = design(&parts, &backbone, &settings(0.0, true), "product", "product").unwrap();
+        assert_eq!(response.product.sequence.len(), 380);
+        assert_eq!(response.junctions.iter().map(|j| j.length).collect::<Vec<_>>(), [20, 20, 20]);
+        check_junctions(&response);
+
+        // Unreachable Tm targets stop at the longest overlap
+        let response = design(&parts, &backbone, &settings(200.0, true), "product", "product").unwrap();
+        assert!(response.junctions.iter().all(|j| j.length == MAX_OVERLAP));
+        check_junctions(&response);
+
+        let response = design(&parts, &backbone, &settings(55.0, true), "product", "product").unwrap();
+        for junction in &response.junctions {
+            assert!(junction.length >= 20 && (junction.tm >= 55.0 || junction.length == MAX_OVERLAP));
+        }
+        check_junctions(&response);
+    }
+
+    #[test]
+    fn bridges_short_parts_and_digested_backbones() {
+        let parts = [part("a", 100, 1), part("linker", 30, 4), part("b", 80, 2)];
+        let backbone = part("backbone", 200, 3);
+        let response = design(&parts, &backbone, &settings(0.0, false), "product", "product").unwrap();
+
+        let junctions = response
+            .junctions
+            .iter()
+            .map(|j| (j.left.as_str(), j.right.as_str(), j.bridged.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            junctions,
+            [
+                ("backbone", "a", vec![]),
+                ("a", "b", vec!["linker".to_string()]),
+                ("b", "backbone", vec![])
+            ]
+        );
+        // The linker is carried by the tails, so the overlap spans it
+        assert!(response.junctions[1].overlap.contains(&parts[1].sequence));
+        // The backbone is not amplified: the overlaps are its own ends
+        assert!(backbone.sequence.ends_with(&response.junctions[0].overlap));
+        assert!(backbone.sequence.starts_with(&response.junctions[2].overlap));
+        assert!(response.fragments[2].forward.is_none());
+        check_junctions(&response);
+    }
+}
@@ -0,0 +1,320 @@
+//! Golden Gate (MoClo) assembly: parts released by a Type IIS enzyme and ligated through their
+//! four base overhangs.
+
+use crate::sequence::enzymes::Enzyme;
+use crate::sequence::reverse_complement;
+use crate::types::{Biobrick, DesignError};
+
+use super::{rotated, Composite};
+
+/// Enzymes cutting outside their recognition site, leaving a 5' overhang.
+pub fn is_type_iis(enzyme: &Enzyme) -> bool {
+    enzyme.cut >= enzyme.site.len() as i32 && enzyme.complement_cut > enzyme.cut
+}
+
+/// Fragment released from a part, with 0-based top strand cuts: `start` and `start_overhang`
+/// delimit its left overhang, `end` and `end_overhang` its right overhang. Cuts of circular
+/// parts are unwrapped, so `start < end` always holds.
+struct Release {
+    part: Biobrick,
+    start: usize,
+    start_overhang: usize,
+    end: usize,
+    end_overhang: usize,
+}
+
+impl Release {
+    fn left(&self) -> String {
+        self.part.sequence[self.start..self.start_overhang].to_uppercase()
+    }
+
+    fn right(&self) -> String {
+        self.part.sequence[self.end..self.end_overhang].to_uppercase()
+    }
+}
+
+fn error(part: Option<&str>, kind: &str, message: String) -> DesignError {
+    DesignError {
+        part: part.map(|p| p.to_string()),
+        kind: kind.to_string(),
+        message,
+    }
+}
+
+/// The fragment between a forward site and a reverse site facing each other, with no other
+/// site in between. Circular parts are rotated so that the fragment does not span the origin.
+fn release(part: &Biobrick, enzyme: &Enzyme, errors: &mut Vec<DesignError>) -> Option<Release> {
+    let id = part.metadata.id.as_str();
+    let sequence = part.sequence.to_uppercase();
+    let circular = part.metadata.circular;
+    let len = sequence.len() as i64;
+    let sites = enzyme.sites(&sequence, circular);
+    let forward = sites.iter().filter(|s| s.location.forward).collect::<Vec<_>>();
+    let reverse = sites.iter().filter(|s| !s.location.forward).collect::<Vec<_>>();
+
+    // Linear parts keep their outermost sites; circular ones must have exactly two
+    let (first, last) = if circular {
+        match (forward.as_slice(), reverse.as_slice()) {
+            ([first], [last]) => (*first, *last),
+            _ => {
+                let message = if sites.len() > 2 {
+                    let at = sites.iter().map(|s| s.location.start.to_string()).collect::<Vec<_>>();
+                    format!(
+                        "{} has {} {} sites, at {}; expected two facing each other",
+                        id,
+                        sites.len(),
+                        enzyme.name,
+                        at.join(", ")
+                    )
+                } else {
+                    format!("{} lacks a pair of {} sites facing each other", id, enzyme.name)
+                };
+                let kind = if sites.len() > 2 { "internal-site" } else { "missing-site" };
+                errors.push(error(Some(id), kind, message));
+                return None;
+            }
+        }
+    } else {
+        match (forward.first(), reverse.last()) {
+            (Some(first), Some(last)) if first.location.start < last.location.start => (*first, *last),
+            _ => {
+                let message = format!("{} lacks a {} site facing into each end", id, enzyme.name);
+                errors.push(error(Some(id), "missing-site", message));
+                return None;
+            }
+        }
+    };
+    let internal = sites
+        .iter()
+        .filter(|s| !std::ptr::eq(*s, first) && !std::ptr::eq(*s, last))
+        .filter(|s| s.location.start > first.location.start && s.location.start < last.location.start)
+        .collect::<Vec<_>>();
+    for site in &internal {
+        let message = format!(
+            "{} has an internal {} site at {}..{}, which would cut the part",
+            id, enzyme.name, site.location.start, site.location.end
+        );
+        errors.push(error(Some(id), "internal-site", message));
+    }
+    if !internal.is_empty() {
+        return None;
+    }
+
+    let (start, start_overhang) = enzyme.cuts_at(first.location.start as i64 - 1, true);
+    let (end, end_overhang) = enzyme.cuts_at(last.location.start as i64 - 1, false);
+    if circular {
+        let origin = start.rem_euclid(len);
+        let shift = |p: i64| (p - origin).rem_euclid(len) as usize;
+        let (end, end_overhang) = (shift(end), shift(end) + (end_overhang - end) as usize);
+        if end_overhang > len as usize {
+            errors.push(error(Some(id), "missing-site", format!("{} sites of {} overlap", enzyme.name, id)));
+            return None;
+        }
+        Some(Release {
+            part: rotated(part, origin as usize),
+            start: 0,
+            start_overhang: (start_overhang - start) as usize,
+            end,
+            end_overhang,
+        })
+    } else if start >= 0 && end_overhang <= len && start_overhang <= end {
+        Some(Release {
+            part: part.clone(),
+            start: start as usize,
+            start_overhang: start_overhang as usize,
+            end: end as usize,
+            end_overhang: end_overhang as usize,
+        })
+    } else {
+        let message = format!("{} {} sites cut outside the part or overlap", id, enzyme.name);
+        errors.push(error(Some(id), "missing-site", message));
+        None
+    }
+}
+
+fn mismatches(a: &str, b: &str) -> usize {
+    a.bytes().zip(b.bytes()).filter(|(x, y)| x != y).count() + a.len().abs_diff(b.len())
+}
+
+/// Overhangs ligating to themselves, used twice, or close to another one: at most one mismatch
+/// with it or its reverse complement. Each overhang comes with where it is, for messages.
+fn check_overhangs(overhangs: &[(String, String)], errors: &mut Vec<DesignError>) {
+    for (i, (overhang, place)) in overhangs.iter().enumerate() {
+        if overhang.is_empty() {
+            continue;
+        }
+        if *overhang == reverse_complement(overhang) {
+            let message = format!("Overhang {} {} is palindromic and ligates to itself", overhang, place);
+            errors.push(error(None, "palindromic-overhang", message));
+        }
+        for (other, other_place) in &overhangs[i + 1..] {
+            if other == overhang || *other == reverse_complement(overhang) {
+                let message = format!("Overhang {} {} is also used {}", overhang, place, other_place);
+                errors.push(error(None, "duplicate-overhang", message));
+            } else if mismatches(overhang, other) <= 1 || mismatches(overhang, &reverse_complement(other)) <= 1 {
+                let message = format!("Overhang {} {} may mis-ligate with {} {}", overhang, place, other, other_place);
+                errors.push(error(None, "overhang-crosstalk", message));
+            }
+        }
+    }
+}
+
+/// Golden Gate product of `parts` in order, closed into `backbone` when given. Without one,
+/// the product is circular only if the last overhang matches the first.
+pub fn assemble(
+    parts: &[Biobrick],
+    backbone: Option<&Biobrick>,
+    enzyme: &Enzyme,
+    id: &str,
+    name: &str,
+) -> Result<Biobrick, Vec<DesignError>> {
+    let mut errors = Vec::new();
+    let mut releases = Vec::new();
+    for part in parts.iter().chain(backbone) {
+        if let Some(release) = release(part, enzyme, &mut errors) {
+            releases.push(release);
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let circular = backbone.is_some() || releases[releases.len() - 1].right() == releases[0].left();
+    let count = if circular { releases.len() } else { releases.len() - 1 };
+    let mut overhangs = Vec::new();
+    for i in 0..count {
+        let (current, next) = (&releases[i], &releases[(i + 1) % releases.len()]);
+        let (overhang, expected) = (current.right(), next.left());
+        let (left, right) = (&current.part.metadata.id, &next.part.metadata.id);
+        if overhang != expected {
+            let message = format!("{} ends with overhang {} but {} starts with {}", left, overhang, right, expected);
+            errors.push(error(Some(right), "overhang-mismatch", message));
+        }
+        overhangs.push((overhang, format!("between {} and {}", left, right)));
+    }
+    // The ends of a linear product can still ligate to the junctions
+    if !circular {
+        let (first, last) = (&releases[0], &releases[releases.len() - 1]);
+        overhangs.push((first.left(), format!("at the start of {}", first.part.metadata.id)));
+        overhangs.push((last.right(), format!("at the end of {}", last.part.metadata.id)));
+    }
+    check_overhangs(&overhangs, &mut errors);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    // Each fragment brings its left overhang; a linear product also keeps the last right one
+    let scar = crate::ontology::find_canonical("assembly-scar").expect("assembly-scar type");
+    let mut composite = Composite::default();
+    for (i, release) in releases.iter().enumerate() {
+        let start = composite.length() as i32 + 1;
+        let last = i == releases.len() - 1;
+        let end = if last && !circular { release.end_overhang } else { release.end };
+        composite.push_part(&release.part, release.start..end);
+        if circular || i > 0 {
+            let overhang = release.left();
+            composite.push_feature(&overhang, scar.into(), start, start + overhang.len() as i32 - 1);
+        }
+    }
+
+    let ids = releases.iter().map(|r| r.part.metadata.id.as_str()).collect::<Vec<_>>();
+    let description = format!("{} Golden Gate assembly of {}", enzyme.name, ids.join(", "));
+    let canonical = if circular { "plasmid" } else { "composite" };
+    Ok(composite.build(id, name, &description, canonical, circular))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ontology::find_canonical;
+    use crate::sequence::enzymes::find_enzyme;
+    use crate::types::MetaBiobrick;
+
+    /// Linear part whose BsaI sites release `left`, `insert` and `right`.
+    fn part(id: &str, left: &str, insert: &str, right: &str) -> Biobrick {
+        let sequence = format!("GGTCTCA{}{}{}AGAGACC", left, insert, right);
+        Biobrick {
+            metadata: MetaBiobrick {
+                id: id.to_string(),
+                name: id.to_string(),
+                description: String::new(),
+                creation: String::new(),
+                size: sequence.len() as i32,
+                circular: false,
+                r#type: find_canonical("composite").unwrap().into(),
+                authors: vec![],
+                providers: vec![],
+            },
+            sequence,
+            features: vec![],
+        }
+    }
+
+    fn kinds(parts: &[Biobrick]) -> Vec<String> {
+        let bsai = find_enzyme("BsaI").unwrap();
+        match assemble(parts, None, bsai, "product", "product") {
+            Ok(_) => vec![],
+            Err(errors) => errors.into_iter().map(|e| e.kind).collect(),
+        }
+    }
+
+    #[test]
+    fn assembles_three_parts() {
+        let parts = [
+            part("a", "GGAG", "AAAAAAAAAA", "AATG"),
+            part("b", "AATG", "CCCCCCCCCC", "GCTT"),
+            part("c", "GCTT", "TTTTTTTTTT", "CGCT"),
+        ];
+        let product = assemble(&parts, None, find_enzyme("BsaI").unwrap(), "product", "product").unwrap();
+        assert_eq!(product.sequence, "GGAGAAAAAAAAAAAATGCCCCCCCCCCGCTTTTTTTTTTTTCGCT");
+        assert!(!product.metadata.circular);
+        assert_eq!(product.metadata.r#type.canonical, "composite");
+
+        let scars = product
+            .features
+            .iter()
+            .filter(|f| f.r#type.canonical == "assembly-scar")
+            .map(|f| (f.name.as_str(), f.location.start, f.location.end))
+            .collect::<Vec<_>>();
+        assert_eq!(scars, [("AATG", 15, 18), ("GCTT", 29, 32)]);
+        let placed = product
+            .features
+            .iter()
+            .filter(|f| f.r#type.canonical == "composite")
+            .map(|f| (f.name.as_str(), f.location.start, f.location.end))
+            .collect::<Vec<_>>();
+        assert_eq!(placed, [("a", 1, 14), ("b", 15, 28), ("c", 29, 46)]);
+    }
+
+    #[test]
+    fn rejects_duplicate_overhangs() {
+        let parts = [part("a", "GGAG", "AAAA", "AATG"), part("b", "AATG", "CCCC", "GCTT"), part("c", "GCTT", "TTTT", "AATG")];
+        assert_eq!(kinds(&parts), ["duplicate-overhang"]);
+    }
+
+    #[test]
+    fn rejects_palindromic_overhangs() {
+        let parts = [part("a", "GGAG", "AAAA", "GATC"), part("b", "GATC", "CCCC", "GCTT")];
+        assert_eq!(kinds(&parts), ["palindromic-overhang"]);
+    }
+
+    #[test]
+    fn rejects_overhang_crosstalk() {
+        let parts = [part("a", "GGAG", "AAAA", "AATG"), part("b", "AATG", "CCCC", "AATC"), part("c", "AATC", "TTTT", "CGCT")];
+        assert_eq!(kinds(&parts), ["overhang-crosstalk"]);
+    }
+
+    #[test]
+    fn rejects_mismatched_overhangs() {
+        let parts = [part("a", "GGAG", "AAAA", "AATG"), part("b", "CGCT", "CCCC", "GCTT")];
+        assert_eq!(kinds(&parts), ["overhang-mismatch"]);
+    }
+
+    #[test]
+    fn rejects_internal_and_missing_sites() {
+        let internal = part("a", "GGAG", "AAGGTCTCAA", "AATG");
+        let mut missing = part("b", "AATG", "CCCC", "GCTT");
+        missing.sequence = missing.sequence.replace("GAGACC", "GAGTCC");
+        assert_eq!(kinds(&[internal, missing]), ["internal-site", "missing-site"]);
+    }
+}
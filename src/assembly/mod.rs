@@ -0,0 +1,365 @@
+//! Composite parts assembled from cached or fetched biobricks.
+
+pub mod biobrick;
+pub mod gibson;
+pub mod golden_gate;
+
+use crate::merge::{concat_unique_authors, concat_unique_providers};
+use crate::ontology::{find_canonical, OntologyEntrySerializable};
+use crate::sequence::{reverse_complement, slice};
+use crate::types::{Biobrick, Location, MetaBiobrick, MetaFeature, Segment};
+
+/// Linear composite under construction: pieces of parts and junction sequences, with features
+/// moved to their new coordinates.
+#[derive(Default)]
+pub struct Composite {
+    sequence: String,
+    features: Vec<MetaFeature>,
+    parts: Vec<Biobrick>,
+}
+
+impl Composite {
+    /// Length of the sequence assembled so far.
+    pub fn length(&self) -> usize {
+        self.sequence.len()
+    }
+
+    /// Appends `range` (0-based, half-open) of `part`, with a feature for the part itself and
+    /// the part features lying entirely within the range.
+    pub fn push_part(&mut self, part: &Biobrick, range: std::ops::Range<usize>) {
+        let offset = self.sequence.len() as i32 - range.start as i32;
+        let (low, high) = (range.start as i32 + 1, range.end as i32);
+        self.sequence.push_str(&part.sequence[range.clone()].to_uppercase());
+        self.push_feature(&part.metadata.id, part.metadata.r#type.clone(), low + offset, high + offset);
+
+        for feature in &part.features {
+            let segments = feature.location.ranges();
+            let inside = feature.location.start <= feature.location.end
+                && segments.iter().all(|s| s.start >= low && s.end <= high);
+            if !inside {
+                continue;
+            }
+            let location = Location {
+                start: feature.location.start + offset,
+                end: feature.location.end + offset,
+                forward: feature.location.forward,
+                segments: if feature.location.segments.is_empty() {
+                    vec![]
+                } else {
+                    segments
+                        .iter()
+                        .map(|s| Segment {
+                            start: s.start + offset,
+                            end: s.end + offset,
+                            forward: s.forward,
+                        })
+                        .collect()
+                },
+                partial_start: feature.location.partial_start,
+                partial_end: feature.location.partial_end,
+            };
+            self.features.push(MetaFeature {
+                id: format!("{}_{}", feature.name, location.start),
+                name: feature.name.clone(),
+                r#type: feature.r#type.clone(),
+                location,
+                translation: feature.translation.clone(),
+            });
+        }
+        self.parts.push(part.clone());
+    }
+
+    /// Appends a junction sequence, annotated with a feature of type `canonical` when named.
+    pub fn push_junction(&mut self, sequence: &str, name: Option<&str>, canonical: &str) {
+        let start = self.sequence.len() as i32 + 1;
+        self.sequence.push_str(&sequence.to_uppercase());
+        if let (Some(name), Some(entry)) = (name, find_canonical(canonical)) {
+            if !sequence.is_empty() {
+                self.push_feature(name, entry.into(), start, self.sequence.len() as i32);
+            }
+        }
+    }
+
+    /// Adds a forward feature over 1-based inclusive composite coordinates.
+    pub fn push_feature(&mut self, name: &str, r#type: OntologyEntrySerializable, start: i32, end: i32) {
+        self.features.push(MetaFeature {
+            id: format!("{}_{}", name, start),
+            name: name.to_string(),
+            r#type,
+            location: Location {
+                start,
+                end,
+                forward: true,
+                segments: vec![],
+                partial_start: false,
+                partial_end: false,
+            },
+            translation: None,
+        });
+    }
+
+    /// Composite biobrick of type `canonical`, with the authors and providers of its parts.
+    pub fn build(mut self, id: &str, name: &str, description: &str, canonical: &str, circular: bool) -> Biobrick {
+        let mut authors = Vec::new();
+        let mut providers = Vec::new();
+        for part in &self.parts {
+            authors = concat_unique_authors(&authors, &part.metadata.authors);
+            providers = concat_unique_providers(&providers, &part.metadata.providers);
+        }
+        self.features.sort_by_key(|f| (f.location.start, -f.location.end));
+
+        Biobrick {
+            metadata: MetaBiobrick {
+                id: id.to_string(),
+                name: name.to_string(),
+                description: description.to_string(),
+                creation: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                size: self.sequence.len() as i32,
+                circular,
+                r#type: find_canonical(canonical).or_else(|| find_canonical("composite")).unwrap().into(),
+                authors,
+                providers,
+            },
+            sequence: self.sequence,
+            features: self.features,
+        }
+    }
+}
+
+/// Circular `part` re-opened at 0-based `origin`, with its features moved along. Segments that
+/// cross the new origin are split there, so features keep their bases as joins.
+pub fn rotated(part: &Biobrick, origin: usize) -> Biobrick {
+    let len = part.sequence.len() as i32;
+    if len == 0 {
+        return part.clone();
+    }
+    let shift = |p: i32| (p - 1 - origin as i32).rem_euclid(len) + 1;
+    let mut rotated = part.clone();
+    rotated.sequence = format!("{}{}", &part.sequence[origin..], &part.sequence[..origin]);
+    for feature in &mut rotated.features {
+        let location = &mut feature.location;
+        let shifted = Location {
+            segments: location
+                .ranges()
+                .into_iter()
+                .map(|s| Segment { start: shift(s.start), end: shift(s.end), forward: s.forward })
+                .collect(),
+            ..location.clone()
+        };
+        let segments = shifted.linear_ranges(len);
+        location.start = segments[0].start;
+        location.end = segments[segments.len() - 1].end;
+        if segments.len() > 1 || !location.segments.is_empty() {
+            location.segments = segments;
+        }
+    }
+    rotated
+}
+
+/// `length` bases of `part` from 0-based `start`, wrapping around the origin of circular parts,
+/// as a linear part. Features overlapping the slice are clipped to it, with partial ends where
+/// they were cut.
+pub fn sliced(part: &Biobrick, start: usize, length: usize) -> Biobrick {
+    let len = part.sequence.len() as i32;
+    let mut sliced = part.clone();
+    sliced.sequence = slice(&part.sequence, start as i64, length);
+    sliced.metadata.size = length as i32;
+    sliced.metadata.circular = false;
+    sliced.features = part
+        .features
+        .iter()
+        .filter_map(|feature| {
+            let ranges = feature.location.ranges();
+            let last = ranges.len() - 1;
+            // Pieces as (segment, partial start, partial end)
+            let mut pieces = Vec::new();
+            for (i, segment) in ranges.iter().enumerate() {
+                let offset = (segment.start - 1 - start as i32).rem_euclid(len);
+                let span = (segment.end - segment.start).rem_euclid(len) + 1;
+                // A segment entering the slice from before its start begins one turn earlier.
+                // Pieces stay in feature order, so a feature over the origin of a circular part
+                // opened there becomes a join of its two ends.
+                for low in [offset, offset - len] {
+                    let high = low + span - 1;
+                    let (first, end) = (low.max(0), high.min(length as i32 - 1));
+                    if first > end {
+                        continue;
+                    }
+                    pieces.push((
+                        Segment {
+                            start: first + 1,
+                            end: end + 1,
+                            forward: segment.forward,
+                        },
+                        first > low || (i == 0 && feature.location.partial_start),
+                        end < high || (i == last && feature.location.partial_end),
+                    ));
+                }
+            }
+            let (first, last) = (pieces.first()?, pieces.last()?);
+            let location = Location {
+                start: first.0.start,
+                end: last.0.end,
+                forward: feature.location.forward,
+                segments: if pieces.len() > 1 || !feature.location.segments.is_empty() {
+                    pieces.iter().map(|p| p.0.clone()).collect()
+                } else {
+                    vec![]
+                },
+                partial_start: first.1,
+                partial_end: last.2,
+            };
+            Some(MetaFeature {
+                id: feature.id.clone(),
+                name: feature.name.clone(),
+                r#type: feature.r#type.clone(),
+                location,
+                translation: feature.translation.clone(),
+            })
+        })
+        .collect();
+    sliced
+}
+
+/// Reverse complement of `part`, with its features moved to the other strand.
+pub fn reverse_complemented(part: &Biobrick) -> Biobrick {
+    let len = part.sequence.len() as i32;
+    let flip = |p: i32| len + 1 - p;
+    let mut reversed = part.clone();
+    reversed.sequence = reverse_complement(&part.sequence);
+    for feature in &mut reversed.features {
+        let location = &mut feature.location;
+        (location.start, location.end) = (flip(location.end), flip(location.start));
+        (location.partial_start, location.partial_end) = (location.partial_end, location.partial_start);
+        location.forward = !location.forward;
+        // Segments stay in forward strand order
+        location.segments.reverse();
+        for segment in &mut location.segments {
+            (segment.start, segment.end) = (flip(segment.end), flip(segment.start));
+            segment.forward = !segment.forward;
+        }
+    }
+    reversed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequence::feature_sequence;
+
+    const SEQUENCE: &str = "ATGCCGTAAGCTTGACCGGA";
+
+    fn location(segments: &[(i32, i32)], forward: bool) -> Location {
+        Location {
+            start: segments[0].0,
+            end: segments[segments.len() - 1].1,
+            forward,
+            segments: segments.iter().map(|&(start, end)| Segment { start, end, forward }).collect(),
+            partial_start: false,
+            partial_end: false,
+        }
+    }
+
+    fn part(circular: bool, features: Vec<(&str, Location)>) -> Biobrick {
+        Biobrick {
+            metadata: MetaBiobrick {
+                id: "BBa_TEST".to_string(),
+                name: "test".to_string(),
+                description: String::new(),
+                creation: String::new(),
+                size: SEQUENCE.len() as i32,
+                circular,
+                r#type: find_canonical("composite").unwrap().into(),
+                authors: vec![],
+                providers: vec![],
+            },
+            sequence: SEQUENCE.to_string(),
+            features: features
+                .into_iter()
+                .map(|(id, location)| MetaFeature {
+                    id: id.to_string(),
+                    name: format!("{} name", id),
+                    r#type: find_canonical("engineered-region").unwrap().into(),
+                    location,
+                    translation: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn ranges(feature: &MetaFeature) -> (i32, i32, Vec<(i32, i32)>) {
+        let location = &feature.location;
+        (location.start, location.end, location.segments.iter().map(|s| (s.start, s.end)).collect())
+    }
+
+    #[test]
+    fn slices_across_origin() {
+        let wrapping = Location { segments: vec![], ..location(&[(18, 2)], true) };
+        let part = part(true, vec![("inside", wrapping), ("cut", location(&[(3, 8)], true))]);
+
+        let sliced = sliced(&part, 15, 10);
+        assert_eq!(sliced.sequence, "CCGGAATGCC");
+        assert!(!sliced.metadata.circular);
+        assert_eq!(sliced.features[0].id, "inside");
+        assert_eq!((sliced.features[0].location.start, sliced.features[0].location.end), (3, 7));
+        assert_eq!(ranges(&sliced.features[1]), (8, 10, vec![(8, 10)]));
+        assert!(!sliced.features[1].location.partial_start && sliced.features[1].location.partial_end);
+        for feature in &sliced.features {
+            assert!(sliced.sequence.contains(&feature_sequence(&sliced.sequence, &feature.location)));
+        }
+    }
+
+    #[test]
+    fn clips_features_at_both_ends() {
+        let part = part(false, vec![("join", location(&[(3, 8), (11, 18)], true))]);
+        let sliced = sliced(&part, 4, 10);
+        let feature = &sliced.features[0];
+        assert_eq!(sliced.sequence, "CGTAAGCTTG");
+        assert_eq!(feature.id, "join");
+        assert_eq!(ranges(feature), (1, 10, vec![(1, 4), (7, 10)]));
+        assert!(feature.location.partial_start && feature.location.partial_end);
+    }
+
+    #[test]
+    fn rotates_at_first_and_last_position() {
+        let features = vec![("a", location(&[(3, 8)], true)), ("b", location(&[(19, 20)], false))];
+        let part = part(true, features);
+
+        let unchanged = rotated(&part, 0);
+        assert_eq!(unchanged.sequence, part.sequence);
+        assert_eq!(ranges(&unchanged.features[0]), (3, 8, vec![(3, 8)]));
+        assert_eq!(ranges(&unchanged.features[1]), (19, 20, vec![(19, 20)]));
+
+        // Position 20 becomes 1, so `b` now crosses the origin
+        let last = rotated(&part, SEQUENCE.len() - 1);
+        assert_eq!(last.sequence, "AATGCCGTAAGCTTGACCGG");
+        assert_eq!(ranges(&last.features[0]), (4, 9, vec![(4, 9)]));
+        assert_eq!(ranges(&last.features[1]), (20, 1, vec![(20, 20), (1, 1)]));
+        assert_eq!(last.features[1].id, "b");
+        for (before, after) in part.features.iter().zip(&last.features) {
+            assert_eq!(
+                feature_sequence(&part.sequence, &before.location),
+                feature_sequence(&last.sequence, &after.location)
+            );
+        }
+    }
+
+    #[test]
+    fn reverse_complements_joins() {
+        let mut join = location(&[(2, 4), (8, 10)], true);
+        join.partial_start = true;
+        let part = part(false, vec![("join", join)]);
+
+        let reversed = reverse_complemented(&part);
+        let feature = &reversed.features[0];
+        assert_eq!(reversed.sequence, "TCCGGTCAAGCTTACGGCAT");
+        assert_eq!(feature.id, "join");
+        assert_eq!(ranges(feature), (11, 19, vec![(11, 13), (17, 19)]));
+        assert!(!feature.location.forward && feature.location.segments.iter().all(|s| !s.forward));
+        assert!(!feature.location.partial_start && feature.location.partial_end);
+        assert_eq!(
+            feature_sequence(&reversed.sequence, &feature.location),
+            feature_sequence(&part.sequence, &part.features[0].location)
+        );
+    }
+}
@@ -1,14 +1,19 @@
+use std::collections::HashMap;
 use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 
 use chrono::Utc;
 use rusqlite::{params, Connection, OptionalExtension};
 
-use crate::types::Biobrick;
+use crate::hnsw::HnswParams;
+use crate::semantic::{self, EmbeddingIndex};
+use crate::sequence::{codon_usage, expression, similarity};
+use crate::types::{Biobrick, CacheSearchParams, ExpressionSort};
 
 #[derive(Clone)]
 pub struct SqliteCache {
     connection: Arc<Mutex<Connection>>,
+    embeddings: Arc<RwLock<EmbeddingIndex>>,
 }
 
 impl SqliteCache {
@@ -21,8 +26,12 @@ impl SqliteCache {
         let connection = Connection::open(path)?;
         let cache = Self {
             connection: Arc::new(Mutex::new(connection)),
+            embeddings: Arc::new(RwLock::new(EmbeddingIndex::new(HnswParams::from_env()))),
         };
         cache.init()?;
+        cache.sync_fts()?;
+        cache.sync_kmers()?;
+        cache.sync_expression()?;
         Ok(cache)
     }
 
@@ -36,6 +45,44 @@ impl SqliteCache {
                 biobrick_json TEXT NOT NULL,
                 cached_at TEXT NOT NULL
             );
+            CREATE TABLE IF NOT EXISTS parts_embeddings (
+                id_normalized TEXT PRIMARY KEY,
+                model TEXT NOT NULL,
+                text_hash TEXT NOT NULL,
+                embedding BLOB NOT NULL
+            );
+            CREATE VIRTUAL TABLE IF NOT EXISTS parts_fts USING fts5(
+                id_normalized UNINDEXED,
+                part_id,
+                name,
+                description,
+                authors,
+                features
+            );
+            CREATE TABLE IF NOT EXISTS parts_kmers (
+                kmer INTEGER NOT NULL,
+                id_normalized TEXT NOT NULL,
+                position INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS parts_kmers_kmer ON parts_kmers (kmer);
+            CREATE INDEX IF NOT EXISTS parts_kmers_id ON parts_kmers (id_normalized);
+            CREATE TABLE IF NOT EXISTS parts_graph (
+                position INTEGER PRIMARY KEY,
+                id_normalized TEXT NOT NULL,
+                links BLOB NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS parts_expression (
+                id_normalized TEXT PRIMARY KEY,
+                feature TEXT,
+                enc REAL
+            );
+            CREATE TABLE IF NOT EXISTS parts_expression_hosts (
+                id_normalized TEXT NOT NULL,
+                host TEXT NOT NULL,
+                cai REAL NOT NULL,
+                tai REAL NOT NULL,
+                PRIMARY KEY (id_normalized, host)
+            );
             CREATE TABLE IF NOT EXISTS usage_log (
                 ip TEXT NOT NULL,
                 date TEXT NOT NULL,
@@ -63,12 +110,20 @@ impl SqliteCache {
     }
 
     pub fn put_part(&self, id_normalized: &str, biobrick: &Biobrick) -> Result<(), rusqlite::Error> {
+        self.store_part(id_normalized, biobrick)?;
+        semantic::index_part(self, id_normalized, biobrick)
+    }
+
+    /// Caches `biobrick` with its full-text, k-mer and expression indexes, in one transaction so
+    /// a failure leaves no partial index rows behind.
+    fn store_part(&self, id_normalized: &str, biobrick: &Biobrick) -> Result<(), rusqlite::Error> {
         let payload = serde_json::to_string(biobrick)
             .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
         let cached_at = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
 
-        let connection = self.connection.lock().unwrap();
-        connection.execute(
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        transaction.execute(
             "
             INSERT INTO parts_cache (id_normalized, biobrick_json, cached_at)
             VALUES (?1, ?2, ?3)
@@ -78,6 +133,211 @@ impl SqliteCache {
             ",
             params![id_normalized, payload, cached_at],
         )?;
+        index_fts(&transaction, id_normalized, biobrick)?;
+        index_kmers(&transaction, id_normalized, biobrick)?;
+        index_expression(&transaction, id_normalized, biobrick)?;
+        transaction.commit()
+    }
+
+    /// Rebuilds the full-text index when it went out of sync with `parts_cache`.
+    fn sync_fts(&self) -> Result<(), rusqlite::Error> {
+        let entries = self.list_part_entries()?;
+        let connection = self.connection.lock().unwrap();
+        let indexed: i64 = connection.query_row("SELECT COUNT(*) FROM parts_fts", [], |row| row.get(0))?;
+        if indexed as usize == entries.len() {
+            return Ok(());
+        }
+
+        connection.execute("DELETE FROM parts_fts", [])?;
+        for (id_normalized, biobrick) in &entries {
+            index_fts(&connection, id_normalized, biobrick)?;
+        }
+        Ok(())
+    }
+
+    /// Indexes the minimizers of parts cached before `parts_kmers` existed.
+    fn sync_kmers(&self) -> Result<(), rusqlite::Error> {
+        let entries = self.list_part_entries()?;
+        let mut connection = self.connection.lock().unwrap();
+        let indexed = {
+            let mut statement = connection.prepare("SELECT DISTINCT id_normalized FROM parts_kmers")?;
+            let rows = statement.query_map([], |row| row.get::<_, String>(0))?;
+            rows.filter_map(|r| r.ok()).collect::<std::collections::HashSet<_>>()
+        };
+
+        let transaction = connection.transaction()?;
+        for (id_normalized, biobrick) in &entries {
+            if biobrick.sequence.len() >= similarity::K && !indexed.contains(id_normalized) {
+                index_kmers(&transaction, id_normalized, biobrick)?;
+            }
+        }
+        transaction.commit()
+    }
+
+    /// Scores the coding sequences of parts cached before `parts_expression` existed.
+    fn sync_expression(&self) -> Result<(), rusqlite::Error> {
+        let entries = self.list_part_entries()?;
+        let mut connection = self.connection.lock().unwrap();
+        let indexed = {
+            let mut statement = connection.prepare("SELECT id_normalized FROM parts_expression")?;
+            let rows = statement.query_map([], |row| row.get::<_, String>(0))?;
+            rows.filter_map(|r| r.ok()).collect::<std::collections::HashSet<_>>()
+        };
+
+        let transaction = connection.transaction()?;
+        for (id_normalized, biobrick) in &entries {
+            if !indexed.contains(id_normalized) {
+                index_expression(&transaction, id_normalized, biobrick)?;
+            }
+        }
+        transaction.commit()
+    }
+
+    /// Score of the longest coding sequence of each scored part in `host`, by ID. The effective
+    /// number of codons does not depend on the host.
+    pub fn expression_scores(&self, host: &str, sort: ExpressionSort) -> Result<HashMap<String, f64>, rusqlite::Error> {
+        let (query, host) = match sort {
+            ExpressionSort::Cai => ("SELECT id_normalized, cai FROM parts_expression_hosts WHERE host = ?1", Some(host)),
+            ExpressionSort::Tai => ("SELECT id_normalized, tai FROM parts_expression_hosts WHERE host = ?1", Some(host)),
+            ExpressionSort::Enc => ("SELECT id_normalized, enc FROM parts_expression WHERE enc IS NOT NULL", None),
+        };
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(query)?;
+        let rows = statement.query_map(rusqlite::params_from_iter(host), |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
+    /// Indexed occurrences of the given minimizer hashes, as (id, hash, position).
+    pub fn kmer_hits(&self, kmers: &[u64]) -> Result<Vec<(String, u64, usize)>, rusqlite::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut hits = Vec::new();
+        for chunk in kmers.chunks(500) {
+            let placeholders = vec!["?"; chunk.len()].join(",");
+            let mut statement = connection.prepare(&format!(
+                "SELECT id_normalized, kmer, position FROM parts_kmers WHERE kmer IN ({})",
+                placeholders
+            ))?;
+            let rows = statement.query_map(
+                rusqlite::params_from_iter(chunk.iter().map(|k| *k as i64)),
+                |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64, row.get::<_, i64>(2)? as usize)),
+            )?;
+            hits.extend(rows.filter_map(|r| r.ok()));
+        }
+        Ok(hits)
+    }
+
+    /// Full-text matches as (id, BM25 rank, highlighted snippet), best first. BM25 is negative,
+    /// lower is better.
+    pub fn lexical_search(&self, fts_query: &str, limit: usize) -> Result<Vec<(String, f64, String)>, rusqlite::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "
+            SELECT id_normalized,
+                   bm25(parts_fts, 0.0, 10.0, 5.0, 1.0, 2.0, 3.0) AS rank,
+                   snippet(parts_fts, -1, '<mark>', '</mark>', '…', 12)
+            FROM parts_fts
+            WHERE parts_fts MATCH ?1
+            ORDER BY rank
+            LIMIT ?2
+            ",
+        )?;
+        let rows = statement.query_map(params![fts_query, limit.min(i64::MAX as usize) as i64], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?;
+
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
+    pub fn embeddings(&self) -> &RwLock<EmbeddingIndex> {
+        &self.embeddings
+    }
+
+    /// Model name and text hash of the stored embedding, if any.
+    pub fn get_embedding_version(&self, id_normalized: &str) -> Option<(String, String)> {
+        let connection = self.connection.lock().unwrap();
+        connection
+            .query_row(
+                "SELECT model, text_hash FROM parts_embeddings WHERE id_normalized = ?1",
+                params![id_normalized],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .ok()
+            .flatten()
+    }
+
+    pub fn put_embedding(&self, id_normalized: &str, model: &str, text_hash: &str, embedding: &[f32]) -> Result<(), rusqlite::Error> {
+        let blob = embedding.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<u8>>();
+
+        let connection = self.connection.lock().unwrap();
+        connection.execute(
+            "
+            INSERT INTO parts_embeddings (id_normalized, model, text_hash, embedding)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT(id_normalized) DO UPDATE SET
+                model = excluded.model,
+                text_hash = excluded.text_hash,
+                embedding = excluded.embedding
+            ",
+            params![id_normalized, model, text_hash, blob],
+        )?;
+        Ok(())
+    }
+
+    /// Stored embeddings computed with `model`, as (id, text hash, embedding).
+    pub fn list_embeddings(&self, model: &str) -> Result<Vec<(String, String, Vec<f32>)>, rusqlite::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT id_normalized, text_hash, embedding FROM parts_embeddings WHERE model = ?1",
+        )?;
+        let rows = statement.query_map(params![model], |row| {
+            let blob: Vec<u8> = row.get(2)?;
+            let embedding = blob
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect::<Vec<_>>();
+            Ok((row.get(0)?, row.get(1)?, embedding))
+        })?;
+
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
+    /// Saves HNSW nodes as (position, id, encoded links), in one transaction.
+    pub fn put_graph_nodes(&self, nodes: &[(usize, String, Vec<u8>)]) -> Result<(), rusqlite::Error> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        {
+            let mut statement = transaction.prepare(
+                "
+                INSERT INTO parts_graph (position, id_normalized, links)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT(position) DO UPDATE SET
+                    id_normalized = excluded.id_normalized,
+                    links = excluded.links
+                ",
+            )?;
+            for (position, id_normalized, links) in nodes {
+                statement.execute(params![*position as i64, id_normalized, links])?;
+            }
+        }
+        transaction.commit()
+    }
+
+    pub fn list_graph_nodes(&self) -> Result<Vec<(usize, String, Vec<u8>)>, rusqlite::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT position, id_normalized, links FROM parts_graph ORDER BY position",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)? as usize, row.get(1)?, row.get(2)?))
+        })?;
+
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
+    pub fn clear_graph(&self) -> Result<(), rusqlite::Error> {
+        let connection = self.connection.lock().unwrap();
+        connection.execute("DELETE FROM parts_graph", [])?;
         Ok(())
     }
 
@@ -148,6 +408,104 @@ impl SqliteCache {
         })
     }
 
+    /// Parts matching the structured filters of `params`, as (id, type canonical, provider
+    /// names), ordered by ID.
+    pub fn filter_parts(&self, params: &CacheSearchParams) -> Result<Vec<(String, String, Vec<String>)>, rusqlite::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "
+            SELECT id_normalized,
+                   json_extract(biobrick_json, '$.metadata.type.canonical'),
+                   (SELECT json_group_array(json_extract(p.value, '$.name'))
+                    FROM json_each(biobrick_json, '$.metadata.providers') p)
+            FROM parts_cache
+            WHERE (?1 IS NULL OR lower(?1) IN (
+                    lower(json_extract(biobrick_json, '$.metadata.type.canonical')),
+                    lower(json_extract(biobrick_json, '$.metadata.type.ontology')),
+                    lower(json_extract(biobrick_json, '$.metadata.type.slug'))))
+              AND (?2 IS NULL OR EXISTS (
+                    SELECT 1 FROM json_each(biobrick_json, '$.metadata.providers') p
+                    WHERE lower(json_extract(p.value, '$.name')) = lower(?2)))
+              AND (?3 IS NULL OR json_extract(biobrick_json, '$.metadata.size') >= ?3)
+              AND (?4 IS NULL OR json_extract(biobrick_json, '$.metadata.size') <= ?4)
+              AND (?5 IS NULL OR json_extract(biobrick_json, '$.metadata.circular') = ?5)
+              AND (?6 IS NULL OR EXISTS (
+                    SELECT 1 FROM json_each(biobrick_json, '$.metadata.authors') a
+                    WHERE json_extract(a.value, '$.name') LIKE '%' || ?6 || '%' ESCAPE '\\'))
+              AND (?7 IS NULL OR json_extract(biobrick_json, '$.metadata.creation') >= ?7)
+              AND (?8 IS NULL OR (json_extract(biobrick_json, '$.metadata.creation') != ''
+                   AND json_extract(biobrick_json, '$.metadata.creation') < ?8))
+              AND (?9 IS NULL OR EXISTS (
+                    SELECT 1 FROM json_each(biobrick_json, '$.features') f
+                    WHERE lower(?9) IN (
+                        lower(json_extract(f.value, '$.type.canonical')),
+                        lower(json_extract(f.value, '$.type.ontology')),
+                        lower(json_extract(f.value, '$.type.slug')))))
+            ORDER BY id_normalized
+            ",
+        )?;
+        let rows = statement.query_map(
+            params![
+                params.r#type,
+                params.provider,
+                params.min_size,
+                params.max_size,
+                params.circular,
+                params.author.as_deref().map(escape_like),
+                params.created_after,
+                params.created_before,
+                params.has_feature
+            ],
+            |row| {
+                let providers: String = row.get(2)?;
+                Ok((
+                    row.get(0)?,
+                    row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    serde_json::from_str::<Vec<Option<String>>>(&providers)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .flatten()
+                        .collect(),
+                ))
+            },
+        )?;
+
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
+    /// Parts whose type is one of the given ontology canonicals.
+    pub fn list_parts_by_type(&self, types: &[&str]) -> Result<Vec<(String, Biobrick)>, rusqlite::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "
+            SELECT id_normalized, biobrick_json FROM parts_cache
+            WHERE json_extract(biobrick_json, '$.metadata.type.canonical') IN (SELECT value FROM json_each(?1))
+            ",
+        )?;
+        let types = serde_json::to_string(types).unwrap_or_default();
+        let rows = statement.query_map(params![types], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+
+        let parts = rows
+            .filter_map(|r| r.ok())
+            .filter_map(|(id, json)| serde_json::from_str::<Biobrick>(&json).ok().map(|b| (id, b)))
+            .collect::<Vec<_>>();
+
+        Ok(parts)
+    }
+
+    pub fn list_part_entries(&self) -> Result<Vec<(String, Biobrick)>, rusqlite::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare("SELECT id_normalized, biobrick_json FROM parts_cache")?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+
+        let parts = rows
+            .filter_map(|r| r.ok())
+            .filter_map(|(id, json)| serde_json::from_str::<Biobrick>(&json).ok().map(|b| (id, b)))
+            .collect::<Vec<_>>();
+
+        Ok(parts)
+    }
+
     pub fn list_parts(&self) -> Result<Vec<Biobrick>, rusqlite::Error> {
         let connection = self.connection.lock().unwrap();
         let mut statement = connection.prepare("SELECT biobrick_json FROM parts_cache")?;
@@ -161,3 +519,148 @@ impl SqliteCache {
         Ok(parts)
     }
 }
+
+fn index_fts(connection: &Connection, id_normalized: &str, biobrick: &Biobrick) -> Result<(), rusqlite::Error> {
+    let authors = biobrick
+        .metadata
+        .authors
+        .iter()
+        .map(|a| a.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let features = biobrick
+        .features
+        .iter()
+        .map(|f| format!("{} {}", f.id, f.name))
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    connection.execute("DELETE FROM parts_fts WHERE id_normalized = ?1", params![id_normalized])?;
+    connection.execute(
+        "
+        INSERT INTO parts_fts (id_normalized, part_id, name, description, authors, features)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        ",
+        params![
+            id_normalized,
+            biobrick.metadata.id,
+            biobrick.metadata.name,
+            biobrick.metadata.description,
+            authors,
+            features
+        ],
+    )?;
+    Ok(())
+}
+
+fn index_kmers(connection: &Connection, id_normalized: &str, biobrick: &Biobrick) -> Result<(), rusqlite::Error> {
+    connection.execute("DELETE FROM parts_kmers WHERE id_normalized = ?1", params![id_normalized])?;
+    let mut statement = connection.prepare_cached(
+        "INSERT INTO parts_kmers (kmer, id_normalized, position) VALUES (?1, ?2, ?3)",
+    )?;
+    for (kmer, position) in similarity::minimizers(&biobrick.sequence, biobrick.metadata.circular) {
+        statement.execute(params![kmer as i64, id_normalized, position as i64])?;
+    }
+    Ok(())
+}
+
+/// `text` matched literally by LIKE, with `\` as the escape character.
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Stores the scores of the part's longest coding sequence, its host-independent ENC once and
+/// CAI and tAI for each host. Parts without one still get a row, so they are not scored again.
+fn index_expression(connection: &Connection, id_normalized: &str, biobrick: &Biobrick) -> Result<(), rusqlite::Error> {
+    connection.execute("DELETE FROM parts_expression WHERE id_normalized = ?1", params![id_normalized])?;
+    connection.execute("DELETE FROM parts_expression_hosts WHERE id_normalized = ?1", params![id_normalized])?;
+    let mut statement = connection.prepare_cached(
+        "INSERT INTO parts_expression_hosts (id_normalized, host, cai, tai) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    let mut longest = None;
+    for host in codon_usage::HOSTS {
+        let report = expression::score(biobrick, host);
+        if let Some(cds) = report.coding_sequences.into_iter().max_by_key(|c| c.codons) {
+            statement.execute(params![id_normalized, host.host, cds.cai, cds.tai])?;
+            longest = Some(cds);
+        }
+    }
+    connection.execute(
+        "INSERT INTO parts_expression (id_normalized, feature, enc) VALUES (?1, ?2, ?3)",
+        params![id_normalized, longest.as_ref().map(|c| &c.feature), longest.as_ref().map(|c| c.enc)],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ontology::find_canonical;
+    use crate::semantic::{cache_search, Cursor};
+    use crate::types::{Author, MetaBiobrick};
+
+    fn part(id: &str, author: &str, circular: bool) -> Biobrick {
+        Biobrick {
+            metadata: MetaBiobrick {
+                id: id.to_string(),
+                name: id.to_string(),
+                description: String::new(),
+                creation: String::new(),
+                size: 12,
+                circular,
+                r#type: find_canonical("promoter").unwrap().into(),
+                authors: vec![Author { name: author.to_string(), role: None }],
+                providers: vec![],
+            },
+            sequence: "TTGACAATTAAT".to_string(),
+            features: vec![],
+        }
+    }
+
+    fn search(cache: &SqliteCache, params: serde_json::Value, cursor: Option<&Cursor>) -> (Vec<String>, Option<String>) {
+        let params: CacheSearchParams = serde_json::from_value(params).unwrap();
+        let response = cache_search(cache, &params, cursor).unwrap();
+        let ids = response.results.into_iter().map(|hit| hit.biobrick.metadata.id).collect();
+        (ids, response.next_cursor)
+    }
+
+    #[test]
+    fn filters_and_pages_with_cursor() {
+        let cache = SqliteCache::new(":memory:").unwrap();
+        let parts = [
+            ("p1", "Jane_Doe", true),
+            ("p2", "JaneXDoe", true),
+            ("p3", "100% Lab", true),
+            ("p4", "1000 Lab", true),
+            ("p5", "Roe", false),
+            ("p6", "Roe", true),
+        ];
+        for (id, author, circular) in parts {
+            cache.store_part(id, &part(id, author, circular)).unwrap();
+        }
+
+        // LIKE wildcards in author names match literally
+        assert_eq!(search(&cache, serde_json::json!({ "author": "e_D" }), None).0, ["p1"]);
+        assert_eq!(search(&cache, serde_json::json!({ "author": "0%" }), None).0, ["p3"]);
+
+        let params = serde_json::json!({ "circular": true, "n": 2 });
+        let mut pages = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (ids, next) = search(&cache, params.clone(), cursor.as_ref());
+            pages.push(ids);
+            match next {
+                Some(next) => cursor = Some(Cursor::decode(&next).unwrap()),
+                None => break,
+            }
+        }
+        assert_eq!(pages, [vec!["p1", "p2"], vec!["p3", "p4"], vec!["p6"]]);
+    }
+}
@@ -0,0 +1,27 @@
+//! FASTA and multi-FASTA exporter for Biobrick.
+
+use crate::types::Biobrick;
+
+const LINE_WIDTH: usize = 60;
+
+pub fn to_fasta(biobrick: &Biobrick) -> String {
+    let metadata = &biobrick.metadata;
+    let mut fasta = format!(
+        ">{} {} [size={}] [topology={}]\n",
+        metadata.id.split_whitespace().collect::<Vec<_>>().join("_"),
+        metadata.name.split_whitespace().collect::<Vec<_>>().join(" "),
+        metadata.size,
+        if metadata.circular { "circular" } else { "linear" },
+    );
+
+    let sequence = biobrick.sequence.to_lowercase();
+    for line in sequence.as_bytes().chunks(LINE_WIDTH) {
+        fasta.push_str(&String::from_utf8_lossy(line));
+        fasta.push('\n');
+    }
+    fasta
+}
+
+pub fn to_multi_fasta(biobricks: &[Biobrick]) -> String {
+    biobricks.iter().map(to_fasta).collect()
+}
@@ -0,0 +1,107 @@
+//! Simulated agarose gel, as an SVG with a ladder lane and a sample lane. Migration is linear in
+//! the logarithm of fragment size over the range of the ladder; brightness follows band mass.
+
+pub struct Ladder {
+    pub name: &'static str,
+    pub sizes: &'static [usize],
+}
+
+pub const LADDERS: &[Ladder] = &[
+    Ladder {
+        name: "1kb",
+        sizes: &[10002, 8001, 6001, 5001, 4001, 3001, 2000, 1500, 1000, 517, 500],
+    },
+    Ladder {
+        name: "1kb-plus",
+        sizes: &[10002, 8001, 6001, 5001, 4001, 3001, 2000, 1500, 1200, 1000, 900, 800, 700, 600, 500, 400, 300, 200, 100],
+    },
+    Ladder {
+        name: "100bp",
+        sizes: &[1517, 1200, 1000, 900, 800, 700, 600, 500, 400, 300, 200, 100],
+    },
+];
+
+pub fn find_ladder(name: &str) -> Option<&'static Ladder> {
+    LADDERS.iter().find(|l| l.name.eq_ignore_ascii_case(name.trim()))
+}
+
+const WIDTH: f64 = 240.0;
+const HEIGHT: f64 = 420.0;
+const TOP: f64 = 50.0;
+const BOTTOM: f64 = 400.0;
+const LANE_WIDTH: f64 = 60.0;
+const LADDER_X: f64 = 70.0;
+const SAMPLE_X: f64 = 160.0;
+
+pub fn to_svg(label: &str, sizes: &[usize], ladder: &Ladder) -> String {
+    let largest = *ladder.sizes.iter().max().unwrap_or(&10_000) as f64;
+    let smallest = *ladder.sizes.iter().min().unwrap_or(&100) as f64;
+    // Sizes beyond the ladder pile up at its ends
+    let y = |size: usize| {
+        let size = (size.max(1) as f64).clamp(smallest / 1.5, largest * 1.5);
+        let fraction = (largest * 1.5).ln() - size.ln();
+        TOP + fraction / ((largest * 1.5).ln() - (smallest / 1.5).ln()) * (BOTTOM - TOP)
+    };
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n\
+         <rect width=\"{w}\" height=\"{h}\" fill=\"#1b1b2f\"/>\n",
+        w = WIDTH,
+        h = HEIGHT
+    );
+    for (x, text) in [(LADDER_X, ladder.name), (SAMPLE_X, label)] {
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"6\" fill=\"#0d0d18\"/>\n",
+            x - LANE_WIDTH / 2.0,
+            TOP - 16.0,
+            LANE_WIDTH
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" fill=\"#cccccc\" font-family=\"sans-serif\" font-size=\"11\" text-anchor=\"middle\">{}</text>\n",
+            x,
+            TOP - 24.0,
+            escape(text)
+        ));
+    }
+
+    for size in ladder.sizes {
+        svg.push_str(&band(LADDER_X, y(*size), 0.8));
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{:.1}\" fill=\"#999999\" font-family=\"sans-serif\" font-size=\"9\" text-anchor=\"end\">{}</text>\n",
+            LADDER_X - LANE_WIDTH / 2.0 - 4.0,
+            y(*size) + 3.0,
+            size
+        ));
+    }
+
+    // Co-migrating fragments add up; opacity is relative to the heaviest band
+    let mut bands: Vec<(usize, usize)> = Vec::new();
+    for size in sizes {
+        match bands.iter_mut().find(|(s, _)| s == size) {
+            Some(band) => band.1 += 1,
+            None => bands.push((*size, 1)),
+        }
+    }
+    let heaviest = bands.iter().map(|(s, n)| s * n).max().unwrap_or(1) as f64;
+    for (size, count) in bands {
+        let opacity = 0.3 + 0.7 * (size * count) as f64 / heaviest;
+        svg.push_str(&band(SAMPLE_X, y(size), opacity));
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn band(x: f64, y: f64, opacity: f64) -> String {
+    format!(
+        "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{}\" height=\"4\" rx=\"1\" fill=\"#f4f4ff\" fill-opacity=\"{:.2}\"/>\n",
+        x - LANE_WIDTH / 2.0,
+        y - 2.0,
+        LANE_WIDTH,
+        opacity
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
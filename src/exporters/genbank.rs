@@ -0,0 +1,300 @@
+//! GenBank flat file exporter for Biobrick, readable back with `parse_genbank_raw`.
+
+use crate::parsers::genbank::STRUCTURED_COMMENT;
+use crate::types::{Biobrick, Location};
+
+const LINE_WIDTH: usize = 80;
+const HEADER_INDENT: &str = "            ";
+const QUALIFIER_INDENT: &str = "                     ";
+
+pub fn to_genbank(biobrick: &Biobrick) -> String {
+    let mut gb = String::new();
+    let metadata = &biobrick.metadata;
+    let size = biobrick.sequence.len();
+
+    let locus_name: String = metadata
+        .id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    let topology = if metadata.circular { "circular" } else { "linear" };
+    let date = chrono::DateTime::parse_from_rfc3339(&metadata.creation)
+        .map(|d| d.format("%d-%b-%Y").to_string().to_uppercase())
+        .unwrap_or_default();
+    gb.push_str(
+        format!("LOCUS       {:<16} {:>11} bp    DNA     {:<8} SYN {}", locus_name, size, topology, date)
+            .trim_end(),
+    );
+    gb.push('\n');
+
+    let mut definition = if metadata.description.is_empty() {
+        metadata.name.clone()
+    } else {
+        format!("{}. {}", metadata.name.trim_end_matches('.'), metadata.description)
+    };
+    if !definition.ends_with('.') {
+        definition.push('.');
+    }
+    push_header(&mut gb, "DEFINITION", &definition);
+    push_header(&mut gb, "ACCESSION", &locus_name);
+    push_header(&mut gb, "KEYWORDS", ".");
+    push_header(&mut gb, "SOURCE", "synthetic DNA construct");
+    push_header(&mut gb, "  ORGANISM", "synthetic DNA construct");
+
+    if !metadata.authors.is_empty() {
+        let names = metadata.authors.iter().map(|a| a.name.as_str()).collect::<Vec<_>>();
+        let authors = match names.split_last() {
+            Some((last, others)) if !others.is_empty() => format!("{} and {}", others.join(", "), last),
+            _ => names.join(", "),
+        };
+        push_header(&mut gb, "REFERENCE", &format!("1  (bases 1 to {})", size));
+        push_header(&mut gb, "  AUTHORS", &authors);
+        push_header(&mut gb, "  TITLE", &metadata.name);
+        push_header(&mut gb, "  JOURNAL", "Exported from Bricks.bio");
+    }
+
+    let mut comment = vec![format!("##{}-START##", STRUCTURED_COMMENT)];
+    comment.push(format!("Name        :: {}", metadata.name));
+    if !metadata.description.is_empty() {
+        comment.push(format!("Description :: {}", metadata.description));
+    }
+    comment.push(format!("Type        :: {}", metadata.r#type.canonical));
+    if !metadata.creation.is_empty() {
+        comment.push(format!("Creation    :: {}", metadata.creation));
+    }
+    for provider in &metadata.providers {
+        comment.push(format!("Provider    :: {} | {} | {}", provider.name, provider.link, provider.date));
+    }
+    for author in &metadata.authors {
+        match &author.role {
+            Some(role) => comment.push(format!("Author      :: {} | {}", author.name, role)),
+            None => comment.push(format!("Author      :: {}", author.name)),
+        }
+    }
+    for (i, feature) in biobrick.features.iter().enumerate() {
+        comment.push(format!("Feature     :: {} | {}", i + 1, feature.id));
+    }
+    comment.push(format!("##{}-END##", STRUCTURED_COMMENT));
+    for (i, line) in comment.iter().enumerate() {
+        let keyword = if i == 0 { "COMMENT" } else { "" };
+        push_header(&mut gb, keyword, line);
+    }
+
+    gb.push_str("FEATURES             Location/Qualifiers\n");
+    for feature in &biobrick.features {
+        let location = location_to_string(&feature.location, size as i32);
+        let mut lines = wrap_on(&location, ',', LINE_WIDTH - QUALIFIER_INDENT.len()).into_iter();
+        gb.push_str(&format!("     {:<16}{}\n", feature_key(&feature.r#type.canonical), lines.next().unwrap_or_default()));
+        for line in lines {
+            gb.push_str(&format!("{}{}\n", QUALIFIER_INDENT, line));
+        }
+        push_qualifier(&mut gb, "label", &feature.name);
+        push_qualifier(&mut gb, "note", &feature.r#type.canonical);
+        if let Some(translation) = &feature.translation {
+            gb.push_str(&format!("{}/transl_table={}\n", QUALIFIER_INDENT, translation.table));
+            push_translation(&mut gb, translation.reported.as_deref().unwrap_or(&translation.protein));
+        }
+    }
+
+    gb.push_str("ORIGIN\n");
+    let sequence = biobrick.sequence.to_lowercase();
+    for (line_idx, line) in sequence.as_bytes().chunks(60).enumerate() {
+        let blocks = line
+            .chunks(10)
+            .map(|block| String::from_utf8_lossy(block).into_owned())
+            .collect::<Vec<_>>()
+            .join(" ");
+        gb.push_str(&format!("{:>9} {}\n", line_idx * 60 + 1, blocks));
+    }
+    gb.push_str("//\n");
+    gb
+}
+
+/// INSDC location string, e.g. `complement(join(<1..200,300..>450))`. Ranges spanning the
+/// origin of the `length` bp sequence are written as a join, as INSDC requires.
+pub fn location_to_string(location: &Location, length: i32) -> String {
+    let ranges = location.linear_ranges(length);
+    let last = ranges.len().saturating_sub(1);
+    let segments = ranges
+        .iter()
+        .enumerate()
+        .map(|(i, segment)| {
+            let start = if i == 0 && location.partial_start { format!("<{}", segment.start) } else { segment.start.to_string() };
+            let end = if i == last && location.partial_end { format!(">{}", segment.end) } else { segment.end.to_string() };
+            if segment.start == segment.end && start == end {
+                start
+            } else {
+                format!("{}..{}", start, end)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let joined = if segments.len() > 1 {
+        format!("join({})", segments.join(","))
+    } else {
+        segments.join(",")
+    };
+    if location.forward {
+        joined
+    } else {
+        format!("complement({})", joined)
+    }
+}
+
+fn feature_key(canonical: &str) -> &'static str {
+    match canonical {
+        "coding-sequence" => "CDS",
+        "ribosome-entry-site" => "RBS",
+        "promoter" => "promoter",
+        "terminator" => "terminator",
+        "origin-of-replication" => "rep_origin",
+        "primer-binding-site" => "primer_bind",
+        "poly-a-site" => "polyA_site",
+        "operator" => "protein_bind",
+        _ => "misc_feature",
+    }
+}
+
+fn push_header(gb: &mut String, keyword: &str, text: &str) {
+    let lines = wrap_on(text, ' ', LINE_WIDTH - HEADER_INDENT.len());
+    for (i, line) in lines.iter().enumerate() {
+        let keyword = if i == 0 { keyword } else { "" };
+        gb.push_str(&format!("{:<12}{}\n", keyword, line));
+    }
+}
+
+fn push_qualifier(gb: &mut String, key: &str, value: &str) {
+    let qualifier = format!("/{}=\"{}\"", key, value.replace('"', "\"\""));
+    for line in wrap_on(&qualifier, ' ', LINE_WIDTH - QUALIFIER_INDENT.len()) {
+        gb.push_str(&format!("{}{}\n", QUALIFIER_INDENT, line));
+    }
+}
+
+/// `/translation` is wrapped anywhere, as it has no spaces.
+fn push_translation(gb: &mut String, protein: &str) {
+    let qualifier = format!("/translation=\"{}\"", protein);
+    let width = LINE_WIDTH - QUALIFIER_INDENT.len();
+    for line in qualifier.as_bytes().chunks(width) {
+        gb.push_str(&format!("{}{}\n", QUALIFIER_INDENT, String::from_utf8_lossy(line)));
+    }
+}
+
+/// Wraps `text` after `separator` so lines fit in `width`; a single token longer than
+/// `width` is kept whole. Space separators are dropped at line ends, others are kept.
+fn wrap_on(text: &str, separator: char, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for token in text.split_inclusive(separator) {
+        let token_len = if separator == ' ' { token.trim_end().len() + 1 } else { token.len() };
+        if !current.is_empty() && current.trim_end().len() + token_len > width {
+            lines.push(current.trim_end().to_string());
+            current.clear();
+        }
+        current.push_str(token);
+    }
+    if !current.trim_end().is_empty() || lines.is_empty() {
+        lines.push(current.trim_end().to_string());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ontology::find_canonical;
+    use crate::parsers::genbank::{genbank_to_biobrick, parse_genbank_raw};
+    use crate::types::{Author, MetaBiobrick, MetaFeature, MetaProvider, Segment, Translation};
+
+    fn feature(id: &str, name: &str, canonical: &str, location: Location, translation: Option<Translation>) -> MetaFeature {
+        MetaFeature {
+            id: id.to_string(),
+            name: name.to_string(),
+            r#type: find_canonical(canonical).unwrap().into(),
+            location,
+            translation,
+        }
+    }
+
+    #[test]
+    fn round_trips_through_parser() {
+        let sequence = "atgaccatgattacggattcactggccgtcgttttacaacgtcgtgactgggaaaaccctggcgttacccaacttaatcgcc".repeat(2);
+        // Partial CDS on the reverse strand, over the origin, in two segments
+        let cds = Location {
+            start: 150,
+            end: 20,
+            forward: false,
+            segments: vec![
+                Segment { start: 150, end: 164, forward: false },
+                Segment { start: 1, end: 20, forward: false },
+            ],
+            partial_start: true,
+            partial_end: true,
+        };
+        let promoter = Location {
+            start: 30,
+            end: 64,
+            forward: true,
+            segments: vec![Segment { start: 30, end: 64, forward: true }],
+            partial_start: false,
+            partial_end: false,
+        };
+        let wrapping = Location {
+            start: 140,
+            end: 10,
+            forward: true,
+            segments: vec![],
+            partial_start: false,
+            partial_end: false,
+        };
+        let biobrick = Biobrick {
+            metadata: MetaBiobrick {
+                id: "BBa_K000001".to_string(),
+                name: "lacZ \"alpha\" fragment".to_string(),
+                description: "A reporter, with a \"quoted\" description long enough to be wrapped over several COMMENT lines of the file".to_string(),
+                creation: "2021-03-04T05:06:07.000Z".to_string(),
+                size: sequence.len() as i32,
+                circular: true,
+                r#type: find_canonical("coding-sequence").unwrap().into(),
+                authors: vec![
+                    Author { name: "Doe, Jane".to_string(), role: Some("designer".to_string()) },
+                    Author { name: "Roe R".to_string(), role: None },
+                ],
+                providers: vec![MetaProvider {
+                    name: "iGEM Registry".to_string(),
+                    link: "https://parts.igem.org/Part:BBa_K000001".to_string(),
+                    date: "2024-01-02T03:04:05.000Z".to_string(),
+                }],
+            },
+            sequence,
+            features: vec![
+                feature(
+                    "lacZ_alpha",
+                    "lacZ \"alpha\" with a name long enough to wrap on the qualifier lines",
+                    "coding-sequence",
+                    cds,
+                    Some(Translation {
+                        table: 11,
+                        reported: Some("MTMITDSLAVVLQRRDWENPGVTQLNRLAAHPPFASWRNSEEARTDRPSQQLRSLNGEWRLMRYFLLTHLCGISHRIWCTLSTICSDAA".to_string()),
+                        ..Default::default()
+                    }),
+                ),
+                feature("custom id", "Plac", "promoter", promoter, None),
+                // A single range over the origin, as annotation and rotation produce
+                feature("term", "rrnB T1", "terminator", wrapping, None),
+            ],
+        };
+
+        let metadata = &biobrick.metadata;
+        let provider = &metadata.providers[0];
+        let genbank = to_genbank(&biobrick);
+        assert!(genbank.contains("terminator      join(140..164,1..10)\n"));
+        let parsed = parse_genbank_raw(&genbank).unwrap();
+        let round_trip = genbank_to_biobrick(&metadata.id, &provider.name, &provider.link, parsed, provider.date.clone());
+        let mut expected = serde_json::to_value(&biobrick).unwrap();
+        expected["features"][2]["location"]["segments"] = serde_json::json!([
+            { "start": 140, "end": 164, "forward": true },
+            { "start": 1, "end": 10, "forward": true },
+        ]);
+        assert_eq!(serde_json::to_value(&round_trip).unwrap(), expected);
+    }
+}
@@ -1 +1,4 @@
+pub mod fasta;
+pub mod gel;
+pub mod genbank;
 pub mod sbol;
@@ -1,63 +1,380 @@
+//! SBOL3 exporter for Biobrick, serialized as RDF/XML, Turtle or JSON-LD.
+
+use std::collections::HashSet;
+
+use serde_json::{json, Map, Value};
+
 use crate::types::Biobrick;
 
-/// SBOL 2.0/3.0 simplistic exporter for Biobrick.
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const SBOL: &str = "http://sbols.org/v3#";
+const PROV: &str = "http://www.w3.org/ns/prov#";
+const DCTERMS: &str = "http://purl.org/dc/terms/";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+const PREFIXES: &[(&str, &str)] = &[
+    ("rdf", RDF),
+    ("sbol", SBOL),
+    ("prov", PROV),
+    ("dcterms", DCTERMS),
+    ("xsd", XSD),
+];
+
+pub const DEFAULT_NAMESPACE: &str = "https://bricks.bio/sbol/";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SbolFormat {
+    RdfXml,
+    Turtle,
+    JsonLd,
+}
+
+impl SbolFormat {
+    /// Picks a serialization from a `format` query value or an `Accept` header, RDF/XML by default.
+    pub fn negotiate(format: Option<&str>, accept: Option<&str>) -> SbolFormat {
+        let wanted = format.or(accept).unwrap_or_default().to_lowercase();
+        if wanted.contains("turtle") || wanted.contains("ttl") {
+            SbolFormat::Turtle
+        } else if wanted.contains("ld+json") || wanted.contains("jsonld") || wanted.contains("json") {
+            SbolFormat::JsonLd
+        } else {
+            SbolFormat::RdfXml
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            SbolFormat::RdfXml => "application/rdf+xml",
+            SbolFormat::Turtle => "text/turtle",
+            SbolFormat::JsonLd => "application/ld+json",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Object {
+    Iri(String),
+    Literal(String),
+    Integer(i64),
+    DateTime(String),
+}
+
+struct Triple {
+    subject: String,
+    predicate: String,
+    object: Object,
+}
+
+#[derive(Default)]
+struct Graph {
+    triples: Vec<Triple>,
+}
+
+impl Graph {
+    fn add(&mut self, subject: &str, predicate: String, object: Object) {
+        self.triples.push(Triple {
+            subject: subject.to_string(),
+            predicate,
+            object,
+        });
+    }
+
+    fn subjects(&self) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        self.triples
+            .iter()
+            .map(|t| t.subject.as_str())
+            .filter(|s| seen.insert(*s))
+            .collect()
+    }
+
+    fn about<'a>(&'a self, subject: &'a str) -> impl Iterator<Item = &'a Triple> + 'a {
+        self.triples.iter().filter(move |t| t.subject == subject)
+    }
+}
+
+fn sbol(name: &str) -> String {
+    format!("{}{}", SBOL, name)
+}
+
+fn iri(value: String) -> Object {
+    Object::Iri(value)
+}
+
+/// SBOL3 displayIds are alphanumeric or underscores and do not start with a digit.
+pub fn display_id(id: &str) -> String {
+    let mut display: String = id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !display.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        display.insert(0, '_');
+    }
+    display
+}
+
+fn so_term(ontology: Option<&str>, canonical: &str) -> String {
+    match ontology {
+        Some(so) => format!("https://identifiers.org/{}", so),
+        // Types without an SO term (composite, backbone, ...) keep their identity as an iGEM part type
+        None => format!("http://wiki.synbiohub.org/wiki/Terms/igem#partType/{}", canonical),
+    }
+}
+
+fn build_graph(biobrick: &Biobrick, namespace: &str) -> Graph {
+    let mut graph = Graph::default();
+    let metadata = &biobrick.metadata;
+    let namespace = if namespace.ends_with('/') || namespace.ends_with('#') {
+        namespace.to_string()
+    } else {
+        format!("{}/", namespace)
+    };
+
+    let component_id = display_id(&metadata.id);
+    let component = format!("{}{}", namespace, component_id);
+    let sequence_id = format!("{}_sequence", component_id);
+    let sequence = format!("{}{}", namespace, sequence_id);
+
+    graph.add(&component, format!("{}type", RDF), iri(sbol("Component")));
+    graph.add(&component, sbol("displayId"), Object::Literal(component_id.clone()));
+    graph.add(&component, sbol("hasNamespace"), iri(namespace.clone()));
+    if !metadata.name.is_empty() {
+        graph.add(&component, sbol("name"), Object::Literal(metadata.name.clone()));
+    }
+    if !metadata.description.is_empty() {
+        graph.add(&component, sbol("description"), Object::Literal(metadata.description.clone()));
+    }
+    graph.add(&component, sbol("type"), iri("https://identifiers.org/SBO:0000251".to_string()));
+    let topology = if metadata.circular { "SO:0000988" } else { "SO:0000987" };
+    graph.add(&component, sbol("type"), iri(format!("https://identifiers.org/{}", topology)));
+    graph.add(
+        &component,
+        sbol("role"),
+        iri(so_term(metadata.r#type.ontology.as_deref(), &metadata.r#type.canonical)),
+    );
+    graph.add(&component, sbol("hasSequence"), iri(sequence.clone()));
+
+    if chrono::DateTime::parse_from_rfc3339(&metadata.creation).is_ok() {
+        graph.add(&component, format!("{}created", DCTERMS), Object::DateTime(metadata.creation.clone()));
+    } else if !metadata.creation.is_empty() {
+        graph.add(&component, format!("{}created", DCTERMS), Object::Literal(metadata.creation.clone()));
+    }
+    for author in &metadata.authors {
+        graph.add(&component, format!("{}creator", DCTERMS), Object::Literal(author.name.clone()));
+    }
+
+    let mut feature_ids = HashSet::new();
+    let mut features = Vec::new();
+    for (i, feature) in biobrick.features.iter().enumerate() {
+        let mut feature_id = display_id(&feature.id);
+        if !feature_ids.insert(feature_id.clone()) {
+            feature_id = format!("{}_{}", feature_id, i);
+            feature_ids.insert(feature_id.clone());
+        }
+        let uri = format!("{}/{}", component, feature_id);
+        graph.add(&component, sbol("hasFeature"), iri(uri.clone()));
+        features.push((uri, feature_id, feature));
+    }
 
-pub fn to_sbol_xml(biobrick: &Biobrick) -> String {
+    let mut activities = Vec::new();
+    for (i, provider) in metadata.providers.iter().enumerate() {
+        let activity_id = format!("{}_retrieval{}", component_id, i + 1);
+        let uri = format!("{}{}", namespace, activity_id);
+        graph.add(&component, format!("{}wasGeneratedBy", PROV), iri(uri.clone()));
+        if !provider.link.is_empty() {
+            graph.add(&component, format!("{}wasDerivedFrom", PROV), iri(provider.link.clone()));
+        }
+        activities.push((uri, activity_id, provider));
+    }
+
+    for (uri, feature_id, feature) in features {
+        graph.add(&uri, format!("{}type", RDF), iri(sbol("SequenceFeature")));
+        graph.add(&uri, sbol("displayId"), Object::Literal(feature_id));
+        if !feature.name.is_empty() {
+            graph.add(&uri, sbol("name"), Object::Literal(feature.name.clone()));
+        }
+        graph.add(
+            &uri,
+            sbol("role"),
+            iri(so_term(feature.r#type.ontology.as_deref(), &feature.r#type.canonical)),
+        );
+        // SBOL ranges have start <= end, so those over the origin are split in two
+        for (j, segment) in feature.location.linear_ranges(biobrick.sequence.len() as i32).iter().enumerate() {
+            let range = format!("{}/Range{}", uri, j + 1);
+            graph.add(&uri, sbol("hasLocation"), iri(range.clone()));
+            graph.add(&range, format!("{}type", RDF), iri(sbol("Range")));
+            graph.add(&range, sbol("displayId"), Object::Literal(format!("Range{}", j + 1)));
+            graph.add(&range, sbol("hasSequence"), iri(sequence.clone()));
+            graph.add(&range, sbol("start"), Object::Integer(segment.start as i64));
+            graph.add(&range, sbol("end"), Object::Integer(segment.end as i64));
+            let orientation = if segment.forward { "SO:0001030" } else { "SO:0001031" };
+            graph.add(&range, sbol("orientation"), iri(format!("https://identifiers.org/{}", orientation)));
+        }
+    }
+
+    graph.add(&sequence, format!("{}type", RDF), iri(sbol("Sequence")));
+    graph.add(&sequence, sbol("displayId"), Object::Literal(sequence_id));
+    graph.add(&sequence, sbol("hasNamespace"), iri(namespace.clone()));
+    graph.add(&sequence, sbol("elements"), Object::Literal(biobrick.sequence.to_lowercase()));
+    graph.add(&sequence, sbol("encoding"), iri("https://identifiers.org/edam:format_1207".to_string()));
+
+    for (uri, activity_id, provider) in activities {
+        graph.add(&uri, format!("{}type", RDF), iri(format!("{}Activity", PROV)));
+        graph.add(&uri, sbol("displayId"), Object::Literal(activity_id));
+        graph.add(&uri, sbol("hasNamespace"), iri(namespace.clone()));
+        graph.add(&uri, sbol("name"), Object::Literal(provider.name.clone()));
+        if !provider.link.is_empty() {
+            graph.add(&uri, format!("{}source", DCTERMS), iri(provider.link.clone()));
+        }
+        if chrono::DateTime::parse_from_rfc3339(&provider.date).is_ok() {
+            graph.add(&uri, format!("{}endedAtTime", PROV), Object::DateTime(provider.date.clone()));
+        }
+    }
+
+    graph
+}
+
+pub fn to_sbol(biobrick: &Biobrick, namespace: &str, format: SbolFormat) -> String {
+    let graph = build_graph(biobrick, namespace);
+    match format {
+        SbolFormat::RdfXml => to_rdf_xml(&graph),
+        SbolFormat::Turtle => to_turtle(&graph),
+        SbolFormat::JsonLd => to_json_ld(&graph),
+    }
+}
+
+/// Prefixed name such as `sbol:displayId`, when the local part is a plain name.
+fn compact(uri: &str) -> Option<String> {
+    PREFIXES.iter().find_map(|(prefix, ns)| {
+        let local = uri.strip_prefix(ns)?;
+        let plain = !local.is_empty()
+            && local.starts_with(|c: char| c.is_ascii_alphabetic())
+            && local.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        plain.then(|| format!("{}:{}", prefix, local))
+    })
+}
+
+fn to_rdf_xml(graph: &Graph) -> String {
     let mut xml = String::new();
     xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
-    xml.push_str("<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\"\n");
-    xml.push_str("         xmlns:sbol=\"http://sbols.org/v2#\"\n");
-    xml.push_str("         xmlns:dcterms=\"http://purl.org/dc/terms/\">\n");
-
-    let base_uri = "https://bricks.bio/sbol/";
-    let part_uri = format!("{}{}", base_uri, biobrick.metadata.id);
-    let seq_uri = format!("{}_seq", part_uri);
-
-    // Component Definition
-    xml.push_str(&format!("  <sbol:ComponentDefinition rdf:about=\"{}\">\n", part_uri));
-    xml.push_str(&format!("    <dcterms:title>{}</dcterms:title>\n", escape_xml(&biobrick.metadata.name)));
-    xml.push_str(&format!("    <dcterms:description>{}</dcterms:description>\n", escape_xml(&biobrick.metadata.description)));
-    
-    xml.push_str("    <sbol:type rdf:resource=\"http://www.biopax.org/release/biopax-level3.owl#DnaRegion\"/>\n");
-    
-    xml.push_str(&format!("    <sbol:role rdf:resource=\"http://identifiers.org/so/{}\"/>\n", 
-        biobrick.metadata.r#type.ontology.as_deref().unwrap_or("SO:0000110")));
-
-    xml.push_str(&format!("    <sbol:sequence rdf:resource=\"{}\"/>\n", seq_uri));
+    xml.push_str("<rdf:RDF");
+    for (i, (prefix, ns)) in PREFIXES.iter().enumerate() {
+        let indent = if i == 0 { " " } else { "         " };
+        xml.push_str(&format!("{}xmlns:{}=\"{}\"", indent, prefix, ns));
+        xml.push_str(if i + 1 == PREFIXES.len() { ">\n" } else { "\n" });
+    }
 
-    for (i, feature) in biobrick.features.iter().enumerate() {
-        let anno_uri = format!("{}/annotation_{}", part_uri, i);
-        let range_uri = format!("{}/range_{}", anno_uri, i);
-        
-        xml.push_str("    <sbol:sequenceAnnotation>\n");
-        xml.push_str(&format!("      <sbol:SequenceAnnotation rdf:about=\"{}\">\n", anno_uri));
-        xml.push_str(&format!("        <dcterms:title>{}</dcterms:title>\n", escape_xml(&feature.name)));
-        xml.push_str("        <sbol:location>\n");
-        xml.push_str(&format!("          <sbol:Range rdf:about=\"{}\">\n", range_uri));
-        xml.push_str(&format!("            <sbol:start>{}</sbol:start>\n", feature.location.start));
-        xml.push_str(&format!("            <sbol:end>{}</sbol:end>\n", feature.location.end));
-        xml.push_str(&format!("            <sbol:orientation rdf:resource=\"http://sbols.org/v2#{}\"/>\n", 
-            if feature.location.forward { "inline" } else { "reverseComplement" }));
-        xml.push_str("          </sbol:Range>\n");
-        xml.push_str("        </sbol:location>\n");
-        xml.push_str(&format!("        <sbol:role rdf:resource=\"http://identifiers.org/so/{}\"/>\n", 
-            feature.r#type.ontology.as_deref().unwrap_or("SO:0000110")));
-        xml.push_str("      </sbol:SequenceAnnotation>\n");
-        xml.push_str("    </sbol:sequenceAnnotation>\n");
-    }
-
-    xml.push_str("  </sbol:ComponentDefinition>\n");
-
-    // Sequence object
-    xml.push_str(&format!("  <sbol:Sequence rdf:about=\"{}\">\n", seq_uri));
-    xml.push_str(&format!("    <sbol:elements>{}</sbol:elements>\n", biobrick.sequence.to_lowercase()));
-    xml.push_str("    <sbol:encoding rdf:resource=\"http://www.chem.qmul.ac.uk/iubmb/misc/naseq.html\"/>\n");
-    xml.push_str("  </sbol:Sequence>\n");
+    let rdf_type = format!("{}type", RDF);
+    for subject in graph.subjects() {
+        let node_type = graph
+            .about(subject)
+            .find_map(|t| match (&t.object, t.predicate == rdf_type) {
+                (Object::Iri(o), true) => compact(o),
+                _ => None,
+            })
+            .unwrap_or_else(|| "rdf:Description".to_string());
+        xml.push_str(&format!("  <{} rdf:about=\"{}\">\n", node_type, escape_xml(subject)));
+        for triple in graph.about(subject) {
+            let Some(predicate) = compact(&triple.predicate) else {
+                continue;
+            };
+            match &triple.object {
+                Object::Iri(o) if triple.predicate == rdf_type && compact(o).as_deref() == Some(node_type.as_str()) => {}
+                Object::Iri(o) => {
+                    xml.push_str(&format!("    <{} rdf:resource=\"{}\"/>\n", predicate, escape_xml(o)));
+                }
+                Object::Literal(l) => {
+                    xml.push_str(&format!("    <{}>{}</{}>\n", predicate, escape_xml(l), predicate));
+                }
+                Object::Integer(n) => {
+                    xml.push_str(&format!("    <{} rdf:datatype=\"{}integer\">{}</{}>\n", predicate, XSD, n, predicate));
+                }
+                Object::DateTime(d) => {
+                    xml.push_str(&format!("    <{} rdf:datatype=\"{}dateTime\">{}</{}>\n", predicate, XSD, escape_xml(d), predicate));
+                }
+            }
+        }
+        xml.push_str(&format!("  </{}>\n", node_type));
+    }
 
     xml.push_str("</rdf:RDF>\n");
     xml
 }
 
+fn to_turtle(graph: &Graph) -> String {
+    let mut ttl = String::new();
+    for (prefix, ns) in PREFIXES {
+        ttl.push_str(&format!("@prefix {}: <{}> .\n", prefix, ns));
+    }
+
+    let rdf_type = format!("{}type", RDF);
+    for subject in graph.subjects() {
+        ttl.push_str(&format!("\n<{}>\n", subject));
+        let triples = graph.about(subject).collect::<Vec<_>>();
+        for (i, triple) in triples.iter().enumerate() {
+            let predicate = if triple.predicate == rdf_type {
+                "a".to_string()
+            } else {
+                compact(&triple.predicate).unwrap_or_else(|| format!("<{}>", triple.predicate))
+            };
+            let object = match &triple.object {
+                Object::Iri(o) => compact(o).unwrap_or_else(|| format!("<{}>", o)),
+                Object::Literal(l) => format!("\"{}\"", escape_turtle(l)),
+                Object::Integer(n) => n.to_string(),
+                Object::DateTime(d) => format!("\"{}\"^^xsd:dateTime", escape_turtle(d)),
+            };
+            let end = if i + 1 == triples.len() { " ." } else { " ;" };
+            ttl.push_str(&format!("    {} {}{}\n", predicate, object, end));
+        }
+    }
+    ttl
+}
+
+fn to_json_ld(graph: &Graph) -> String {
+    let context = PREFIXES
+        .iter()
+        .filter(|(prefix, _)| *prefix != "rdf")
+        .map(|(prefix, ns)| (prefix.to_string(), json!(ns)))
+        .collect::<Map<String, Value>>();
+
+    let rdf_type = format!("{}type", RDF);
+    let nodes = graph
+        .subjects()
+        .into_iter()
+        .map(|subject| {
+            let mut node = Map::new();
+            node.insert("@id".to_string(), json!(subject));
+            for triple in graph.about(subject) {
+                let (key, value) = if triple.predicate == rdf_type {
+                    let Object::Iri(ref o) = triple.object else {
+                        continue;
+                    };
+                    ("@type".to_string(), json!(compact(o).unwrap_or_else(|| o.clone())))
+                } else {
+                    let key = compact(&triple.predicate).unwrap_or_else(|| triple.predicate.clone());
+                    let value = match &triple.object {
+                        Object::Iri(o) => json!({ "@id": o }),
+                        Object::Literal(l) => json!(l),
+                        Object::Integer(n) => json!(n),
+                        Object::DateTime(d) => json!({ "@value": d, "@type": "xsd:dateTime" }),
+                    };
+                    (key, value)
+                };
+                match node.get_mut(&key) {
+                    Some(Value::Array(values)) => values.push(value),
+                    Some(existing) => *existing = json!([existing.clone(), value]),
+                    None => {
+                        node.insert(key, value);
+                    }
+                }
+            }
+            Value::Object(node)
+        })
+        .collect::<Vec<_>>();
+
+    serde_json::to_string_pretty(&json!({ "@context": context, "@graph": nodes })).unwrap_or_default()
+}
+
 fn escape_xml(s: &str) -> String {
     s.replace('&', "&amp;")
      .replace('<', "&lt;")
@@ -65,3 +382,110 @@ fn escape_xml(s: &str) -> String {
      .replace('"', "&quot;")
      .replace('\'', "&apos;")
 }
+
+fn escape_turtle(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ontology::find_canonical;
+    use crate::parsers::sbol::parse_sbol_raw;
+    use crate::types::{Location, MetaBiobrick, MetaFeature, Segment};
+
+    fn feature(id: &str, name: &str, canonical: &str, segments: Vec<Segment>) -> MetaFeature {
+        let (first, last) = (segments[0].clone(), segments[segments.len() - 1].clone());
+        MetaFeature {
+            id: id.to_string(),
+            name: name.to_string(),
+            r#type: find_canonical(canonical).unwrap().into(),
+            location: Location {
+                start: first.start,
+                end: last.end,
+                forward: first.forward,
+                segments,
+                partial_start: false,
+                partial_end: false,
+            },
+            translation: None,
+        }
+    }
+
+    #[test]
+    fn round_trips_through_parser() {
+        let sequence = "ttgacagctagctcagtcctaggtataatgctagcaaagaggagaaaatgcgtaaaggcgaagagctgttcactggtgtcgtcccta".repeat(2);
+        let biobrick = Biobrick {
+            metadata: MetaBiobrick {
+                id: "BBa_K000002".to_string(),
+                name: "Expression cassette".to_string(),
+                description: String::new(),
+                creation: String::new(),
+                size: sequence.len() as i32,
+                circular: true,
+                r#type: find_canonical("composite").unwrap().into(),
+                authors: vec![],
+                providers: vec![],
+            },
+            sequence,
+            // A device over the origin holding a promoter and a reverse, split coding sequence
+            features: vec![
+                feature(
+                    "device",
+                    "Device",
+                    "composite",
+                    vec![
+                        Segment { start: 150, end: 174, forward: true },
+                        Segment { start: 1, end: 60, forward: true },
+                    ],
+                ),
+                feature("promoter", "J23100", "promoter", vec![Segment { start: 1, end: 35, forward: true }]),
+                feature(
+                    "cds",
+                    "gfp",
+                    "coding-sequence",
+                    vec![
+                        Segment { start: 160, end: 174, forward: false },
+                        Segment { start: 1, end: 12, forward: false },
+                    ],
+                ),
+                feature("cds 2", "gfp fragment", "coding-sequence", vec![Segment { start: 40, end: 60, forward: false }]),
+                MetaFeature {
+                    location: Location {
+                        start: 170,
+                        end: 5,
+                        forward: false,
+                        segments: vec![],
+                        partial_start: false,
+                        partial_end: false,
+                    },
+                    ..feature("term", "terminator", "terminator", vec![Segment { start: 170, end: 5, forward: false }])
+                },
+            ],
+        };
+
+        let parsed = parse_sbol_raw(&to_sbol(&biobrick, DEFAULT_NAMESPACE, SbolFormat::RdfXml), None).unwrap();
+        assert_eq!(parsed.display_id, "BBa_K000002");
+        assert_eq!(parsed.sequence, biobrick.sequence);
+        assert!(parsed.circular);
+        let mut expected = serde_json::to_value(&biobrick.features).unwrap();
+        expected[3]["id"] = "cds_2".into(); // displayIds are sanitized
+        // A single range over the origin comes back as two
+        expected[4]["location"]["segments"] = serde_json::json!([
+            { "start": 170, "end": 174, "forward": false },
+            { "start": 1, "end": 5, "forward": false },
+        ]);
+        assert_eq!(serde_json::to_value(&parsed.features).unwrap(), expected);
+    }
+}
@@ -0,0 +1,422 @@
+//! Hierarchical Navigable Small World graph (Malkov & Yashunin, 2016) over cosine similarity.
+//! Vectors are owned by the caller and addressed by position; the graph only keeps links.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet};
+
+const MAX_LEVEL: usize = 16;
+
+#[derive(Debug, Clone, Copy)]
+pub struct HnswParams {
+    /// Links per node on upper layers, twice as many on layer 0.
+    pub m: usize,
+    /// Candidate list size while inserting; higher builds a better graph, slower.
+    pub ef_construction: usize,
+    /// Candidate list size while searching; higher improves recall, slower.
+    pub ef_search: usize,
+}
+
+impl Default for HnswParams {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 100,
+            ef_search: 64,
+        }
+    }
+}
+
+impl HnswParams {
+    /// Reads `HNSW_M`, `HNSW_EF_CONSTRUCTION` and `HNSW_EF_SEARCH`, falling back to the defaults.
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let var = |name: &str, fallback: usize| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(fallback)
+        };
+        Self {
+            m: var("HNSW_M", default.m).max(2),
+            ef_construction: var("HNSW_EF_CONSTRUCTION", default.ef_construction),
+            ef_search: var("HNSW_EF_SEARCH", default.ef_search),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Scored {
+    similarity: f64,
+    node: usize,
+}
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.similarity
+            .total_cmp(&other.similarity)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Hnsw {
+    params: HnswParams,
+    /// node -> layer -> neighbours
+    links: Vec<Vec<Vec<u32>>>,
+    entry: Option<usize>,
+    seed: u64,
+}
+
+impl Hnsw {
+    pub fn new(params: HnswParams) -> Self {
+        Self {
+            params,
+            ..Self::default()
+        }
+    }
+
+    /// Rebuilds a graph from saved links, or `None` when they point outside the graph.
+    pub fn from_links(params: HnswParams, links: Vec<Vec<Vec<u32>>>) -> Option<Self> {
+        let count = links.len();
+        let valid = links
+            .iter()
+            .all(|layers| !layers.is_empty() && layers.iter().flatten().all(|&n| (n as usize) < count));
+        if !valid {
+            return None;
+        }
+
+        let entry = (0..count).max_by_key(|&i| (links[i].len(), Reverse(i)));
+        Some(Self {
+            params,
+            seed: count as u64,
+            links,
+            entry,
+        })
+    }
+
+    pub fn params(&self) -> HnswParams {
+        self.params
+    }
+
+    pub fn links(&self, node: usize) -> &[Vec<u32>] {
+        &self.links[node]
+    }
+
+    /// Links `node` into the graph, either a new node (`node == len()`) or one whose vector
+    /// changed. Returns every node whose links were modified, `node` included.
+    pub fn insert(&mut self, node: usize, vectors: &[Vec<f32>]) -> Vec<usize> {
+        let level = if node < self.links.len() {
+            let level = self.links[node].len() - 1;
+            self.links[node] = vec![Vec::new(); level + 1];
+            level
+        } else {
+            let level = self.random_level();
+            self.links.push(vec![Vec::new(); level + 1]);
+            level
+        };
+
+        let mut touched = vec![node];
+        let Some(entry) = self.entry.filter(|&e| e != node || self.links.len() > 1) else {
+            self.entry = Some(node);
+            return touched;
+        };
+        // An updated entry point searches from any other node
+        let entry = if entry == node { (node + 1) % self.links.len() } else { entry };
+
+        let query = &vectors[node];
+        let top = self.links[entry].len() - 1;
+        let mut current = vec![Scored {
+            similarity: cosine_similarity(query, &vectors[entry]),
+            node: entry,
+        }];
+        for layer in (level + 1..=top).rev() {
+            current = self.search_layer(query, vectors, &current, 1, layer);
+        }
+
+        for layer in (0..=level.min(top)).rev() {
+            let candidates = self
+                .search_layer(query, vectors, &current, self.params.ef_construction, layer)
+                .into_iter()
+                .filter(|c| c.node != node)
+                .collect::<Vec<_>>();
+            let max_links = if layer == 0 { self.params.m * 2 } else { self.params.m };
+            let selected = select_neighbours(&candidates, self.params.m, vectors);
+            self.links[node][layer] = selected.iter().map(|s| s.node as u32).collect();
+
+            for neighbour in selected.iter().map(|s| s.node) {
+                let list = &mut self.links[neighbour][layer];
+                if !list.contains(&(node as u32)) {
+                    list.push(node as u32);
+                }
+                if list.len() > max_links {
+                    let mut scored = list
+                        .iter()
+                        .map(|&n| Scored {
+                            similarity: cosine_similarity(&vectors[neighbour], &vectors[n as usize]),
+                            node: n as usize,
+                        })
+                        .collect::<Vec<_>>();
+                    scored.sort_by(|a, b| b.cmp(a));
+                    self.links[neighbour][layer] = select_neighbours(&scored, max_links, vectors)
+                        .iter()
+                        .map(|s| s.node as u32)
+                        .collect();
+                }
+                touched.push(neighbour);
+            }
+
+            if !candidates.is_empty() {
+                current = candidates;
+            }
+        }
+
+        if level > top {
+            self.entry = Some(node);
+        }
+        touched.sort_unstable();
+        touched.dedup();
+        touched
+    }
+
+    /// Approximate `k` most similar nodes to `query`, best first.
+    pub fn search(&self, query: &[f32], vectors: &[Vec<f32>], k: usize) -> Vec<(usize, f64)> {
+        let Some(entry) = self.entry else {
+            return vec![];
+        };
+
+        let mut current = vec![Scored {
+            similarity: cosine_similarity(query, &vectors[entry]),
+            node: entry,
+        }];
+        for layer in (1..self.links[entry].len()).rev() {
+            current = self.search_layer(query, vectors, &current, 1, layer);
+        }
+
+        self.search_layer(query, vectors, &current, self.params.ef_search.max(k), 0)
+            .into_iter()
+            .take(k)
+            .map(|s| (s.node, s.similarity))
+            .collect()
+    }
+
+    fn search_layer(&self, query: &[f32], vectors: &[Vec<f32>], entries: &[Scored], ef: usize, layer: usize) -> Vec<Scored> {
+        let mut visited = entries.iter().map(|e| e.node).collect::<HashSet<_>>();
+        let mut candidates = entries.iter().copied().collect::<BinaryHeap<_>>();
+        let mut results = entries.iter().copied().map(Reverse).collect::<BinaryHeap<_>>();
+        while results.len() > ef {
+            results.pop();
+        }
+
+        while let Some(candidate) = candidates.pop() {
+            let worst = results.peek().map(|r| r.0.similarity).unwrap_or(f64::MIN);
+            if results.len() >= ef && candidate.similarity < worst {
+                break;
+            }
+
+            let neighbours = self.links[candidate.node].get(layer).map(|l| l.as_slice()).unwrap_or_default();
+            for &neighbour in neighbours {
+                let neighbour = neighbour as usize;
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let similarity = cosine_similarity(query, &vectors[neighbour]);
+                let worst = results.peek().map(|r| r.0.similarity).unwrap_or(f64::MIN);
+                if results.len() < ef || similarity > worst {
+                    let scored = Scored { similarity, node: neighbour };
+                    candidates.push(scored);
+                    results.push(Reverse(scored));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        results.into_sorted_vec().into_iter().map(|Reverse(s)| s).collect()
+    }
+
+    fn random_level(&mut self) -> usize {
+        self.seed = self.seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        let uniform = ((self.seed >> 11) as f64 + 1.0) / (1_u64 << 53) as f64;
+        let level_multiplier = 1.0 / (self.params.m as f64).ln();
+        ((-uniform.ln() * level_multiplier) as usize).min(MAX_LEVEL)
+    }
+}
+
+/// Neighbour selection heuristic: keeps candidates closer to the new node than to any
+/// already selected neighbour, then fills up with the pruned ones.
+fn select_neighbours(candidates: &[Scored], m: usize, vectors: &[Vec<f32>]) -> Vec<Scored> {
+    let mut selected: Vec<Scored> = Vec::with_capacity(m);
+    let mut pruned = Vec::new();
+    for candidate in candidates {
+        if selected.len() >= m {
+            break;
+        }
+        let diverse = selected.iter().all(|s| {
+            cosine_similarity(&vectors[candidate.node], &vectors[s.node]) < candidate.similarity
+        });
+        if diverse {
+            selected.push(*candidate);
+        } else {
+            pruned.push(*candidate);
+        }
+    }
+    for candidate in pruned {
+        if selected.len() >= m {
+            break;
+        }
+        selected.push(candidate);
+    }
+    selected
+}
+
+/// Per layer: a little-endian u32 count followed by that many u32 neighbours.
+pub fn encode_links(layers: &[Vec<u32>]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for layer in layers {
+        bytes.extend((layer.len() as u32).to_le_bytes());
+        for neighbour in layer {
+            bytes.extend(neighbour.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+pub fn decode_links(bytes: &[u8]) -> Option<Vec<Vec<u32>>> {
+    let mut words = bytes
+        .chunks_exact(4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+    let mut layers = Vec::new();
+    while let Some(count) = words.next() {
+        let layer = words.by_ref().take(count as usize).collect::<Vec<_>>();
+        if layer.len() != count as usize {
+            return None;
+        }
+        layers.push(layer);
+    }
+    Some(layers)
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    if a.is_empty() || b.is_empty() || a.len() != b.len() {
+        return 0.0;
+    }
+
+    let mut dot = 0.0_f64;
+    let mut norm_a = 0.0_f64;
+    let mut norm_b = 0.0_f64;
+
+    for i in 0..a.len() {
+        let av = a[i] as f64;
+        let bv = b[i] as f64;
+        dot += av * bv;
+        norm_a += av * av;
+        norm_b += bv * bv;
+    }
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+
+    (dot / (norm_a.sqrt() * norm_b.sqrt())).clamp(-1.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Clustered vectors, closer to real text embeddings than uniform noise.
+    fn corpus(count: usize, dim: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut state = seed;
+        let mut next = move || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((state >> 40) as f32 / (1_u64 << 24) as f32) * 2.0 - 1.0
+        };
+        let centers = (0..20).map(|_| (0..dim).map(|_| next()).collect::<Vec<_>>()).collect::<Vec<_>>();
+        (0..count)
+            .map(|i| centers[i % centers.len()].iter().map(|c| c + next() * 0.6).collect())
+            .collect()
+    }
+
+    fn exact(query: &[f32], vectors: &[Vec<f32>], k: usize) -> Vec<usize> {
+        let mut scored = vectors
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i, cosine_similarity(query, v)))
+            .collect::<Vec<_>>();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.into_iter().take(k).map(|(i, _)| i).collect()
+    }
+
+    fn recall(graph: &Hnsw, vectors: &[Vec<f32>], queries: &[Vec<f32>], k: usize) -> f64 {
+        let found: usize = queries
+            .iter()
+            .map(|q| {
+                let truth = exact(q, vectors, k);
+                graph.search(q, vectors, k).iter().filter(|(i, _)| truth.contains(i)).count()
+            })
+            .sum();
+        found as f64 / (queries.len() * k) as f64
+    }
+
+    #[test]
+    fn recall_against_exact_cosine() {
+        let vectors = corpus(3000, 48, 7);
+        let queries = corpus(100, 48, 8);
+        let mut graph = Hnsw::new(HnswParams::default());
+        for node in 0..vectors.len() {
+            graph.insert(node, &vectors);
+        }
+
+        let recall = recall(&graph, &vectors, &queries, 10);
+        assert!(recall >= 0.95, "recall@10 = {}", recall);
+    }
+
+    #[test]
+    fn larger_ef_search_does_not_lower_recall() {
+        let vectors = corpus(2000, 32, 11);
+        let queries = corpus(50, 32, 12);
+        let mut graph = Hnsw::new(HnswParams { ef_search: 10, ..HnswParams::default() });
+        for node in 0..vectors.len() {
+            graph.insert(node, &vectors);
+        }
+        let low = recall(&graph, &vectors, &queries, 10);
+        graph.params.ef_search = 200;
+        let high = recall(&graph, &vectors, &queries, 10);
+        assert!(high >= low && high >= 0.98, "recall@10 = {} (ef 10) / {} (ef 200)", low, high);
+    }
+
+    #[test]
+    fn updates_and_reload_keep_recall() {
+        let mut vectors = corpus(1500, 32, 21);
+        let queries = corpus(50, 32, 22);
+        let mut graph = Hnsw::new(HnswParams::default());
+        for node in 0..vectors.len() {
+            graph.insert(node, &vectors);
+        }
+
+        let replacements = corpus(300, 32, 23);
+        for (i, replacement) in replacements.into_iter().enumerate() {
+            vectors[i * 5] = replacement;
+            graph.insert(i * 5, &vectors);
+        }
+
+        let saved = (0..graph.links.len()).map(|i| encode_links(graph.links(i))).collect::<Vec<_>>();
+        let links = saved.iter().map(|b| decode_links(b).unwrap()).collect::<Vec<_>>();
+        let reloaded = Hnsw::from_links(HnswParams::default(), links).unwrap();
+
+        let before = recall(&graph, &vectors, &queries, 10);
+        let after = recall(&reloaded, &vectors, &queries, 10);
+        assert!(after >= 0.95, "recall@10 after updates = {}", after);
+        assert!((before - after).abs() < 1e-9);
+    }
+}
@@ -5,16 +5,19 @@ mod limit;
 mod types;
 mod search;
 mod semantic;
+mod hnsw;
 mod providers;
 mod parsers;
 mod exporters;
+mod sequence;
+mod assembly;
 
 use axum::{
     extract::{ConnectInfo, Path, Request, State},
-    http::{header, HeaderValue, StatusCode},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     middleware::{self, Next},
     response::{Html, IntoResponse, Response},
-    routing::get,
+    routing::{get, post},
     Json, Router,
 };
 use crate::ontology::{ONTOLOGY, OntologyEntrySerializable};
@@ -23,7 +26,7 @@ use std::{collections::HashSet, net::{IpAddr, SocketAddr}, sync::Arc};
 use axum::extract::Query;
 use tower_http::services::ServeDir;
 use serde_json::json;
-use types::{ApiStats, Biobrick, CacheSearchParams, CacheStats, SearchHit, SearchResponse};
+use types::{Analysis, AnalysisParams, AnnotateParams, AnnotateResponse, ApiStats, AssemblyParams, Biobrick, BiobrickAssemblyRequest, CacheSearchParams, CacheStats, CompatibilityReport, DigestParams, DigestResponse, EnzymeInfo, ExpressionParams, ExpressionReport, FastaParams, GibsonRequest, GibsonResponse, GoldenGateRequest, Location, MetaBiobrick, MotifSearchParams, MotifSearchResponse, OrfParams, OrfsResponse, PartInput, PrimerParams, PrimersResponse, RecodeParams, RecodeResponse, SbolParams, SearchResponse, SequenceSearchParams, SequenceSearchResponse, SequenceViewParams, SynthesisParams, SynthesisReport};
 
 #[derive(Clone)]
 pub struct AppState {
@@ -31,6 +34,8 @@ pub struct AppState {
     pub cache: cache::SqliteCache,
     pub refresh_in_flight: Arc<tokio::sync::Mutex<HashSet<String>>>,
     pub rate_limiter: RateLimiter,
+    pub sbol_namespace: String,
+    pub auto_annotate: bool,
 }
 
 #[tokio::main]
@@ -42,11 +47,18 @@ async fn main() {
         .unwrap();
 
     let cache = cache::SqliteCache::new("cache/bricksbio.db").unwrap();
+    match semantic::sync_index(&cache) {
+        Ok(count) => println!("Loaded {} part embeddings", count),
+        Err(error) => eprintln!("Failed to load part embeddings: {}", error),
+    }
     let state = AppState {
         client,
         cache,
         refresh_in_flight: Arc::new(tokio::sync::Mutex::new(HashSet::new())),
         rate_limiter: RateLimiter::new(),
+        sbol_namespace: std::env::var("SBOL_NAMESPACE")
+            .unwrap_or_else(|_| exporters::sbol::DEFAULT_NAMESPACE.to_string()),
+        auto_annotate: std::env::var("AUTO_ANNOTATE").map_or(true, |v| v != "0" && v != "false"),
     };
 
     let app = Router::new()
@@ -54,10 +66,32 @@ async fn main() {
         .route("/openapi.yaml", get(serve_openapi))
         .route("/cache/stats", get(get_cache_stats))
         .route("/cache/search", get(get_cache_search))
+        .route("/cache/fasta", get(get_cache_fasta))
+        .route("/fasta", get(get_fasta))
+        .route("/search/sequence", get(get_search_sequence).post(post_search_sequence))
+        .route("/search/motif", get(get_search_motif))
         .nest_service("/assets", ServeDir::new("assets"))
         .route("/parts/:id", get(get_part))
         .route("/parts/:id/sbol", get(get_part_sbol))
+        .route("/parts/:id/genbank", get(get_part_genbank))
+        .route("/parts/:id/fasta", get(get_part_fasta))
+        .route("/parts/:id/compatibility", get(get_part_compatibility))
+        .route("/parts/:id/digest", get(get_part_digest))
+        .route("/parts/:id/primers", get(get_part_primers))
+        .route("/parts/:id/orfs", get(get_part_orfs))
+        .route("/parts/:id/analysis", get(get_part_analysis))
+        .route("/parts/:id/synthesis-check", get(get_part_synthesis_check))
+        .route("/parts/:id/recode", get(get_part_recode))
+        .route("/parts/:id/expression", get(get_part_expression))
+        .route("/parts/:id/sequence", get(get_part_sequence))
+        .route("/convert/sbol", post(post_convert_sbol))
+        .route("/annotate", post(post_annotate))
+        .route("/synthesis-check", post(post_synthesis_check))
+        .route("/assemble/biobrick", post(post_assemble_biobrick))
+        .route("/assemble/golden-gate", post(post_assemble_golden_gate))
+        .route("/assemble/gibson", post(post_assemble_gibson))
         .route("/ontology", get(get_ontology))
+        .route("/enzymes", get(get_enzymes))
         .route("/stats", get(get_api_stats))
         .layer(middleware::from_fn_with_state(state.clone(), rate_limit_middleware))
         .with_state(state);
@@ -149,33 +183,138 @@ async fn get_cache_search(
     State(state): State<AppState>,
     Query(params): Query<CacheSearchParams>,
 ) -> Result<Json<SearchResponse>, (StatusCode, Json<serde_json::Value>)> {
-    let query = params.q.trim().to_string();
-    if query.is_empty() {
+    if params.q.trim().is_empty() && !params.has_filters() && params.sort.is_none() {
         return Err((
             StatusCode::BAD_REQUEST,
             Json(json!({ "message": "Missing or empty query parameter: q" })),
         ));
     }
+    if params.order.is_some() && params.sort.is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "order only applies with sort" })),
+        ));
+    }
+    if let Some(name) = params.host.as_deref() {
+        host_usage(name)?;
+    }
+
+    let cursor = match params.cursor.as_deref().map(semantic::Cursor::decode) {
+        Some(None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Invalid cursor" })),
+            ));
+        }
+        cursor => cursor.flatten(),
+    };
+
+    match semantic::cache_search(&state.cache, &params, cursor.as_ref()) {
+        Ok(response) => Ok(Json(response)),
+        Err(error) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": format!("Failed to search cache: {}", error) })),
+        )),
+    }
+}
+
+#[axum::debug_handler]
+async fn get_search_sequence(
+    State(state): State<AppState>,
+    Query(params): Query<SequenceSearchParams>,
+) -> Result<Json<SequenceSearchResponse>, (StatusCode, Json<serde_json::Value>)> {
+    search_sequence(&state, &params.q, &params).await
+}
+
+#[axum::debug_handler]
+async fn post_search_sequence(
+    State(state): State<AppState>,
+    Query(params): Query<SequenceSearchParams>,
+    body: String,
+) -> Result<Json<SequenceSearchResponse>, (StatusCode, Json<serde_json::Value>)> {
+    search_sequence(&state, &body, &params).await
+}
+
+async fn search_sequence(
+    state: &AppState,
+    input: &str,
+    params: &SequenceSearchParams,
+) -> Result<Json<SequenceSearchResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let Some(query) = sequence::clean_sequence(input) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Query is not a DNA sequence" })),
+        ));
+    };
+    if query.len() < sequence::similarity::K || query.len() > 10_000 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": format!("Query must be {} to 10000 bp long", sequence::similarity::K) })),
+        ));
+    }
 
     let requested = params.n.unwrap_or(10).min(50);
+    let min_identity = params.min_identity.unwrap_or(0.8).clamp(0.0, 1.0);
 
-    match semantic::cache_search(&state.cache, &query, requested) {
-        Ok(items) => {
-            let results = items
-                .into_iter()
-                .map(|(biobrick, score)| SearchHit {
-                    r#match: score,
-                    biobrick,
-                })
-                .collect::<Vec<_>>();
-
-            Ok(Json(SearchResponse {
-                query,
-                requested,
-                count: results.len(),
-                results,
-            }))
-        }
+    let length = query.len();
+    let cache = state.cache.clone();
+    let searched = tokio::task::spawn_blocking(move || {
+        sequence::similarity::sequence_search(&cache, &query, requested, min_identity)
+    })
+    .await;
+    match searched {
+        Ok(Ok(results)) => Ok(Json(SequenceSearchResponse {
+            length,
+            requested,
+            count: results.len(),
+            results,
+        })),
+        Ok(Err(error)) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": format!("Failed to search cache: {}", error) })),
+        )),
+        Err(error) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": format!("Failed to search cache: {}", error) })),
+        )),
+    }
+}
+
+#[axum::debug_handler]
+async fn get_search_motif(
+    State(state): State<AppState>,
+    Query(params): Query<MotifSearchParams>,
+) -> Result<Json<MotifSearchResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let variants = match sequence::motif::parse_motif(&params.q) {
+        Ok(variants) => variants,
+        Err(message) => return Err((StatusCode::BAD_REQUEST, Json(json!({ "message": message })))),
+    };
+    let shortest = variants.iter().map(|v| v.len()).min().unwrap_or(0);
+    let mismatches = params.mismatches.unwrap_or(0);
+    if shortest < 4 || mismatches > 3 || 2 * mismatches >= shortest {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Motif must be at least 4 bp, with at most 3 mismatches and fewer than half its length" })),
+        ));
+    }
+
+    let requested = params.n.unwrap_or(10).min(50);
+    let cache = state.cache.clone();
+    let searched =
+        tokio::task::spawn_blocking(move || sequence::motif::motif_search(&cache, &variants, mismatches, requested)).await;
+    match searched {
+        Ok(Ok((total, results))) => Ok(Json(MotifSearchResponse {
+            query: params.q.trim().to_uppercase(),
+            mismatches,
+            requested,
+            count: results.len(),
+            total,
+            results,
+        })),
+        Ok(Err(error)) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": format!("Failed to search cache: {}", error) })),
+        )),
         Err(error) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({ "message": format!("Failed to search cache: {}", error) })),
@@ -183,6 +322,48 @@ async fn get_cache_search(
     }
 }
 
+#[axum::debug_handler]
+async fn post_annotate(
+    State(state): State<AppState>,
+    Query(params): Query<AnnotateParams>,
+    body: String,
+) -> Result<Json<AnnotateResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let Some(query) = sequence::clean_sequence(&body) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Body is not a DNA sequence" })),
+        ));
+    };
+    if query.is_empty() || query.len() > 100_000 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Sequence must be 1 to 100000 bp long" })),
+        ));
+    }
+
+    let circular = params.circular.unwrap_or(false);
+    let length = query.len();
+    let cache = state.cache.clone();
+    let annotated =
+        tokio::task::spawn_blocking(move || sequence::annotate::annotate(&cache, &query, circular, None)).await;
+    match annotated {
+        Ok(Ok(features)) => Ok(Json(AnnotateResponse {
+            length,
+            circular,
+            count: features.len(),
+            features,
+        })),
+        Ok(Err(error)) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": format!("Failed to annotate sequence: {}", error) })),
+        )),
+        Err(error) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": format!("Failed to annotate sequence: {}", error) })),
+        )),
+    }
+}
+
 #[axum::debug_handler]
 async fn get_part(
     State(state): State<AppState>,
@@ -211,6 +392,8 @@ async fn get_part(
 async fn get_part_sbol(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    Query(params): Query<SbolParams>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
     let biobrick = search::meta_search(&state, &id).await;
 
@@ -222,9 +405,11 @@ async fn get_part_sbol(
                     Json(json!({ "message": "Part not found" })),
                 ));
             }
-            let sbol = exporters::sbol::to_sbol_xml(&b);
+            let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+            let format = exporters::sbol::SbolFormat::negotiate(params.format.as_deref(), accept);
+            let sbol = exporters::sbol::to_sbol(&b, &state.sbol_namespace, format);
             Ok((
-                [(header::CONTENT_TYPE, "application/rdf+xml")],
+                [(header::CONTENT_TYPE, format.content_type())],
                 sbol,
             ))
         }
@@ -235,12 +420,804 @@ async fn get_part_sbol(
     }
 }
 
+#[axum::debug_handler]
+async fn get_part_genbank(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let biobrick = search::meta_search(&state, &id).await;
+
+    match biobrick {
+        Some(b) => {
+            if b.metadata.size == 0 {
+                return Err((
+                    StatusCode::NOT_FOUND,
+                    Json(json!({ "message": "Part not found" })),
+                ));
+            }
+            let genbank = exporters::genbank::to_genbank(&b);
+            Ok((
+                [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+                genbank,
+            ))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "message": "Part not found" })),
+        )),
+    }
+}
+
+#[axum::debug_handler]
+async fn get_part_compatibility(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<CompatibilityReport>, (StatusCode, Json<serde_json::Value>)> {
+    let biobrick = search::meta_search(&state, &id).await;
+
+    match biobrick {
+        Some(b) if b.metadata.size != 0 => Ok(Json(CompatibilityReport {
+            id: b.metadata.id.clone(),
+            size: b.sequence.len(),
+            circular: b.metadata.circular,
+            standards: sequence::compatibility::check_standards(&b),
+        })),
+        _ => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "message": "Part not found" })),
+        )),
+    }
+}
+
+#[axum::debug_handler]
+async fn get_part_digest(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<DigestParams>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let mut enzymes: Vec<&sequence::enzymes::Enzyme> = Vec::new();
+    for name in params.enzymes.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        match sequence::enzymes::find_enzyme(name) {
+            Some(enzyme) if enzymes.iter().any(|e| e.name == enzyme.name) => {}
+            Some(enzyme) => enzymes.push(enzyme),
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "message": format!("Unknown enzyme: {}", name) })),
+                ))
+            }
+        }
+    }
+    if enzymes.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "No enzymes given" })),
+        ));
+    }
+
+    let svg = params.format.as_deref().is_some_and(|f| f.eq_ignore_ascii_case("svg"));
+    let ladder = match params.ladder.as_deref() {
+        None => &exporters::gel::LADDERS[0],
+        Some(name) => match exporters::gel::find_ladder(name) {
+            Some(ladder) => ladder,
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "message": format!("Unknown ladder: {}", name) })),
+                ))
+            }
+        },
+    };
+
+    let b = match search::meta_search(&state, &id).await {
+        Some(b) if b.metadata.size != 0 => b,
+        _ => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Part not found" })),
+            ))
+        }
+    };
+    let (sites, fragments) = sequence::digest::digest(&b.sequence, b.metadata.circular, &enzymes);
+
+    if svg {
+        let label = enzymes.iter().map(|e| e.name).collect::<Vec<_>>().join(" + ");
+        let sizes = fragments.iter().map(|f| f.length).collect::<Vec<_>>();
+        let gel = exporters::gel::to_svg(&label, &sizes, ladder);
+        return Ok(([(header::CONTENT_TYPE, "image/svg+xml")], gel).into_response());
+    }
+    Ok(Json(DigestResponse {
+        id: b.metadata.id.clone(),
+        size: b.sequence.len(),
+        circular: b.metadata.circular,
+        enzymes: enzymes.iter().map(|e| EnzymeInfo::from(*e)).collect(),
+        sites,
+        fragments,
+    })
+    .into_response())
+}
+
+#[axum::debug_handler]
+async fn get_part_fasta(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let biobrick = search::meta_search(&state, &id).await;
+
+    match biobrick {
+        Some(b) => {
+            if b.metadata.size == 0 {
+                return Err((
+                    StatusCode::NOT_FOUND,
+                    Json(json!({ "message": "Part not found" })),
+                ));
+            }
+            let fasta = exporters::fasta::to_fasta(&b);
+            Ok((
+                [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+                fasta,
+            ))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "message": "Part not found" })),
+        )),
+    }
+}
+
+#[axum::debug_handler]
+async fn get_fasta(
+    State(state): State<AppState>,
+    Query(params): Query<FastaParams>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let ids = params
+        .ids
+        .split(',')
+        .map(|id| id.trim())
+        .filter(|id| !id.is_empty())
+        .collect::<Vec<_>>();
+    if ids.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Missing or empty query parameter: ids" })),
+        ));
+    }
+    if ids.len() > 50 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Too many IDs, at most 50 are allowed" })),
+        ));
+    }
+
+    let biobricks = futures::future::join_all(ids.iter().map(|id| search::meta_search(&state, id)))
+        .await
+        .into_iter()
+        .flatten()
+        .filter(|b| b.metadata.size > 0)
+        .collect::<Vec<_>>();
+
+    if biobricks.is_empty() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "message": "No parts found" })),
+        ));
+    }
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        exporters::fasta::to_multi_fasta(&biobricks),
+    ))
+}
+
+#[axum::debug_handler]
+async fn get_cache_fasta(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    match state.cache.list_parts() {
+        Ok(parts) => Ok((
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            exporters::fasta::to_multi_fasta(&parts),
+        )),
+        Err(error) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": format!("Failed to read cache: {}", error) })),
+        )),
+    }
+}
+
+#[axum::debug_handler]
+async fn post_convert_sbol(
+    body: String,
+) -> Result<Json<Biobrick>, (StatusCode, Json<serde_json::Value>)> {
+    match parsers::sbol::parse_sbol_raw(&body, None) {
+        Some(sbol_data) => {
+            let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+            let id = sbol_data.display_id.clone();
+            let link = sbol_data.uri.clone();
+            Ok(Json(parsers::sbol::sbol_to_biobrick(&id, "SBOL document", &link, sbol_data, now)))
+        }
+        None => Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Body is not a valid SBOL RDF/XML document" })),
+        )),
+    }
+}
+
+#[axum::debug_handler]
+async fn get_part_orfs(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<OrfParams>,
+) -> Result<Json<OrfsResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let table = params.table.unwrap_or(sequence::genetic_code::BACTERIAL);
+    let Some(code) = sequence::genetic_code::find_code(table) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": format!("Unknown genetic code table: {}", table) })),
+        ));
+    };
+    let min_length = params.min_length.unwrap_or(30).max(1);
+
+    let b = match search::meta_search(&state, &id).await {
+        Some(b) if b.metadata.size != 0 => b,
+        _ => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Part not found" })),
+            ))
+        }
+    };
+    let orfs = sequence::orfs::find_orfs(
+        &b.sequence,
+        b.metadata.circular,
+        code,
+        min_length,
+        params.alternative_starts.unwrap_or(false),
+    );
+    Ok(Json(OrfsResponse {
+        id: b.metadata.id.clone(),
+        size: b.sequence.len(),
+        circular: b.metadata.circular,
+        table: code.id,
+        min_length,
+        count: orfs.len(),
+        orfs,
+    }))
+}
+
+#[axum::debug_handler]
+async fn get_part_analysis(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<AnalysisParams>,
+) -> Result<Json<Analysis>, (StatusCode, Json<serde_json::Value>)> {
+    let b = match search::meta_search(&state, &id).await {
+        Some(b) if b.metadata.size != 0 => b,
+        _ => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Part not found" })),
+            ))
+        }
+    };
+    let window = params.window.unwrap_or(50).clamp(10, 1000);
+    let step = params.step.unwrap_or(window / 5).clamp(1, window);
+    let circular = b.metadata.circular;
+
+    let mut repeats = sequence::analysis::repeats(&b.sequence, circular, params.min_repeat.unwrap_or(15).max(8));
+    let repeat_count = repeats.len();
+    repeats.truncate(100);
+    Ok(Json(Analysis {
+        id: b.metadata.id.clone(),
+        size: b.sequence.len(),
+        circular,
+        gc: sequence::analysis::gc_content(&b.sequence),
+        window,
+        gc_windows: sequence::analysis::gc_windows(&b.sequence, circular, window, step),
+        tm: (b.sequence.len() <= sequence::analysis::MAX_TM_LENGTH)
+            .then(|| (sequence::primers::melting_temperature(&b.sequence) * 10.0).round() / 10.0),
+        molecular_weight: sequence::analysis::molecular_weight(&b.sequence, circular),
+        homopolymers: sequence::analysis::homopolymers(&b.sequence, circular, params.min_homopolymer.unwrap_or(6).max(2)),
+        repeat_count,
+        longest_repeat: repeats.first().cloned(),
+        repeats,
+    }))
+}
+
+fn synthesis_report(
+    id: Option<String>,
+    sequence: &str,
+    circular: bool,
+    params: &SynthesisParams,
+) -> Result<SynthesisReport, (StatusCode, Json<serde_json::Value>)> {
+    let rules = sequence::synthesis::rules(params)
+        .map_err(|message| (StatusCode::BAD_REQUEST, Json(json!({ "message": message }))))?;
+    let violations = sequence::synthesis::check(sequence, circular, &rules);
+    let fragments = sequence::synthesis::split(sequence, &rules, &violations);
+    Ok(SynthesisReport {
+        id,
+        size: sequence.len(),
+        circular,
+        profile: params
+            .profile
+            .as_deref()
+            .and_then(sequence::synthesis::find_profile)
+            .map_or(sequence::synthesis::DEFAULT_PROFILE, |p| p.name)
+            .to_string(),
+        feasible: !violations.iter().any(|v| v.severity == "error"),
+        rules,
+        violations,
+        fragments,
+    })
+}
+
+#[axum::debug_handler]
+async fn get_part_synthesis_check(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<SynthesisParams>,
+) -> Result<Json<SynthesisReport>, (StatusCode, Json<serde_json::Value>)> {
+    let b = match search::meta_search(&state, &id).await {
+        Some(b) if b.metadata.size != 0 => b,
+        _ => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Part not found" })),
+            ))
+        }
+    };
+    synthesis_report(Some(b.metadata.id.clone()), &b.sequence, b.metadata.circular, &params).map(Json)
+}
+
+#[axum::debug_handler]
+async fn post_synthesis_check(
+    Query(params): Query<SynthesisParams>,
+    body: String,
+) -> Result<Json<SynthesisReport>, (StatusCode, Json<serde_json::Value>)> {
+    let Some(query) = sequence::clean_sequence(&body) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Body is not a DNA sequence" })),
+        ));
+    };
+    if query.is_empty() || query.len() > 100_000 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Sequence must be 1 to 100000 bp long" })),
+        ));
+    }
+    synthesis_report(None, &query, params.circular.unwrap_or(false), &params).map(Json)
+}
+
+fn host_usage(name: &str) -> Result<&'static sequence::codon_usage::CodonUsage, (StatusCode, Json<serde_json::Value>)> {
+    sequence::codon_usage::find_host(name).ok_or_else(|| {
+        let hosts = sequence::codon_usage::HOSTS.iter().map(|h| h.host).collect::<Vec<_>>();
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": format!("Unknown host: {}, expected one of {}", name, hosts.join(", ")) })),
+        )
+    })
+}
+
+#[axum::debug_handler]
+async fn get_part_expression(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<ExpressionParams>,
+) -> Result<Json<ExpressionReport>, (StatusCode, Json<serde_json::Value>)> {
+    let host = host_usage(params.host.as_deref().unwrap_or(sequence::codon_usage::DEFAULT_HOST))?;
+    let b = match search::meta_search(&state, &id).await {
+        Some(b) if b.metadata.size != 0 => b,
+        _ => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Part not found" })),
+            ))
+        }
+    };
+    Ok(Json(sequence::expression::score(&b, host)))
+}
+
+#[axum::debug_handler]
+async fn get_part_recode(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<RecodeParams>,
+) -> Result<Json<RecodeResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let host = params.host.as_deref().map(host_usage).transpose()?;
+    let standard = match params.standard.as_deref() {
+        Some(name) => match sequence::compatibility::find_standard(name) {
+            Some(standard) => Some(standard),
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "message": format!("Unknown standard: {}", name) })),
+                ))
+            }
+        },
+        None => None,
+    };
+    if host.is_none() && standard.is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Give a host to optimize for, a standard to domesticate against, or both" })),
+        ));
+    }
+
+    let b = match search::meta_search(&state, &id).await {
+        Some(b) if b.metadata.size != 0 => b,
+        _ => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Part not found" })),
+            ))
+        }
+    };
+    let mut response = sequence::recode::recode(&b, host, standard)
+        .map_err(|message| (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({ "message": message }))))?;
+
+    let targets = host
+        .map(|h| format!("codon optimized for {}", h.organism))
+        .into_iter()
+        .chain(standard.map(|s| format!("domesticated for {}", s.name)))
+        .collect::<Vec<_>>();
+    let metadata = &mut response.biobrick.metadata;
+    metadata.id = params.id.unwrap_or(format!("{}_recoded", b.metadata.id));
+    metadata.name = params.name.unwrap_or(format!("{} (recoded)", b.metadata.name));
+    metadata.description = format!("{} {}", b.metadata.id, targets.join(" and "));
+    metadata.creation = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+    Ok(Json(response))
+}
+
+#[axum::debug_handler]
+async fn get_part_sequence(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<SequenceViewParams>,
+) -> Result<Json<Biobrick>, (StatusCode, Json<serde_json::Value>)> {
+    let bad_request = |message: String| (StatusCode::BAD_REQUEST, Json(json!({ "message": message })));
+    let reverse = match params.strand.as_deref() {
+        None | Some("forward") => false,
+        Some("reverse") => true,
+        Some(strand) => return Err(bad_request(format!("Unknown strand: {}", strand))),
+    };
+    let sliced = params.start.is_some() || params.end.is_some();
+    if params.rotate.is_some() && sliced {
+        return Err(bad_request("Rotation cannot be combined with start or end".to_string()));
+    }
+
+    let mut b = match search::meta_search(&state, &id).await {
+        Some(b) if b.metadata.size != 0 => b,
+        _ => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Part not found" })),
+            ))
+        }
+    };
+    let len = b.sequence.len() as i32;
+    if let Some(rotate) = params.rotate {
+        if !b.metadata.circular {
+            return Err(bad_request("Only circular parts can be rotated".to_string()));
+        }
+        if !(1..=len).contains(&rotate) {
+            return Err(bad_request(format!("Rotation must be a position from 1 to {}", len)));
+        }
+        b = assembly::rotated(&b, rotate as usize - 1);
+    }
+    if sliced {
+        let (start, end) = (params.start.unwrap_or(1), params.end.unwrap_or(len));
+        if !(1..=len).contains(&start) || !(1..=len).contains(&end) {
+            return Err(bad_request(format!("Start and end must be positions from 1 to {}", len)));
+        }
+        if start > end && !b.metadata.circular {
+            return Err(bad_request("Start is after end, which only circular parts allow".to_string()));
+        }
+        b = assembly::sliced(&b, start as usize - 1, ((end - start).rem_euclid(len) + 1) as usize);
+    }
+    if reverse {
+        b = assembly::reverse_complemented(&b);
+    }
+    sequence::orfs::translate_features(&mut b);
+    Ok(Json(b))
+}
+
+#[axum::debug_handler]
+async fn get_part_primers(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<PrimerParams>,
+) -> Result<Json<PrimersResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let sequencing = match params.mode.as_deref() {
+        None | Some("amplify") => false,
+        Some("sequencing") => true,
+        Some(mode) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": format!("Unknown mode: {}", mode) })),
+            ))
+        }
+    };
+    let tail = |tail: Option<&str>, biobrick: String| match tail {
+        None => Ok(String::new()),
+        Some(t) if t.eq_ignore_ascii_case("biobrick") => Ok(biobrick),
+        Some(t) => sequence::clean_sequence(t).ok_or((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": format!("Tail is not a DNA sequence: {}", t) })),
+        )),
+    };
+    let forward_tail = tail(params.forward_tail.as_deref(), sequence::compatibility::BIOBRICK_PREFIX.to_string())?;
+    let reverse_tail = tail(
+        params.reverse_tail.as_deref(),
+        sequence::reverse_complement(sequence::compatibility::BIOBRICK_SUFFIX),
+    )?;
+
+    let b = match search::meta_search(&state, &id).await {
+        Some(b) if b.metadata.size != 0 => b,
+        _ => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Part not found" })),
+            ))
+        }
+    };
+    let size = b.sequence.len() as i32;
+    let circular = b.metadata.circular;
+    let (start, end) = (params.start.unwrap_or(1), params.end.unwrap_or(size));
+    let in_part = |p: i32| (1..=size).contains(&p);
+    if !in_part(start) || !in_part(end) || (start > end && !circular) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": format!("Region must lie within 1..{}, and end after start unless the part is circular", size) })),
+        ));
+    }
+    let length = sequence::primers::region_length(&b, start, end);
+    if length < 40 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Region must be at least 40 bp long" })),
+        ));
+    }
+
+    let (mode, primers, product) = if sequencing {
+        let spacing = params.spacing.unwrap_or(500).clamp(100, 1000);
+        let tm = params.tm.unwrap_or(55.0);
+        ("sequencing", sequence::primers::sequencing_primers(&b, start, end, spacing, tm), None)
+    } else {
+        let tm = params.tm.unwrap_or(60.0);
+        let primers = sequence::primers::amplification_primers(&b, start, end, (&forward_tail, &reverse_tail), tm);
+        ("amplify", primers, Some(forward_tail.len() + length + reverse_tail.len()))
+    };
+
+    Ok(Json(PrimersResponse {
+        id: b.metadata.id.clone(),
+        size: b.sequence.len(),
+        circular,
+        mode: mode.to_string(),
+        region: Location {
+            start,
+            end,
+            forward: true,
+            segments: vec![],
+            partial_start: false,
+            partial_end: false,
+        },
+        product,
+        primers,
+    }))
+}
+
+#[axum::debug_handler]
+async fn post_assemble_biobrick(
+    State(state): State<AppState>,
+    Query(params): Query<AssemblyParams>,
+    Json(request): Json<BiobrickAssemblyRequest>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let format = export_format(params.format.as_deref())?;
+    if request.parts.len() < 2 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "At least two parts are needed" })),
+        ));
+    }
+    let parts = fetch_parts(&state, &request.parts).await?;
+
+    let id = request.id.clone().unwrap_or_else(|| request.parts.join("-"));
+    let name = request.name.clone().unwrap_or_else(|| composite_name(&parts));
+    match assembly::biobrick::assemble(&parts, &id, &name) {
+        Ok(composite) => Ok(export_biobrick(&state, composite, format)),
+        Err(message) => Err((StatusCode::UNPROCESSABLE_ENTITY, Json(json!({ "message": message })))),
+    }
+}
+
+#[axum::debug_handler]
+async fn post_assemble_golden_gate(
+    State(state): State<AppState>,
+    Query(params): Query<AssemblyParams>,
+    Json(request): Json<GoldenGateRequest>,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let format = export_format(params.format.as_deref())?;
+    let enzyme_name = request.enzyme.as_deref().unwrap_or("BsaI");
+    let Some(enzyme) = sequence::enzymes::find_enzyme(enzyme_name).filter(|e| assembly::golden_gate::is_type_iis(e)) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": format!("Unknown or not a Type IIS enzyme: {}", enzyme_name) })),
+        ));
+    };
+    if request.parts.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "At least one part is needed" })),
+        ));
+    }
+
+    let mut parts = Vec::new();
+    for (i, input) in request.parts.iter().enumerate() {
+        parts.push(resolve_part(&state, input, &format!("part{}", i + 1)).await?);
+    }
+    let backbone = match &request.backbone {
+        Some(input) => Some(resolve_part(&state, input, "backbone").await?),
+        None => None,
+    };
+
+    let id = request.id.clone().unwrap_or_else(|| {
+        parts.iter().chain(&backbone).map(|p| p.metadata.id.as_str()).collect::<Vec<_>>().join("-")
+    });
+    let name = request.name.clone().unwrap_or_else(|| composite_name(&parts));
+    match assembly::golden_gate::assemble(&parts, backbone.as_ref(), enzyme, &id, &name) {
+        Ok(composite) => Ok(export_biobrick(&state, composite, format)),
+        Err(errors) => Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({
+                "message": format!("Assembly design has {} error(s)", errors.len()),
+                "errors": errors,
+            })),
+        )),
+    }
+}
+
+#[axum::debug_handler]
+async fn post_assemble_gibson(
+    State(state): State<AppState>,
+    Json(request): Json<GibsonRequest>,
+) -> Result<Json<GibsonResponse>, (StatusCode, Json<serde_json::Value>)> {
+    let overlap = request.overlap.unwrap_or(20);
+    if !(15..=assembly::gibson::MAX_OVERLAP).contains(&overlap) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": format!("Overlap must be 15 to {} bp long", assembly::gibson::MAX_OVERLAP) })),
+        ));
+    }
+    if request.parts.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "At least one part is needed" })),
+        ));
+    }
+
+    let mut parts = Vec::new();
+    for (i, input) in request.parts.iter().enumerate() {
+        parts.push(resolve_part(&state, input, &format!("part{}", i + 1)).await?);
+    }
+    let backbone = resolve_part(&state, &request.backbone, "backbone").await?;
+
+    let settings = assembly::gibson::Settings {
+        overlap,
+        overlap_tm: request.overlap_tm.unwrap_or(50.0),
+        primer_tm: request.primer_tm.unwrap_or(60.0),
+        amplify_backbone: request.amplify_backbone.unwrap_or(true),
+    };
+    let id = request.id.clone().unwrap_or_else(|| {
+        parts.iter().chain([&backbone]).map(|p| p.metadata.id.as_str()).collect::<Vec<_>>().join("-")
+    });
+    let name = request.name.clone().unwrap_or_else(|| composite_name(&parts));
+    match assembly::gibson::design(&parts, &backbone, &settings, &id, &name) {
+        Ok(design) => Ok(Json(design)),
+        Err(message) => Err((StatusCode::UNPROCESSABLE_ENTITY, Json(json!({ "message": message })))),
+    }
+}
+
+/// A part by ID, or a linear part made from a raw sequence and named `fallback` if unnamed.
+async fn resolve_part(
+    state: &AppState,
+    input: &PartInput,
+    fallback: &str,
+) -> Result<Biobrick, (StatusCode, Json<serde_json::Value>)> {
+    match input {
+        PartInput::Id(id) => match search::meta_search(state, id).await {
+            Some(b) if b.metadata.size != 0 => Ok(b),
+            _ => Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": format!("Part not found: {}", id) })),
+            )),
+        },
+        PartInput::Sequence { sequence: raw, name, circular } => {
+            let Some(sequence) = sequence::clean_sequence(raw).filter(|s| !s.is_empty()) else {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "message": format!("{} is not a DNA sequence", name.as_deref().unwrap_or(fallback)) })),
+                ));
+            };
+            let name = name.clone().unwrap_or_else(|| fallback.to_string());
+            Ok(Biobrick {
+                metadata: MetaBiobrick {
+                    id: name.clone(),
+                    name,
+                    description: String::new(),
+                    creation: String::new(),
+                    size: sequence.len() as i32,
+                    circular: *circular,
+                    r#type: ontology::find_canonical("engineered-region").unwrap().into(),
+                    authors: vec![],
+                    providers: vec![],
+                },
+                sequence,
+                features: vec![],
+            })
+        }
+    }
+}
+
+/// Default name of a composite: its part names, or IDs, joined.
+fn composite_name(parts: &[Biobrick]) -> String {
+    parts
+        .iter()
+        .map(|p| if p.metadata.name.is_empty() { p.metadata.id.as_str() } else { p.metadata.name.as_str() })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// Parts by ID, in order, or a 404 naming the first one not found.
+async fn fetch_parts(state: &AppState, ids: &[String]) -> Result<Vec<Biobrick>, (StatusCode, Json<serde_json::Value>)> {
+    let found = futures::future::join_all(ids.iter().map(|id| search::meta_search(state, id))).await;
+    ids.iter()
+        .zip(found)
+        .map(|(id, part)| match part {
+            Some(b) if b.metadata.size != 0 => Ok(b),
+            _ => Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": format!("Part not found: {}", id) })),
+            )),
+        })
+        .collect()
+}
+
+/// SBOL serialization named by an assembly `format`, or `None` for Biobrick JSON.
+fn export_format(format: Option<&str>) -> Result<Option<exporters::sbol::SbolFormat>, (StatusCode, Json<serde_json::Value>)> {
+    use exporters::sbol::SbolFormat;
+    match format.map(|f| f.trim().to_lowercase()).as_deref() {
+        None | Some("json") => Ok(None),
+        Some("sbol" | "xml" | "rdf") => Ok(Some(SbolFormat::RdfXml)),
+        Some("turtle" | "ttl") => Ok(Some(SbolFormat::Turtle)),
+        Some("jsonld") => Ok(Some(SbolFormat::JsonLd)),
+        Some(other) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": format!("Unknown format {}, expected json, sbol, rdf, xml, turtle, ttl or jsonld", other) })),
+        )),
+    }
+}
+
+/// Biobrick JSON, or SBOL in `format`.
+fn export_biobrick(state: &AppState, biobrick: Biobrick, format: Option<exporters::sbol::SbolFormat>) -> Response {
+    match format {
+        None => Json(biobrick).into_response(),
+        Some(format) => {
+            let sbol = exporters::sbol::to_sbol(&biobrick, &state.sbol_namespace, format);
+            ([(header::CONTENT_TYPE, format.content_type())], sbol).into_response()
+        }
+    }
+}
+
 #[axum::debug_handler]
 async fn get_ontology() -> Json<Vec<OntologyEntrySerializable>> {
     let serializable: Vec<OntologyEntrySerializable> = ONTOLOGY.iter().map(OntologyEntrySerializable::from).collect();
     Json(serializable)
 }
 
+async fn get_enzymes() -> Json<Vec<EnzymeInfo>> {
+    Json(sequence::enzymes::ENZYMES.iter().map(EnzymeInfo::from).collect())
+}
+
 #[axum::debug_handler]
 async fn get_api_stats(
     State(state): State<AppState>,
@@ -47,7 +47,7 @@ pub fn enrich(biobrick1: Biobrick, biobrick2: Biobrick) -> Biobrick {
     }
 }
 
-fn concat_unique_providers(list1: &[MetaProvider], list2: &[MetaProvider]) -> Vec<MetaProvider> {
+pub fn concat_unique_providers(list1: &[MetaProvider], list2: &[MetaProvider]) -> Vec<MetaProvider> {
     let mut seen_names = HashSet::new();
     let mut unique = Vec::new();
     
@@ -66,7 +66,7 @@ fn concat_unique_providers(list1: &[MetaProvider], list2: &[MetaProvider]) -> Ve
     unique
 }
 
-fn concat_unique_authors(list1: &[Author], list2: &[Author]) -> Vec<Author> {
+pub fn concat_unique_authors(list1: &[Author], list2: &[Author]) -> Vec<Author> {
     let mut seen_names = HashSet::new();
     let mut unique = Vec::new();
     
@@ -91,7 +91,9 @@ pub fn merge_features(f1: MetaFeature, f2: MetaFeature) -> MetaFeature {
         name: merge_strings(&f1.name, &f2.name),
         id: f1.id,
         r#type: multiple_type_inference(&[f1.r#type.canonical.clone(), f2.r#type.canonical.clone()]).into(),
-        location: f1.location,
+        // Keep the location that describes the most segments (spliced or origin-spanning features)
+        location: if f2.location.segments.len() > f1.location.segments.len() { f2.location } else { f1.location },
+        translation: f1.translation.or(f2.translation),
     }
 }
 
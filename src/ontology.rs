@@ -164,12 +164,30 @@ pub const ONTOLOGY: &[OntologyEntry] = &[
         visual: Some("aptamer"),
         also: &["aptamer"],
         slug: Some("aptamer"),
+    },
+    OntologyEntry {
+        canonical: "protein-tag",
+        ontology: Some("SO:0000324"),
+        visual: None,
+        also: &["protein tag", "epitope tag", "affinity tag", "his tag"],
+        slug: Some("tag"),
+    },
+    OntologyEntry {
+        canonical: "recombination-site",
+        ontology: Some("SO:0000299"),
+        visual: Some("recombination-site"),
+        also: &["recombination site", "loxp", "frt site"],
+        slug: None,
     }
 ];
 
+pub fn find_canonical(canonical: &str) -> Option<&'static OntologyEntry> {
+    ONTOLOGY.iter().find(|e| e.canonical == canonical)
+}
+
 pub fn type_inference(note: &str) -> &'static OntologyEntry {
     if note.is_empty() {
-        return ONTOLOGY.iter().find(|e| e.canonical == "sequence_feature").unwrap();
+        return ONTOLOGY.iter().find(|e| e.canonical == "unknown-feature").unwrap();
     }
 
     let note_lower = note
@@ -188,7 +206,7 @@ pub fn type_inference(note: &str) -> &'static OntologyEntry {
         }
     }
 
-    ONTOLOGY.iter().find(|e| e.canonical == "sequence_feature").unwrap()
+    ONTOLOGY.iter().find(|e| e.canonical == "unknown-feature").unwrap()
 }
 
 pub fn multiple_type_inference(notes: &[String]) -> &'static OntologyEntry {
@@ -196,7 +214,7 @@ pub fn multiple_type_inference(notes: &[String]) -> &'static OntologyEntry {
 
     for note in notes {
         let entry = type_inference(note);
-        if entry.canonical != "sequence_feature" {
+        if entry.canonical != "unknown-feature" {
             results.insert(entry.canonical);
         }
     }
@@ -211,5 +229,5 @@ pub fn multiple_type_inference(notes: &[String]) -> &'static OntologyEntry {
         return ONTOLOGY.iter().find(|e| e.canonical == canonical).unwrap();
     }
 
-    ONTOLOGY.iter().find(|e| e.canonical == "sequence_feature").unwrap()
+    ONTOLOGY.iter().find(|e| e.canonical == "unknown-feature").unwrap()
 }
\ No newline at end of file
@@ -1,5 +1,9 @@
-use crate::types::{Biobrick, MetaBiobrick, MetaFeature, MetaProvider, Location};
-use crate::ontology::multiple_type_inference;
+use crate::types::{Author, Biobrick, MetaBiobrick, MetaFeature, MetaProvider, Location, Segment, Translation};
+use crate::sequence::genetic_code::BACTERIAL;
+use crate::ontology::{find_canonical, multiple_type_inference};
+
+/// Name of the structured COMMENT block holding Biobrick metadata that GenBank has no field for.
+pub const STRUCTURED_COMMENT: &str = "Bricks.bio-Data";
 
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -10,17 +14,24 @@ pub struct GenBankData {
     pub sequence: String,
     pub circular: bool,
     pub features: Vec<GenBankFeature>,
+    pub authors: Vec<String>,
+    pub comment: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct GenBankFeature {
     pub kind: String,
-    pub start: i32,
-    pub end: i32,
-    pub strand: i32,
+    pub location: Location,
     pub qualifiers: Vec<(String, String)>,
 }
 
+struct RawFeature {
+    kind: String,
+    location: String,
+    location_done: bool,
+    qualifiers: Vec<(String, String)>,
+}
+
 pub fn parse_genbank_raw(text: &str) -> Option<GenBankData> {
     let lines: Vec<&str> = text.lines().collect();
     if lines.is_empty() {
@@ -30,12 +41,26 @@ pub fn parse_genbank_raw(text: &str) -> Option<GenBankData> {
     let mut definition = String::new();
     let mut sequence = String::new();
     let mut circular = false;
-    let mut features = Vec::new();
+    let mut raw_features: Vec<RawFeature> = Vec::new();
     let mut in_features = false;
     let mut in_origin = false;
     let mut creation = None;
+    let mut header_key = String::new();
+    let mut references = 0;
+    let mut authors = String::new();
+    let mut comment = Vec::new();
     for line in lines {
         let trimmed = line.trim();
+        if !in_features && !in_origin {
+            if !line.starts_with(' ') && !trimmed.is_empty() {
+                header_key = trimmed.split_whitespace().next().unwrap_or_default().to_string();
+                if header_key == "REFERENCE" {
+                    references += 1;
+                }
+            } else if line.starts_with("  ") && !line.starts_with("            ") {
+                header_key = trimmed.split_whitespace().next().unwrap_or_default().to_string();
+            }
+        }
         if line.starts_with("LOCUS") {
             let parts: Vec<&str> = line.split_whitespace().collect();
             if parts.len() >= 2 {
@@ -67,6 +92,23 @@ pub fn parse_genbank_raw(text: &str) -> Option<GenBankData> {
             }
         } else if line.starts_with("DEFINITION") {
             definition = line["DEFINITION".len()..].trim().to_string();
+        } else if let Some(rest) = line.strip_prefix("  AUTHORS").filter(|_| references == 1) {
+            authors = rest.trim().to_string();
+        } else if let Some(rest) = line.strip_prefix("COMMENT") {
+            comment.push(rest.trim().to_string());
+        } else if !in_features && !in_origin && line.starts_with("            ") {
+            match header_key.as_str() {
+                "DEFINITION" => {
+                    definition.push(' ');
+                    definition.push_str(trimmed);
+                }
+                "AUTHORS" if references == 1 => {
+                    authors.push(' ');
+                    authors.push_str(trimmed);
+                }
+                "COMMENT" => comment.push(trimmed.to_string()),
+                _ => {}
+            }
         } else if line.starts_with("FEATURES") {
             in_features = true;
             in_origin = false;
@@ -78,29 +120,38 @@ pub fn parse_genbank_raw(text: &str) -> Option<GenBankData> {
         } else if in_features && line.starts_with("     ") && !line.starts_with("                     ") {
             let feature_line = line.trim();
             if let Some(space_idx) = feature_line.find(char::is_whitespace) {
-                let kind = feature_line[..space_idx].to_string();
-                let location = feature_line[space_idx..].trim();
-                if let Some((start, end, strand)) = parse_location(location) {
-                    features.push(GenBankFeature {
-                        kind,
-                        start,
-                        end,
-                        strand,
-                        qualifiers: Vec::new(),
-                    });
-                }
+                raw_features.push(RawFeature {
+                    kind: feature_line[..space_idx].to_string(),
+                    location: feature_line[space_idx..].trim().to_string(),
+                    location_done: false,
+                    qualifiers: Vec::new(),
+                });
             }
         } else if in_features && line.starts_with("                     /") {
-            if let Some(last_feature) = features.last_mut() {
+            if let Some(last_feature) = raw_features.last_mut() {
+                last_feature.location_done = true;
                 let qualifier = line.trim();
                 if qualifier.starts_with('/') {
                     if let Some(eq_idx) = qualifier.find('=') {
                         let key = qualifier[1..eq_idx].to_string();
-                        let value = qualifier[eq_idx + 1..].trim_matches('"').to_string();
+                        let value = qualifier[eq_idx + 1..].to_string();
                         last_feature.qualifiers.push((key, value));
                     }
                 }
             }
+        } else if in_features && line.starts_with("                     ") {
+            // Locations longer than one line continue before the first qualifier,
+            // free-text qualifiers are wrapped on spaces and translations are not
+            if let Some(last_feature) = raw_features.last_mut() {
+                if !last_feature.location_done {
+                    last_feature.location.push_str(trimmed);
+                } else if let Some((key, value)) = last_feature.qualifiers.last_mut() {
+                    if key != "translation" {
+                        value.push(' ');
+                    }
+                    value.push_str(trimmed);
+                }
+            }
         } else if in_origin && !trimmed.is_empty() {
             let seq_part: String = trimmed.chars()
                 .filter(|c| c.is_alphabetic())
@@ -111,6 +162,16 @@ pub fn parse_genbank_raw(text: &str) -> Option<GenBankData> {
     if name.is_empty() && sequence.is_empty() {
         return None;
     }
+    let features = raw_features
+        .into_iter()
+        .filter_map(|raw| {
+            parse_location(&raw.location).map(|location| GenBankFeature {
+                kind: raw.kind,
+                location,
+                qualifiers: raw.qualifiers.into_iter().map(|(key, value)| (key, unquote(&value))).collect(),
+            })
+        })
+        .collect();
     Some(GenBankData {
         name,
         definition,
@@ -118,66 +179,336 @@ pub fn parse_genbank_raw(text: &str) -> Option<GenBankData> {
         circular,
         features,
         creation,
+        authors: split_genbank_authors(&authors),
+        comment,
+    })
+}
+
+/// Qualifier value without its enclosing quotes, with `""` read back as `"`.
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"') {
+        Some(inner) => inner.strip_suffix('"').unwrap_or(inner).replace("\"\"", "\""),
+        None => value.to_string(),
+    }
+}
+
+/// Splits a REFERENCE `AUTHORS` line such as `Smith,J., Doe,J. and Roe,R.`.
+fn split_genbank_authors(authors: &str) -> Vec<String> {
+    let authors = authors.trim();
+    if authors.is_empty() || authors == "." {
+        return Vec::new();
+    }
+    let (others, last) = match authors.rsplit_once(" and ") {
+        Some((others, last)) => (others, Some(last)),
+        None => (authors, None),
+    };
+    others
+        .split(", ")
+        .chain(last)
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Key/value pairs of a `##<name>-START##` ... `##<name>-END##` structured comment.
+pub fn structured_comment(comment: &[String], name: &str) -> Vec<(String, String)> {
+    let start = format!("##{}-START##", name);
+    let end = format!("##{}-END##", name);
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    let mut inside = false;
+    for line in comment {
+        if *line == start {
+            inside = true;
+        } else if *line == end {
+            inside = false;
+        } else if inside {
+            if let Some((key, value)) = line.split_once(" :: ") {
+                pairs.push((key.trim().to_string(), value.trim().to_string()));
+            } else if let Some((_, value)) = pairs.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        }
+    }
+    pairs
+}
+
+/// Parses an INSDC feature location (`join`, `order`, `complement`, `<`/`>` partial
+/// ends, `a^b` sites) into segments listed in forward-strand order.
+/// References to other records (`J00194.1:100..202`) and `gap()` are skipped.
+pub fn parse_location(loc: &str) -> Option<Location> {
+    let compact: String = loc.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut parsed = parse_location_expr(&compact)?;
+    if parsed.is_empty() {
+        return None;
+    }
+
+    let forward = parsed.iter().filter(|p| p.segment.forward).count() * 2 >= parsed.len();
+    if !forward {
+        // Reverse-strand segments come out in 5'->3' order of the feature
+        parsed.reverse();
+    }
+
+    let first = parsed.first()?;
+    let last = parsed.last()?;
+    Some(Location {
+        start: first.segment.start,
+        end: last.segment.end,
+        forward,
+        partial_start: first.partial_start,
+        partial_end: last.partial_end,
+        segments: parsed.into_iter().map(|p| p.segment).collect(),
     })
 }
 
-fn parse_location(loc: &str) -> Option<(i32, i32, i32)> {
-    let loc = loc.trim();
-    if loc.starts_with("complement(") && loc.ends_with(')') {
-        let inner = &loc[11..loc.len() - 1];
-        return parse_location(inner).map(|(s, e, _)| (s, e, 2));
+struct ParsedSegment {
+    segment: Segment,
+    partial_start: bool,
+    partial_end: bool,
+}
+
+fn parse_location_expr(loc: &str) -> Option<Vec<ParsedSegment>> {
+    if let Some(inner) = strip_operator(loc, "complement") {
+        let mut parsed = parse_location_expr(inner)?;
+        parsed.reverse();
+        for p in parsed.iter_mut() {
+            p.segment.forward = !p.segment.forward;
+        }
+        return Some(parsed);
+    }
+    if let Some(inner) = strip_operator(loc, "join").or_else(|| strip_operator(loc, "order")) {
+        let mut parsed = Vec::new();
+        for part in split_top_level(inner) {
+            parsed.extend(parse_location_expr(part)?);
+        }
+        return Some(parsed);
+    }
+    if strip_operator(loc, "gap").is_some() || loc.contains(':') {
+        return Some(Vec::new());
+    }
+
+    let (start, end) = if let Some(dot_idx) = loc.find("..") {
+        (&loc[..dot_idx], &loc[dot_idx + 2..])
+    } else if let Some(caret_idx) = loc.find('^') {
+        (&loc[..caret_idx], &loc[caret_idx + 1..])
+    } else {
+        (loc, loc)
+    };
+    let (start, partial_start) = parse_position(start, true)?;
+    let (end, partial_end) = parse_position(end, false)?;
+    Some(vec![ParsedSegment {
+        segment: Segment { start, end, forward: true },
+        partial_start,
+        partial_end,
+    }])
+}
+
+fn strip_operator<'a>(loc: &'a str, operator: &str) -> Option<&'a str> {
+    let inner = loc.strip_prefix(operator)?.strip_prefix('(')?.strip_suffix(')')?;
+    // Reject `join(1..2),join(3..4)` where the outer parentheses do not pair up
+    let mut depth = 0;
+    for c in inner.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return None,
+            ')' => depth -= 1,
+            _ => {}
+        }
     }
-    if let Some(dot_idx) = loc.find("..") {
-        let start = loc[..dot_idx].parse::<i32>().ok()?;
-        let end = loc[dot_idx + 2..].parse::<i32>().ok()?;
-        return Some((start, end, 1));
+    Some(inner)
+}
+
+fn split_top_level(loc: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut last = 0;
+    for (i, c) in loc.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&loc[last..i]);
+                last = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&loc[last..]);
+    parts
+}
+
+/// Single position such as `12`, `<12`, `>12` or the uncertain `(10.12)`.
+/// `is_start` picks the outer bound of an uncertain position.
+fn parse_position(pos: &str, is_start: bool) -> Option<(i32, bool)> {
+    if let Some(rest) = pos.strip_prefix('<') {
+        return Some((rest.parse().ok()?, true));
     }
-    if let Ok(pos) = loc.parse::<i32>() {
-        return Some((pos, pos, 1));
+    if let Some(rest) = pos.strip_prefix('>') {
+        return Some((rest.parse().ok()?, true));
     }
-    None
+    let pos = pos.trim_start_matches('(').trim_end_matches(')');
+    if let Some((low, high)) = pos.split_once('.') {
+        let value = if is_start { low } else { high };
+        return Some((value.parse().ok()?, false));
+    }
+    Some((pos.parse().ok()?, false))
 }
 
 pub fn genbank_to_biobrick(id: &str, provider: &str, provider_link: &str, gb_data: GenBankData, date: String) -> Biobrick {
     let size = gb_data.sequence.len() as i32;
-    let features: Vec<MetaFeature> = gb_data.features.iter().map(|f| {
+    let bricks_data = structured_comment(&gb_data.comment, STRUCTURED_COMMENT);
+    let field = |key: &str| bricks_data.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+    let fields = |key: &'static str| bricks_data.iter().filter(move |(k, _)| k == key).map(|(_, v)| v.as_str());
+
+    // Feature IDs, by position in the feature table
+    let feature_ids = fields("Feature")
+        .filter_map(|value| value.split_once(" | "))
+        .filter_map(|(position, id)| Some((position.trim().parse::<usize>().ok()?, id.to_string())))
+        .collect::<std::collections::HashMap<_, _>>();
+    let features: Vec<MetaFeature> = gb_data.features.iter().enumerate().map(|(i, f)| {
         let name = f.qualifiers.iter()
             .find(|(k, _)| k == "label" || k == "gene" || k == "note" || k == "locus_tag")
             .map(|(_, v)| v.clone())
             .unwrap_or_else(|| f.kind.clone());
+        // Records written by `exporters::genbank` carry the canonical type as a /note
+        let r#type = f.qualifiers.iter()
+            .filter(|(k, _)| k == "note")
+            .find_map(|(_, v)| find_canonical(v))
+            .unwrap_or_else(|| multiple_type_inference(&[name.clone(), f.kind.clone()]));
+        let qualifier = |key: &str| f.qualifiers.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone());
+        let reported = qualifier("translation").map(|t| t.split_whitespace().collect::<String>());
+        let table = qualifier("transl_table").and_then(|t| t.trim().parse().ok());
         MetaFeature {
-            id: format!("{}_{}", name, f.start),
+            id: feature_ids.get(&(i + 1)).cloned().unwrap_or_else(|| format!("{}_{}", name, f.location.start)),
             name: name.clone(),
-            r#type: multiple_type_inference(&[name.clone(), f.kind.clone()]).into(),
-            location: Location {
-                start: f.start,
-                end: f.end,
-                forward: f.strand == 1,
-            },
+            r#type: r#type.into(),
+            location: f.location.clone(),
+            translation: (reported.is_some() || table.is_some()).then(|| Translation {
+                table: table.unwrap_or(BACTERIAL),
+                reported,
+                ..Default::default()
+            }),
         }
     }).collect();
-    let name = if gb_data.definition.is_empty() {
-        String::from("")
-    } else {
-        gb_data.definition.clone()
-    };
+
+    let name = field("Name").unwrap_or_else(|| gb_data.definition.clone());
+    let r#type = field("Type")
+        .and_then(|canonical| find_canonical(&canonical))
+        .unwrap_or_else(|| multiple_type_inference(&[gb_data.definition.clone()]));
+
+    let mut providers = vec![MetaProvider {
+        name: provider.to_string(),
+        link: provider_link.to_string(),
+        date,
+    }];
+    for value in fields("Provider") {
+        let mut parts = value.split(" | ");
+        let (Some(name), Some(link)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        if providers.iter().all(|p| p.name != name) {
+            providers.push(MetaProvider {
+                name: name.to_string(),
+                link: link.to_string(),
+                date: parts.next().unwrap_or_default().to_string(),
+            });
+        }
+    }
+
+    // Author names may contain commas, which the AUTHORS line cannot tell apart
+    let mut authors = fields("Author")
+        .map(|value| match value.split_once(" | ") {
+            Some((name, role)) => Author {
+                name: name.to_string(),
+                role: Some(role.to_string()),
+            },
+            None => Author {
+                name: value.to_string(),
+                role: None,
+            },
+        })
+        .collect::<Vec<_>>();
+    if authors.is_empty() {
+        authors = gb_data.authors.iter().map(|name| Author {
+            name: name.clone(),
+            role: None,
+        }).collect();
+    }
+
     Biobrick {
         metadata: MetaBiobrick {
             id: id.to_string(),
             name,
-            r#type: multiple_type_inference(&[gb_data.definition.clone()]).into(),
+            r#type: r#type.into(),
             circular: gb_data.circular,
             size,
-            providers: vec![MetaProvider {
-                name: provider.to_string(),
-                link: provider_link.to_string(),
-                date,
-            }],
-            description: String::from(""),
-            authors: vec![],
-            creation: gb_data.creation.unwrap_or_default(),
+            providers,
+            description: field("Description").unwrap_or_default(),
+            authors,
+            creation: field("Creation").or(gb_data.creation).unwrap_or_default(),
         },
         sequence: gb_data.sequence,
         features,
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Expected location as (start, end, forward, partial start, partial end, segments).
+    type Expected = (i32, i32, bool, bool, bool, &'static [(i32, i32, bool)]);
+
+    #[test]
+    fn parses_location_grammar() {
+        let cases: &[(&str, Option<Expected>)] = &[
+            // Ranges, single bases and sites between two bases
+            ("100..200", Some((100, 200, true, false, false, &[(100, 200, true)]))),
+            ("42", Some((42, 42, true, false, false, &[(42, 42, true)]))),
+            ("12^13", Some((12, 13, true, false, false, &[(12, 13, true)]))),
+            // Partial ends and uncertain positions
+            ("<1..200", Some((1, 200, true, true, false, &[(1, 200, true)]))),
+            ("1..>200", Some((1, 200, true, false, true, &[(1, 200, true)]))),
+            ("<1..>200", Some((1, 200, true, true, true, &[(1, 200, true)]))),
+            ("(10.12)..(198.200)", Some((10, 200, true, false, false, &[(10, 200, true)]))),
+            // Complement
+            ("complement(100..200)", Some((100, 200, false, false, false, &[(100, 200, false)]))),
+            ("complement(<100..>200)", Some((100, 200, false, true, true, &[(100, 200, false)]))),
+            // Join and order, listed in forward strand order
+            ("join(1..10,20..30)", Some((1, 30, true, false, false, &[(1, 10, true), (20, 30, true)]))),
+            ("order(1..10,20..30)", Some((1, 30, true, false, false, &[(1, 10, true), (20, 30, true)]))),
+            ("join(<1..10,20..>30)", Some((1, 30, true, true, true, &[(1, 10, true), (20, 30, true)]))),
+            ("complement(join(1..10,20..30))", Some((1, 30, false, false, false, &[(1, 10, false), (20, 30, false)]))),
+            (
+                "join(complement(20..30),complement(1..10))",
+                Some((1, 30, false, false, false, &[(1, 10, false), (20, 30, false)])),
+            ),
+            ("join(1..10,join(20..30,40..50))", Some((1, 50, true, false, false, &[(1, 10, true), (20, 30, true), (40, 50, true)]))),
+            // Joins over the origin of circular records
+            ("join(90..100,1..10)", Some((90, 10, true, false, false, &[(90, 100, true), (1, 10, true)]))),
+            ("complement(join(90..100,1..10))", Some((90, 10, false, false, false, &[(90, 100, false), (1, 10, false)]))),
+            // Whitespace from wrapped lines, remote references and gaps
+            ("join(1..10,\n                     20..30)", Some((1, 30, true, false, false, &[(1, 10, true), (20, 30, true)]))),
+            ("join(1..10,J00194.1:100..202)", Some((1, 10, true, false, false, &[(1, 10, true)]))),
+            ("join(1..10,gap(50),20..30)", Some((1, 30, true, false, false, &[(1, 10, true), (20, 30, true)]))),
+            ("J00194.1:100..202", None),
+            // Malformed
+            ("", None),
+            ("abc", None),
+            ("1..x", None),
+            ("join(1..10", None),
+            ("join(1..2),join(3..4)", None),
+        ];
+
+        for (input, expected) in cases {
+            let parsed = parse_location(input).map(|l| {
+                let segments = l.segments.iter().map(|s| (s.start, s.end, s.forward)).collect::<Vec<_>>();
+                (l.start, l.end, l.forward, l.partial_start, l.partial_end, segments)
+            });
+            let expected = expected.map(|(start, end, forward, partial_start, partial_end, segments)| {
+                (start, end, forward, partial_start, partial_end, segments.to_vec())
+            });
+            assert_eq!(parsed, expected, "{}", input);
+        }
+    }
+}
@@ -1 +1,2 @@
 pub mod genbank;
+pub mod sbol;
@@ -0,0 +1,575 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ontology::{multiple_type_inference, OntologyEntry, ONTOLOGY};
+use crate::types::{Author, Biobrick, Location, MetaBiobrick, MetaFeature, MetaProvider, Segment};
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const SBOL2: &str = "http://sbols.org/v2#";
+const SBOL3: &str = "http://sbols.org/v3#";
+const DCTERMS: &str = "http://purl.org/dc/terms/";
+const DC: &str = "http://purl.org/dc/elements/1.1/";
+const PROV: &str = "http://www.w3.org/ns/prov#";
+
+const MAX_DEPTH: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct SbolData {
+    pub uri: String,
+    pub display_id: String,
+    pub name: String,
+    pub description: String,
+    pub creation: Option<String>,
+    pub sequence: String,
+    pub circular: bool,
+    pub r#type: &'static OntologyEntry,
+    pub authors: Vec<String>,
+    pub providers: Vec<MetaProvider>,
+    pub features: Vec<MetaFeature>,
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Literal(String),
+    Resource(String),
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    types: Vec<String>,
+    properties: Vec<(String, Value)>,
+}
+
+/// Minimal RDF/XML reader covering the striped syntax written by SBOL libraries.
+#[derive(Debug, Default)]
+struct Graph {
+    nodes: HashMap<String, Node>,
+    order: Vec<String>,
+}
+
+impl Graph {
+    fn parse(text: &str) -> Option<Graph> {
+        let document = roxmltree::Document::parse(text).ok()?;
+        let root = document.root_element();
+        if root.tag_name().namespace() != Some(RDF) || root.tag_name().name() != "RDF" {
+            return None;
+        }
+
+        let mut graph = Graph::default();
+        let mut blank = 0;
+        for element in root.children().filter(|n| n.is_element()) {
+            graph.read_node(element, &mut blank);
+        }
+        Some(graph)
+    }
+
+    fn read_node(&mut self, element: roxmltree::Node, blank: &mut usize) -> String {
+        let uri = element
+            .attribute((RDF, "about"))
+            .or_else(|| element.attribute((RDF, "nodeID")))
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| {
+                *blank += 1;
+                format!("_:b{}", blank)
+            });
+
+        let mut node = Node::default();
+        let tag = expand(element);
+        if tag != format!("{}Description", RDF) {
+            node.types.push(tag);
+        }
+
+        for property in element.children().filter(|n| n.is_element()) {
+            let predicate = expand(property);
+            let value = if let Some(resource) = property.attribute((RDF, "resource")) {
+                Value::Resource(resource.to_string())
+            } else if let Some(child) = property.children().find(|n| n.is_element()) {
+                Value::Resource(self.read_node(child, blank))
+            } else {
+                Value::Literal(property.text().unwrap_or_default().trim().to_string())
+            };
+            if predicate == format!("{}type", RDF) {
+                if let Value::Resource(ref t) = value {
+                    node.types.push(t.clone());
+                }
+            }
+            node.properties.push((predicate, value));
+        }
+
+        let entry = self.nodes.entry(uri.clone()).or_insert_with(|| {
+            self.order.push(uri.clone());
+            Node::default()
+        });
+        entry.types.extend(node.types);
+        entry.properties.extend(node.properties);
+        uri
+    }
+
+    fn of_type(&self, types: &[String]) -> Vec<&str> {
+        self.order
+            .iter()
+            .filter(|uri| self.nodes[*uri].types.iter().any(|t| types.contains(t)))
+            .map(|uri| uri.as_str())
+            .collect()
+    }
+
+    fn values<'a>(&'a self, uri: &str, predicates: &[String]) -> Vec<&'a Value> {
+        self.nodes
+            .get(uri)
+            .map(|node| {
+                node.properties
+                    .iter()
+                    .filter(|(p, _)| predicates.contains(p))
+                    .map(|(_, v)| v)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn literal(&self, uri: &str, predicates: &[String]) -> Option<String> {
+        self.values(uri, predicates).into_iter().find_map(|v| match v {
+            Value::Literal(s) if !s.is_empty() => Some(s.clone()),
+            _ => None,
+        })
+    }
+
+    fn resources(&self, uri: &str, predicates: &[String]) -> Vec<String> {
+        self.values(uri, predicates)
+            .into_iter()
+            .map(|v| match v {
+                Value::Resource(s) | Value::Literal(s) => s.clone(),
+            })
+            .collect()
+    }
+}
+
+fn expand(element: roxmltree::Node) -> String {
+    format!("{}{}", element.tag_name().namespace().unwrap_or_default(), element.tag_name().name())
+}
+
+/// Same term in the SBOL2 and SBOL3 namespaces.
+fn sbol(name: &str) -> Vec<String> {
+    vec![format!("{}{}", SBOL2, name), format!("{}{}", SBOL3, name)]
+}
+
+fn terms(names: &[(&str, &str)]) -> Vec<String> {
+    names.iter().map(|(ns, name)| format!("{}{}", ns, name)).collect()
+}
+
+/// Parses an SBOL2 or SBOL3 RDF/XML document. The root component is the one whose
+/// displayId matches `id`, otherwise the first one that is not used as a sub-component.
+pub fn parse_sbol_raw(text: &str, id: Option<&str>) -> Option<SbolData> {
+    let graph = Graph::parse(text)?;
+
+    let components = graph.of_type(&terms(&[(SBOL2, "ComponentDefinition"), (SBOL3, "Component")]));
+    if components.is_empty() {
+        return None;
+    }
+
+    let used_as_child: HashSet<String> = graph
+        .of_type(&terms(&[(SBOL2, "Component"), (SBOL3, "SubComponent")]))
+        .into_iter()
+        .flat_map(|c| graph.resources(c, &terms(&[(SBOL2, "definition"), (SBOL3, "instanceOf")])))
+        .collect();
+
+    let display_id = |uri: &str| {
+        graph
+            .literal(uri, &sbol("displayId"))
+            .unwrap_or_else(|| uri.trim_end_matches('/').rsplit('/').next().unwrap_or(uri).to_string())
+    };
+    let root = id
+        .and_then(|id| {
+            components.iter().find(|c| {
+                let display = display_id(c);
+                display.eq_ignore_ascii_case(id) || c.trim_end_matches("/1").ends_with(&format!("/{}", id))
+            })
+        })
+        .or_else(|| components.iter().find(|c| !used_as_child.contains(**c)))
+        .or_else(|| components.first())?
+        .to_string();
+
+    let name = graph
+        .literal(&root, &terms(&[(DCTERMS, "title"), (SBOL3, "name")]))
+        .unwrap_or_else(|| display_id(&root));
+    let description = graph
+        .literal(&root, &terms(&[(DCTERMS, "description"), (SBOL3, "description")]))
+        .unwrap_or_default();
+    let creation = graph.literal(&root, &terms(&[(DCTERMS, "created"), (PROV, "generatedAtTime")]));
+    let authors = graph
+        .values(&root, &terms(&[(DC, "creator"), (DCTERMS, "creator")]))
+        .into_iter()
+        .filter_map(|v| match v {
+            Value::Literal(s) if !s.is_empty() => Some(s.clone()),
+            _ => None,
+        })
+        .collect();
+    // Provenance written by our exporter: one prov:Activity per provider
+    let providers = graph
+        .resources(&root, &terms(&[(PROV, "wasGeneratedBy")]))
+        .iter()
+        .filter_map(|activity| {
+            Some(MetaProvider {
+                name: graph.literal(activity, &sbol("name"))?,
+                link: graph.resources(activity, &terms(&[(DCTERMS, "source")])).pop().unwrap_or_default(),
+                date: graph.literal(activity, &terms(&[(PROV, "endedAtTime")])).unwrap_or_default(),
+            })
+        })
+        .collect();
+    let circular = graph
+        .resources(&root, &sbol("type"))
+        .iter()
+        .any(|t| t.ends_with("SO:0000988"));
+
+    let mut features = Vec::new();
+    collect_features(&graph, &root, &mut features, &|p| p, true, 0);
+
+    Some(SbolData {
+        display_id: display_id(&root),
+        name,
+        description,
+        creation,
+        sequence: component_sequence(&graph, &root),
+        circular,
+        r#type: role_type(&graph.resources(&root, &sbol("role"))),
+        authors,
+        providers,
+        features,
+        uri: root,
+    })
+}
+
+fn component_sequence(graph: &Graph, component: &str) -> String {
+    graph
+        .resources(component, &terms(&[(SBOL2, "sequence"), (SBOL3, "hasSequence")]))
+        .iter()
+        .find_map(|seq| graph.literal(seq, &sbol("elements")))
+        .map(|s| s.chars().filter(|c| c.is_ascii_alphabetic()).collect::<String>().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Maps SO roles onto the ontology, falling back to iGEM `partType` roles.
+fn role_type(roles: &[String]) -> &'static OntologyEntry {
+    roles
+        .iter()
+        .find_map(|role| {
+            let so = role.rsplit('/').next()?;
+            ONTOLOGY.iter().find(|e| e.ontology == Some(so))
+        })
+        .unwrap_or_else(|| {
+            let hints = roles
+                .iter()
+                .filter_map(|role| role.split("partType/").nth(1))
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>();
+            multiple_type_inference(&hints)
+        })
+}
+
+/// Reads sequence annotations (SBOL2) or features (SBOL3) of `component`, descending
+/// into sub-components with `map` translating their coordinates into the root sequence.
+fn collect_features(
+    graph: &Graph,
+    component: &str,
+    features: &mut Vec<MetaFeature>,
+    map: &dyn Fn(i32) -> i32,
+    forward: bool,
+    depth: usize,
+) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+
+    let mut located = HashSet::new();
+    let mut unlocated = Vec::new();
+    for annotation in graph.resources(component, &terms(&[(SBOL2, "sequenceAnnotation"), (SBOL3, "hasFeature")])) {
+        let child = graph
+            .resources(&annotation, &terms(&[(SBOL2, "component"), (SBOL3, "instanceOf")]))
+            .into_iter()
+            .next();
+        let definition = child.as_ref().and_then(|c| {
+            if is_sbol2_component(graph, c) {
+                located.insert(c.clone());
+                graph.resources(c, &sbol("definition")).into_iter().next()
+            } else {
+                Some(c.clone())
+            }
+        });
+
+        let segments = graph
+            .resources(&annotation, &terms(&[(SBOL2, "location"), (SBOL3, "hasLocation")]))
+            .into_iter()
+            .filter_map(|location| read_segment(graph, &location))
+            .map(|s| {
+                let (start, end) = (map(s.start), map(s.end));
+                Segment {
+                    start: start.min(end),
+                    end: start.max(end),
+                    forward: s.forward == forward,
+                }
+            })
+            .collect::<Vec<_>>();
+        if segments.is_empty() {
+            if let Some(definition) = definition {
+                unlocated.push((annotation, definition));
+            }
+            continue;
+        }
+
+        let mut roles = graph.resources(&annotation, &sbol("role"));
+        if let Some(ref def) = definition {
+            roles.extend(graph.resources(def, &sbol("role")));
+        }
+        let name = graph
+            .literal(&annotation, &terms(&[(DCTERMS, "title"), (SBOL3, "name")]))
+            .or_else(|| definition.as_ref().and_then(|d| graph.literal(d, &terms(&[(DCTERMS, "title"), (SBOL3, "name")]))))
+            .or_else(|| graph.literal(&annotation, &sbol("displayId")))
+            .unwrap_or_else(|| role_type(&roles).canonical.to_string());
+        let feature_id = graph
+            .literal(&annotation, &sbol("displayId"))
+            .unwrap_or_else(|| format!("{}_{}", name, segments[0].start));
+
+        let strand_forward = segments.iter().filter(|s| s.forward).count() * 2 >= segments.len();
+        let (first, last) = (segments[0].clone(), segments[segments.len() - 1].clone());
+        features.push(MetaFeature {
+            id: feature_id,
+            name,
+            r#type: role_type(&roles).into(),
+            location: Location {
+                start: first.start,
+                end: last.end,
+                forward: strand_forward,
+                segments,
+                partial_start: false,
+                partial_end: false,
+            },
+            translation: None,
+        });
+
+        if let Some(def) = definition {
+            // `first` is already in root coordinates
+            let child_map = move |p: i32| if first.forward { first.start + p - 1 } else { first.end - p + 1 };
+            collect_features(graph, &def, features, &child_map, first.forward, depth + 1);
+        }
+    }
+
+    for child in graph.resources(component, &sbol("component")) {
+        if !located.contains(&child) {
+            if let Some(definition) = graph.resources(&child, &sbol("definition")).into_iter().next() {
+                unlocated.push((child, definition));
+            }
+        }
+    }
+    locate_by_constraints(graph, component, unlocated, features, map, forward);
+}
+
+fn is_sbol2_component(graph: &Graph, uri: &str) -> bool {
+    graph
+        .nodes
+        .get(uri)
+        .is_some_and(|n| n.types.iter().any(|t| *t == format!("{}Component", SBOL2)))
+}
+
+fn read_segment(graph: &Graph, location: &str) -> Option<Segment> {
+    let forward = !graph
+        .resources(location, &sbol("orientation"))
+        .iter()
+        .any(|o| o.ends_with("reverseComplement") || o.ends_with("SO:0001031"));
+    if let (Some(start), Some(end)) = (
+        graph.literal(location, &sbol("start")).and_then(|s| s.parse().ok()),
+        graph.literal(location, &sbol("end")).and_then(|s| s.parse().ok()),
+    ) {
+        return Some(Segment { start, end, forward });
+    }
+    let at = graph.literal(location, &sbol("at")).and_then(|s| s.parse().ok())?;
+    Some(Segment { start: at, end: at, forward })
+}
+
+/// Sub-components without a location only carry their order through `precedes`
+/// constraints; place them by searching their own sequence in that order, on the strand of
+/// `component` in the root sequence.
+fn locate_by_constraints(
+    graph: &Graph,
+    component: &str,
+    unlocated: Vec<(String, String)>,
+    features: &mut Vec<MetaFeature>,
+    map: &dyn Fn(i32) -> i32,
+    forward: bool,
+) {
+    if unlocated.is_empty() {
+        return;
+    }
+
+    let mut precedes: Vec<(String, String)> = Vec::new();
+    for constraint in graph.resources(component, &terms(&[(SBOL2, "sequenceConstraint"), (SBOL3, "hasConstraint")])) {
+        let is_precedes = graph
+            .resources(&constraint, &sbol("restriction"))
+            .iter()
+            .any(|r| r.ends_with("precedes"));
+        let subject = graph.resources(&constraint, &sbol("subject")).into_iter().next();
+        let object = graph.resources(&constraint, &sbol("object")).into_iter().next();
+        if let (true, Some(subject), Some(object)) = (is_precedes, subject, object) {
+            precedes.push((subject, object));
+        }
+    }
+
+    // Kahn ordering, document order breaking ties
+    let mut ordered = Vec::new();
+    let mut remaining = unlocated;
+    while !remaining.is_empty() {
+        let next = remaining
+            .iter()
+            .position(|(c, _)| !precedes.iter().any(|(s, o)| o == c && remaining.iter().any(|(r, _)| r == s)))
+            .unwrap_or(0);
+        ordered.push(remaining.remove(next));
+    }
+
+    let parent_sequence = component_sequence(graph, component);
+    let mut cursor = 0;
+    for (child, definition) in ordered {
+        let child_sequence = component_sequence(graph, &definition);
+        if child_sequence.is_empty() {
+            continue;
+        }
+        let Some(found) = parent_sequence.get(cursor..).and_then(|s| s.find(&child_sequence)) else {
+            continue;
+        };
+        let start = (cursor + found + 1) as i32;
+        let end = start + child_sequence.len() as i32 - 1;
+        cursor += found + child_sequence.len();
+
+        let roles = graph.resources(&definition, &sbol("role"));
+        let name = graph
+            .literal(&definition, &terms(&[(DCTERMS, "title"), (SBOL3, "name")]))
+            .or_else(|| graph.literal(&child, &sbol("displayId")))
+            .unwrap_or_else(|| role_type(&roles).canonical.to_string());
+        let (start, end) = (map(start).min(map(end)), map(start).max(map(end)));
+        features.push(MetaFeature {
+            id: graph.literal(&child, &sbol("displayId")).unwrap_or_else(|| format!("{}_{}", name, start)),
+            name,
+            r#type: role_type(&roles).into(),
+            location: Location {
+                start,
+                end,
+                forward,
+                segments: vec![Segment { start, end, forward }],
+                partial_start: false,
+                partial_end: false,
+            },
+            translation: None,
+        });
+    }
+}
+
+pub fn sbol_to_biobrick(id: &str, provider: &str, provider_link: &str, sbol_data: SbolData, date: String) -> Biobrick {
+    let mut providers = vec![MetaProvider {
+        name: provider.to_string(),
+        link: provider_link.to_string(),
+        date,
+    }];
+    for previous in sbol_data.providers {
+        if providers.iter().all(|p| p.name != previous.name) {
+            providers.push(previous);
+        }
+    }
+
+    Biobrick {
+        metadata: MetaBiobrick {
+            id: id.to_string(),
+            name: sbol_data.name,
+            description: sbol_data.description,
+            creation: sbol_data.creation.unwrap_or_default(),
+            size: sbol_data.sequence.len() as i32,
+            circular: sbol_data.circular,
+            r#type: sbol_data.r#type.into(),
+            authors: sbol_data.authors.into_iter().map(|name| Author { name, role: None }).collect(),
+            providers,
+        },
+        sequence: sbol_data.sequence,
+        features: sbol_data.features,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequence::reverse_complement;
+
+    fn definition(uri: &str, role: &str, sequence: &str, body: &str) -> String {
+        format!(
+            r#"<sbol:ComponentDefinition rdf:about="{uri}">
+  <sbol:displayId>{id}</sbol:displayId>
+  <sbol:role rdf:resource="http://identifiers.org/so/{role}"/>
+  <sbol:sequence rdf:resource="{uri}_seq"/>
+  {body}
+</sbol:ComponentDefinition>
+<sbol:Sequence rdf:about="{uri}_seq"><sbol:elements>{sequence}</sbol:elements></sbol:Sequence>"#,
+            id = uri.rsplit('/').next().unwrap(),
+        )
+    }
+
+    #[test]
+    fn constrained_sub_components_follow_parent_strand() {
+        let root = "gcatgctagcttgacagctagctcagtcctaggtataatgctagcaaagaggagaaaatgc";
+        let device = reverse_complement(&root[10..40]);
+        // The device sits reversed in the root; its parts are only ordered by a constraint
+        let document = format!(
+            r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#" xmlns:sbol="http://sbols.org/v2#">
+{}
+{}
+{}
+{}
+</rdf:RDF>"#,
+            definition(
+                "https://example.org/root",
+                "SO:0000804",
+                root,
+                r#"<sbol:component><sbol:Component rdf:about="https://example.org/root/device_c">
+    <sbol:displayId>device_c</sbol:displayId>
+    <sbol:definition rdf:resource="https://example.org/device"/>
+  </sbol:Component></sbol:component>
+  <sbol:sequenceAnnotation><sbol:SequenceAnnotation rdf:about="https://example.org/root/device_a">
+    <sbol:displayId>device_a</sbol:displayId>
+    <sbol:component rdf:resource="https://example.org/root/device_c"/>
+    <sbol:location><sbol:Range rdf:about="https://example.org/root/device_a/range">
+      <sbol:start>11</sbol:start>
+      <sbol:end>40</sbol:end>
+      <sbol:orientation rdf:resource="http://sbols.org/v2#reverseComplement"/>
+    </sbol:Range></sbol:location>
+  </sbol:SequenceAnnotation></sbol:sequenceAnnotation>"#,
+            ),
+            definition(
+                "https://example.org/device",
+                "SO:0000804",
+                &device,
+                r#"<sbol:component><sbol:Component rdf:about="https://example.org/device/first">
+    <sbol:displayId>first</sbol:displayId>
+    <sbol:definition rdf:resource="https://example.org/promoter"/>
+  </sbol:Component></sbol:component>
+  <sbol:component><sbol:Component rdf:about="https://example.org/device/second">
+    <sbol:displayId>second</sbol:displayId>
+    <sbol:definition rdf:resource="https://example.org/rbs"/>
+  </sbol:Component></sbol:component>
+  <sbol:sequenceConstraint><sbol:SequenceConstraint rdf:about="https://example.org/device/order">
+    <sbol:restriction rdf:resource="http://sbols.org/v2#precedes"/>
+    <sbol:subject rdf:resource="https://example.org/device/first"/>
+    <sbol:object rdf:resource="https://example.org/device/second"/>
+  </sbol:SequenceConstraint></sbol:sequenceConstraint>"#,
+            ),
+            definition("https://example.org/promoter", "SO:0000167", &device[..8], ""),
+            definition("https://example.org/rbs", "SO:0000139", &device[15..25], ""),
+        );
+
+        let parsed = parse_sbol_raw(&document, Some("root")).unwrap();
+        let located = parsed
+            .features
+            .iter()
+            .map(|f| (f.id.as_str(), f.location.start, f.location.end, f.location.forward))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            located,
+            [("device_a", 11, 40, false), ("first", 33, 40, false), ("second", 16, 25, false)]
+        );
+        for feature in &parsed.features {
+            assert!(feature.location.segments.iter().all(|s| !s.forward), "{}", feature.id);
+        }
+    }
+}
@@ -20,6 +20,10 @@ impl super::ProviderEnumTrait for AddGeneProvider {
         format!("https://www.addgene.org/{}/sequences/", id)
     }
 
+    fn accepts(&self, id: &str) -> bool {
+        super::is_addgene_id(id)
+    }
+
     async fn parse(&self, id: &str, html_text: &str) -> Option<Biobrick> {
         let client = reqwest::Client::builder()
             .user_agent("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
@@ -19,6 +19,10 @@ impl ProviderEnumTrait for EnsemblProvider {
         format!("https://www.ensembl.org/Homo_sapiens/Export/Output/Gene?db=core;flank3_display=0;flank5_display=0;g={id};output=genbank;_format=Text", id = id)
     }
 
+    fn accepts(&self, id: &str) -> bool {
+        super::is_ensembl_id(id)
+    }
+
     async fn parse(&self, id: &str, _text: &str) -> Option<Biobrick> {
         let url = self.url(id);
         let client = reqwest::Client::new();
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use crate::types::{Biobrick, MetaBiobrick, MetaFeature, MetaProvider, Location, Author};
+use crate::types::{Biobrick, MetaBiobrick, MetaFeature, MetaProvider, Location, Segment, Author};
 use crate::ontology::multiple_type_inference;
 use scraper::{Html, Selector};
 
@@ -19,6 +19,10 @@ impl super::ProviderEnumTrait for IgemPartsProvider {
         self.link(id)
     }
     
+    fn accepts(&self, id: &str) -> bool {
+        super::is_igem_id(id)
+    }
+
     async fn parse(&self, id: &str, html_text: &str) -> Option<Biobrick> {
         println!("    Parsing iGEM Parts HTML, length: {}", html_text.len());
         
@@ -167,7 +171,11 @@ fn extract_features_from_edit(document: &Html) -> Vec<MetaFeature> {
                         start,
                         end,
                         forward: strand == 1,
+                        segments: vec![Segment { start, end, forward: strand == 1 }],
+                        partial_start: false,
+                        partial_end: false,
                     },
+                    translation: None,
                 });
             }
         }
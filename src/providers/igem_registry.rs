@@ -23,6 +23,10 @@ impl super::ProviderEnumTrait for IgemApiProvider {
         format!("https://api.registry.igem.org/v1/parts/slugs/{}", slugify(id))
     }
 
+    fn accepts(&self, id: &str) -> bool {
+        super::is_igem_id(id)
+    }
+
     async fn parse(&self, id: &str, json_text: &str) -> Option<Biobrick> {
         let api_part: ApiPart = serde_json::from_str(json_text).ok()?;
         let sequence = api_part.sequence.clone().unwrap_or_default().to_lowercase();
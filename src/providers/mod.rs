@@ -6,6 +6,7 @@ pub trait ProviderEnumTrait: Send + Sync {
     fn name(&self) -> &'static str;
     fn link(&self, id: &str) -> String;
     fn url(&self, id: &str) -> String;
+    fn accepts(&self, id: &str) -> bool;
     async fn parse(&self, id: &str, text: &str) -> Option<Biobrick>;
 }
 pub mod synbiohub;
@@ -22,6 +23,66 @@ use crate::providers::igem_registry::IgemApiProvider;
 use crate::providers::ensembl::EnsemblProvider;
 use crate::providers::addgene::AddGeneProvider;
 
+/// iGEM part or backbone name, e.g. `BBa_B0034` or `pSB1C3`.
+pub fn is_igem_id(id: &str) -> bool {
+    let id = id.trim().to_lowercase();
+    (id.starts_with("bba_") && id.len() > 4) || (id.starts_with("psb") && id.len() > 3)
+}
+
+/// RefSeq (`NM_000546.6`, `NZ_CP009072.1`) or INSDC (`U49845`, `AB123456`, `AAAA01000001`)
+/// nucleotide accession.
+pub fn is_ncbi_accession(id: &str) -> bool {
+    let id = id.trim().to_uppercase();
+    let id = match id.split_once('.') {
+        Some((base, version)) if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()) => base.to_string(),
+        Some(_) => return false,
+        None => id,
+    };
+
+    if let Some((prefix, rest)) = id.split_once('_') {
+        // RefSeq numbers have 6 digits or more, after the INSDC prefix of WGS and
+        // assembly-based records, as in `NZ_CP009072`
+        let letters = rest.chars().take_while(|c| c.is_ascii_alphabetic()).count();
+        let digits = &rest[letters..];
+        return prefix.len() == 2
+            && prefix.chars().all(|c| c.is_ascii_alphabetic())
+            && letters <= 6
+            && digits.len() >= 6
+            && digits.chars().all(|c| c.is_ascii_digit());
+    }
+
+    let letters = id.chars().take_while(|c| c.is_ascii_alphabetic()).count();
+    let digits = &id[letters..];
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    matches!(
+        (letters, digits.len()),
+        (1, 5) | (2, 6) | (2, 8) | (4, 8..=10) | (6, 9..=11)
+    )
+}
+
+/// Ensembl stable gene or transcript ID, e.g. `ENSG00000139618` or `ENSMUST00000000001.5`.
+pub fn is_ensembl_id(id: &str) -> bool {
+    let id = id.trim().to_uppercase();
+    let id = id.split('.').next().unwrap_or_default();
+    let Some(rest) = id.strip_prefix("ENS") else {
+        return false;
+    };
+    let letters = rest.chars().take_while(|c| c.is_ascii_alphabetic()).count();
+    let (species_and_kind, digits) = rest.split_at(letters);
+    (species_and_kind.ends_with('G') || species_and_kind.ends_with('T'))
+        && species_and_kind.len() <= 4
+        && digits.len() == 11
+        && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// AddGene plasmid IDs are plain integers, e.g. `26093`.
+pub fn is_addgene_id(id: &str) -> bool {
+    let id = id.trim();
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_digit())
+}
+
 pub enum ProviderEnum {
     SynBioHub(SynBioHubProvider),
     IgemParts(IgemPartsProvider),
@@ -62,6 +123,16 @@ impl ProviderEnum {
             ProviderEnum::AddGene(p) => p.url(id),
         }
     }
+    pub fn accepts(&self, id: &str) -> bool {
+        match self {
+            ProviderEnum::SynBioHub(p) => p.accepts(id),
+            ProviderEnum::IgemParts(p) => p.accepts(id),
+            ProviderEnum::Ncbi(p) => p.accepts(id),
+            ProviderEnum::IgemApi(p) => p.accepts(id),
+            ProviderEnum::Ensembl(p) => p.accepts(id),
+            ProviderEnum::AddGene(p) => p.accepts(id),
+        }
+    }
     pub async fn parse(&self, id: &str, text: &str) -> Option<Biobrick> {
         use crate::providers::ProviderEnumTrait;
         match self {
@@ -85,3 +156,82 @@ pub fn get_all_providers() -> Vec<ProviderEnum> {
         ProviderEnum::AddGene(AddGeneProvider),
     ]
 }
+
+/// Providers that can own `id`, or every provider when the ID shape is unknown.
+pub fn get_providers_for(id: &str) -> Vec<ProviderEnum> {
+    let matching: Vec<ProviderEnum> = get_all_providers()
+        .into_iter()
+        .filter(|provider| provider.accepts(id))
+        .collect();
+
+    if matching.is_empty() {
+        get_all_providers()
+    } else {
+        matching
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn igem_ids() {
+        for id in ["BBa_B0034", "bba_k1234567", "pSB1C3", " pSB1K3 "] {
+            assert!(is_igem_id(id), "{}", id);
+        }
+        for id in ["BBa_", "pSB", "B0034", "NM_000546", "26093", ""] {
+            assert!(!is_igem_id(id), "{}", id);
+        }
+    }
+
+    #[test]
+    fn ncbi_accessions() {
+        let accepted = [
+            "U49845",
+            "AB123456",
+            "AB12345678",
+            "AAAA01000001",
+            "AAAAAA010000001",
+            "NM_000546.6",
+            "nc_000913.3",
+            "NZ_CP009072.1",
+            "NZ_JAHXYZ010000001",
+            "NC_000913",
+        ];
+        for id in accepted {
+            assert!(is_ncbi_accession(id), "{}", id);
+        }
+        let rejected = ["BBa_B0034", "26093", "U4984", "NM_", "NM_ABC", "NC_1", "NC_12345", "NZ_CP12345", "N_000546", "NZ_ABCDEFG1", "U49845.", "U49845.a", "ENSG00000139618"];
+        for id in rejected {
+            assert!(!is_ncbi_accession(id), "{}", id);
+        }
+    }
+
+    #[test]
+    fn ensembl_ids() {
+        for id in ["ENSG00000139618", "ENST00000380152.8", "ENSMUSG00000017167", "ensmust00000000001.5"] {
+            assert!(is_ensembl_id(id), "{}", id);
+        }
+        for id in ["ENSP00000369497", "ENSG0000013961", "ENSABCDEG00000139618", "NM_000546", "BBa_B0034"] {
+            assert!(!is_ensembl_id(id), "{}", id);
+        }
+    }
+
+    #[test]
+    fn addgene_ids() {
+        for id in ["26093", " 1 "] {
+            assert!(is_addgene_id(id), "{}", id);
+        }
+        for id in ["", "26093a", "AB123456", "-1"] {
+            assert!(!is_addgene_id(id), "{}", id);
+        }
+    }
+
+    #[test]
+    fn numeric_ids_go_to_ncbi_and_addgene() {
+        let names = get_providers_for("26093").iter().map(|p| p.name()).collect::<Vec<_>>();
+        assert_eq!(names.len(), 2, "{:?}", names);
+        assert!(get_providers_for("NZ_CP009072.1").iter().all(|p| matches!(p, ProviderEnum::Ncbi(_))));
+    }
+}
@@ -18,6 +18,11 @@ impl super::ProviderEnumTrait for NcbiProvider {
         format!("https://eutils.ncbi.nlm.nih.gov/entrez/eutils/efetch.fcgi?db=nuccore&id={}&rettype=gb&retmode=text", id)
     }
     
+    fn accepts(&self, id: &str) -> bool {
+        // Bare GI numbers look like AddGene IDs, so both providers are asked
+        super::is_ncbi_accession(id) || super::is_addgene_id(id)
+    }
+
     async fn parse(&self, id: &str, text: &str) -> Option<Biobrick> {
         if text.contains("Error:") || text.contains("Failed") {
             println!("    NCBI error: {}", text.chars().take(100).collect::<String>());
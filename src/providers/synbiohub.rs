@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use crate::types::Biobrick;
-use crate::parsers::genbank::{parse_genbank_raw, genbank_to_biobrick};
+use crate::parsers::sbol::{parse_sbol_raw, sbol_to_biobrick};
 
 pub struct SynBioHubProvider;
 
@@ -15,14 +15,19 @@ impl super::ProviderEnumTrait for SynBioHubProvider {
     }
     
     fn url(&self, id: &str) -> String {
-        format!("https://synbiohub.org/public/igem/{}/1/gb", id)
+        format!("https://synbiohub.org/public/igem/{}/1/sbol", id)
     }
     
+    fn accepts(&self, id: &str) -> bool {
+        super::is_igem_id(id)
+    }
+
     async fn parse(&self, id: &str, text: &str) -> Option<Biobrick> {
-        parse_genbank_raw(text)
-            .map(|gb_data| {
+        parse_sbol_raw(text, Some(id))
+            .filter(|sbol_data| !sbol_data.sequence.is_empty())
+            .map(|sbol_data| {
                 let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
-                genbank_to_biobrick(id, self.name(), &self.link(id), gb_data, now)
+                sbol_to_biobrick(id, self.name(), &self.link(id), sbol_data, now)
             })
     }
 }
@@ -2,20 +2,22 @@ use futures::future::join_all;
 
 use crate::AppState;
 use crate::types::Biobrick;
-use crate::providers::get_all_providers;
+use crate::providers::get_providers_for;
 
 pub async fn meta_search(state: &AppState, id: &str) -> Option<Biobrick> {
     let id_normalized = normalize_id(id);
 
-    if let Some(cached) = state.cache.get_part(&id_normalized) {
+    if let Some(mut cached) = state.cache.get_part(&id_normalized) {
         spawn_refresh(state.clone(), id.to_string(), id_normalized).await;
+        // Entries cached before translations existed
+        crate::sequence::orfs::translate_features(&mut cached);
         return Some(cached);
     }
 
-    let fetched = fetch_and_merge(&state.client, id).await;
+    let fetched = fetch_and_merge(state, id).await;
 
     if let Some(ref biobrick) = fetched {
-        if let Err(error) = state.cache.put_part(&normalize_id(id), biobrick) {
+        if let Err(error) = cache_part(state, normalize_id(id), biobrick.clone()).await {
             eprintln!("Failed to persist cache entry for {}: {}", id, error);
         }
     }
@@ -23,6 +25,15 @@ pub async fn meta_search(state: &AppState, id: &str) -> Option<Biobrick> {
     fetched
 }
 
+/// Caches `biobrick` on the blocking pool, since indexing it runs the embedding model.
+async fn cache_part(state: &AppState, id_normalized: String, biobrick: Biobrick) -> Result<(), String> {
+    let cache = state.cache.clone();
+    match tokio::task::spawn_blocking(move || cache.put_part(&id_normalized, &biobrick)).await {
+        Ok(result) => result.map_err(|error| error.to_string()),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
 async fn spawn_refresh(state: AppState, id: String, id_normalized: String) {
     let mut refresh_in_flight = state.refresh_in_flight.lock().await;
     if !refresh_in_flight.insert(id_normalized.clone()) {
@@ -31,10 +42,10 @@ async fn spawn_refresh(state: AppState, id: String, id_normalized: String) {
     drop(refresh_in_flight);
 
     tokio::spawn(async move {
-        let refresh_result = fetch_and_merge(&state.client, &id).await;
+        let refresh_result = fetch_and_merge(&state, &id).await;
 
-        if let Some(ref biobrick) = refresh_result {
-            if let Err(error) = state.cache.put_part(&id_normalized, biobrick) {
+        if let Some(biobrick) = refresh_result {
+            if let Err(error) = cache_part(&state, id_normalized.clone(), biobrick).await {
                 eprintln!("Failed to refresh cache entry for {}: {}", id, error);
             }
         }
@@ -44,10 +55,11 @@ async fn spawn_refresh(state: AppState, id: String, id_normalized: String) {
     });
 }
 
-async fn fetch_and_merge(client: &reqwest::Client, id: &str) -> Option<Biobrick> {
+async fn fetch_and_merge(state: &AppState, id: &str) -> Option<Biobrick> {
     println!("Searching for part: {}", id);
+    let client = &state.client;
     
-    let providers = get_all_providers();
+    let providers = get_providers_for(id);
     
     let mut futures = Vec::new();
 
@@ -95,6 +107,26 @@ async fn fetch_and_merge(client: &reqwest::Client, id: &str) -> Option<Biobrick>
         final_biobrick = crate::merge::enrich(final_biobrick, next_result);
     }
 
+    if final_biobrick.features.is_empty() && state.auto_annotate {
+        let cache = state.cache.clone();
+        let sequence = final_biobrick.sequence.clone();
+        let circular = final_biobrick.metadata.circular;
+        let id_normalized = normalize_id(id);
+        let annotated = tokio::task::spawn_blocking(move || {
+            crate::sequence::annotate::annotate(&cache, &sequence, circular, Some(&id_normalized))
+        })
+        .await;
+        match annotated {
+            Ok(Ok(annotations)) => {
+                println!("Annotated {} features", annotations.len());
+                final_biobrick.features = annotations.into_iter().map(|a| a.feature).collect();
+            }
+            Ok(Err(error)) => eprintln!("Failed to annotate {}: {}", id, error),
+            Err(error) => eprintln!("Failed to annotate {}: {}", id, error),
+        }
+    }
+    crate::sequence::orfs::translate_features(&mut final_biobrick);
+
     Some(final_biobrick)
 }
 
@@ -1,69 +1,443 @@
+use std::collections::{BTreeSet, HashMap};
 use std::sync::{Mutex, OnceLock};
 
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
 
 use crate::cache::SqliteCache;
-use crate::types::Biobrick;
+use crate::hnsw::{cosine_similarity, decode_links, encode_links, Hnsw, HnswParams};
+use crate::sequence::codon_usage;
+use crate::types::{Biobrick, CacheSearchParams, SearchFacets, SearchHit, SearchMode, SearchResponse, SortOrder};
 
-pub fn cache_search(cache: &SqliteCache, query: &str, n: usize) -> Result<Vec<(Biobrick, f64)>, rusqlite::Error> {
-    let n = n.min(50);
-    if n == 0 {
-        return Ok(vec![]);
+/// Stored next to each embedding, so a model change invalidates them all.
+pub const EMBEDDING_MODEL: &str = "AllMiniLML6V2";
+
+/// In-memory copy of `parts_embeddings`, keyed by normalized part ID, with its HNSW graph.
+#[derive(Debug, Default)]
+pub struct EmbeddingIndex {
+    ids: Vec<String>,
+    positions: HashMap<String, usize>,
+    embeddings: Vec<Vec<f32>>,
+    graph: Hnsw,
+}
+
+impl EmbeddingIndex {
+    pub fn new(params: HnswParams) -> Self {
+        Self {
+            graph: Hnsw::new(params),
+            ..Self::default()
+        }
     }
 
-    let query = query.trim();
-    if query.is_empty() {
-        return Ok(vec![]);
+    /// Restores an index from saved graph nodes, in position order.
+    fn restore(params: HnswParams, nodes: Vec<(String, Vec<f32>, Vec<u8>)>) -> Option<Self> {
+        let mut index = Self::new(params);
+        let mut links = Vec::with_capacity(nodes.len());
+        for (id, embedding, node_links) in nodes {
+            index.positions.insert(id.clone(), index.ids.len());
+            index.ids.push(id);
+            index.embeddings.push(embedding);
+            links.push(decode_links(&node_links)?);
+        }
+        index.graph = Hnsw::from_links(params, links)?;
+        Some(index)
     }
 
-    let parts = cache.list_parts()?;
-    if parts.is_empty() {
-        return Ok(vec![]);
+    /// Inserts or replaces an embedding, returning the graph nodes whose links changed.
+    pub fn upsert(&mut self, id: &str, embedding: Vec<f32>) -> Vec<usize> {
+        let node = match self.positions.get(id) {
+            Some(&i) => {
+                self.embeddings[i] = embedding;
+                i
+            }
+            None => {
+                self.positions.insert(id.to_string(), self.ids.len());
+                self.ids.push(id.to_string());
+                self.embeddings.push(embedding);
+                self.ids.len() - 1
+            }
+        };
+        self.graph.insert(node, &self.embeddings)
     }
 
-    let docs = parts.iter().map(biobrick_to_text).collect::<Vec<_>>();
-    let model_mutex = embedding_model().map_err(rusqlite::Error::ToSqlConversionFailure)?;
-    let model = model_mutex
-        .lock()
-        .map_err(|_| {
-            rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Embedding model lock poisoned",
-            )))
-        })?;
-
-    let mut inputs = Vec::with_capacity(docs.len() + 1);
-    inputs.push(query.to_string());
-    inputs.extend(docs);
-
-    let embeddings = model
-        .embed(inputs, None)
-        .map_err(|e| {
-            rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                e.to_string(),
-            )))
-        })?;
-
-    if embeddings.len() < 2 {
-        return Ok(vec![]);
+    /// Exact cosine ranking restricted to `ids`, for filtered searches where the graph would
+    /// mostly return excluded parts.
+    pub fn search_within<'a>(&'a self, query: &[f32], ids: impl IntoIterator<Item = &'a str>, k: usize) -> Vec<(&'a str, f64)> {
+        let mut scored = ids
+            .into_iter()
+            .filter_map(|id| self.positions.get(id).map(|&i| (self.ids[i].as_str(), cosine_similarity(query, &self.embeddings[i]))))
+            .collect::<Vec<_>>();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(k);
+        scored
+    }
+
+    pub fn search(&self, query: &[f32], k: usize) -> Vec<(&str, f64)> {
+        self.graph
+            .search(query, &self.embeddings, k)
+            .into_iter()
+            .map(|(i, similarity)| (self.ids[i].as_str(), similarity))
+            .collect()
+    }
+
+    /// Rows for `parts_graph`: (position, id, encoded links).
+    fn graph_nodes<'a>(&self, nodes: impl IntoIterator<Item = &'a usize>) -> Vec<(usize, String, Vec<u8>)> {
+        nodes
+            .into_iter()
+            .map(|&i| (i, self.ids[i].clone(), encode_links(self.graph.links(i))))
+            .collect()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.positions.contains_key(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+/// Rank constant of reciprocal rank fusion, as in Cormack et al. (2009).
+const RRF_K: f64 = 60.0;
+
+/// Ranked searches are paginated over their best results only.
+const MAX_RESULTS: usize = 1000;
+
+/// Position after the last result of a page, in (key descending, relevance descending, ID
+/// ascending) order, so that pages stay stable when parts are cached in between.
+#[derive(Debug, Clone)]
+pub struct Cursor {
+    key: f64,
+    relevance: f64,
+    id: String,
+}
+
+impl Cursor {
+    pub fn decode(cursor: &str) -> Option<Cursor> {
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|i| cursor.get(i..i + 2).and_then(|h| u8::from_str_radix(h, 16).ok()))
+            .collect::<Option<Vec<u8>>>()?;
+        let text = String::from_utf8(bytes).ok()?;
+        let bits = |range: std::ops::Range<usize>| text.get(range).and_then(|h| u64::from_str_radix(h, 16).ok());
+        Some(Cursor {
+            key: f64::from_bits(bits(0..16)?),
+            relevance: f64::from_bits(bits(16..32)?),
+            id: text.get(32..)?.to_string(),
+        })
     }
 
-    let query_embedding = &embeddings[0];
-    let doc_embeddings = &embeddings[1..];
+    fn encode(ranked: &Ranked) -> String {
+        format!("{:016x}{:016x}{}", ranked.key.to_bits(), ranked.relevance.to_bits(), ranked.id)
+            .bytes()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    fn precedes(&self, ranked: &Ranked) -> bool {
+        let (position, cursor) = ((ranked.key, ranked.relevance), (self.key, self.relevance));
+        position < cursor || (position == cursor && ranked.id > self.id)
+    }
+}
+
+/// A matching part, ranked by `key`: its relevance, or its signed `sort` metric.
+struct Ranked {
+    id: String,
+    key: f64,
+    relevance: f64,
+}
+
+pub fn cache_search(cache: &SqliteCache, params: &CacheSearchParams, cursor: Option<&Cursor>) -> Result<SearchResponse, rusqlite::Error> {
+    let query = params.q.trim();
+    let mode = params.mode.unwrap_or_default();
+    let requested = params.n.unwrap_or(10).min(50);
+    let offset = params.offset.unwrap_or(0);
+
+    let matching = cache
+        .filter_parts(params)?
+        .into_iter()
+        .map(|(id, canonical, providers)| (id, (canonical, providers)))
+        .collect::<HashMap<_, _>>();
+
+    let (mut ranked, mut snippets) = if query.is_empty() {
+        (matching.keys().map(|id| (id.clone(), 1.0)).collect(), HashMap::new())
+    } else {
+        rank(cache, query, mode, params.has_filters().then_some(&matching))?
+    };
+    ranked.retain(|(id, _)| matching.contains_key(id));
+    // Ascending sorts rank by the negated metric
+    let sign = match params.sort.map(|sort| params.order.unwrap_or(sort.default_order())) {
+        Some(SortOrder::Asc) => -1.0,
+        _ => 1.0,
+    };
+    let mut ranked = match params.sort {
+        None => ranked
+            .into_iter()
+            .map(|(id, relevance)| Ranked { id, key: relevance, relevance })
+            .collect::<Vec<_>>(),
+        Some(sort) => {
+            // Parts without a coding sequence have no score, and drop out
+            let host = params.host.as_deref().and_then(codon_usage::find_host).map_or(codon_usage::DEFAULT_HOST, |h| h.host);
+            let scores = cache.expression_scores(host, sort)?;
+            ranked
+                .into_iter()
+                .filter_map(|(id, relevance)| {
+                    let score = *scores.get(&id)?;
+                    Some(Ranked { id, key: sign * score, relevance })
+                })
+                .collect()
+        }
+    };
+    ranked.sort_by(|a, b| {
+        b.key
+            .partial_cmp(&a.key)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| b.relevance.partial_cmp(&a.relevance).unwrap_or(std::cmp::Ordering::Equal))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    if !query.is_empty() {
+        ranked.truncate(MAX_RESULTS);
+    }
+
+    let mut facets = SearchFacets::default();
+    for Ranked { id, .. } in &ranked {
+        let (canonical, providers) = &matching[id];
+        *facets.types.entry(canonical.clone()).or_default() += 1;
+        for provider in providers {
+            *facets.providers.entry(provider.clone()).or_default() += 1;
+        }
+    }
 
-    let candidate_indices = ann_candidates(query_embedding, doc_embeddings, n);
+    let mut remaining = ranked
+        .iter()
+        .filter(|ranked| cursor.is_none_or(|c| c.precedes(ranked)))
+        .skip(offset);
+    let page = remaining.by_ref().take(requested).collect::<Vec<_>>();
+    let next_cursor = match (page.last(), remaining.next()) {
+        (Some(last), Some(_)) => Some(Cursor::encode(last)),
+        _ => None,
+    };
 
-    let mut scored: Vec<(Biobrick, f64)> = candidate_indices
+    let results = page
         .into_iter()
-        .filter_map(|i| {
-            let score = cosine_similarity(query_embedding, &doc_embeddings[i]);
-            parts.get(i).cloned().map(|p| (p, score.max(0.0)))
+        .filter_map(|ranked| {
+            let snippet = snippets.remove(&ranked.id);
+            cache.get_part(&ranked.id).map(|biobrick| SearchHit {
+                r#match: ranked.relevance,
+                score: params.sort.map(|_| sign * ranked.key),
+                snippet,
+                biobrick,
+            })
         })
-        .filter(|(_, score)| *score > 0.05)
+        .collect::<Vec<_>>();
+
+    Ok(SearchResponse {
+        query: query.to_string(),
+        mode,
+        requested,
+        offset,
+        count: results.len(),
+        total: ranked.len(),
+        next_cursor,
+        facets,
+        results,
+    })
+}
+
+type Filtered<'a> = Option<&'a HashMap<String, (String, Vec<String>)>>;
+/// Scores by part ID, and highlighted snippets of the lexical matches.
+type Ranking = (Vec<(String, f64)>, HashMap<String, String>);
+
+/// Scores for `query` in the given mode, with lexical snippets. When `filtered` is set,
+/// rankings are computed over those parts only instead of the best results overall.
+fn rank(cache: &SqliteCache, query: &str, mode: SearchMode, filtered: Filtered) -> Result<Ranking, rusqlite::Error> {
+    let depth = if filtered.is_some() { usize::MAX } else { MAX_RESULTS };
+
+    let lexical = match (mode, fts_query(query)) {
+        (SearchMode::Semantic, _) | (_, None) => vec![],
+        (_, Some(fts_query)) => cache
+            .lexical_search(&fts_query, depth)?
+            .into_iter()
+            .filter(|(id, _, _)| filtered.is_none_or(|f| f.contains_key(id)))
+            .collect(),
+    };
+    let semantic = match mode {
+        SearchMode::Lexical => vec![],
+        SearchMode::Semantic => semantic_search(cache, query, filtered)?,
+        // Lexical results are still useful when the embedding model is unavailable
+        SearchMode::Hybrid => semantic_search(cache, query, filtered).unwrap_or_else(|error| {
+            eprintln!("Semantic search failed, falling back to lexical: {}", error);
+            vec![]
+        }),
+    };
+
+    let scored = match mode {
+        SearchMode::Lexical => lexical
+            .iter()
+            .map(|(id, bm25, _)| (id.clone(), -bm25 / (1.0 - bm25)))
+            .collect(),
+        SearchMode::Semantic => semantic,
+        SearchMode::Hybrid => {
+            let mut fused: HashMap<String, f64> = HashMap::new();
+            let rankings = [
+                lexical.iter().map(|(id, _, _)| id.clone()).collect::<Vec<_>>(),
+                semantic.into_iter().map(|(id, _)| id).collect(),
+            ];
+            for ranking in rankings {
+                for (rank, id) in ranking.into_iter().enumerate() {
+                    // Normalized so a part ranked first by both searches scores 1
+                    *fused.entry(id).or_default() += (RRF_K + 1.0) / (2.0 * (RRF_K + rank as f64 + 1.0));
+                }
+            }
+            fused.into_iter().collect()
+        }
+    };
+
+    let snippets = lexical
+        .into_iter()
+        .map(|(id, _, snippet)| (id, snippet))
         .collect();
+    Ok((scored, snippets))
+}
 
-    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-    scored.truncate(n);
-    Ok(scored)
+fn semantic_search(cache: &SqliteCache, query: &str, filtered: Filtered) -> Result<Vec<(String, f64)>, rusqlite::Error> {
+    if cache.embeddings().read().map_err(|_| embedding_error("Embedding index lock poisoned"))?.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let query_embedding = embed(vec![query.to_string()])?.pop().unwrap_or_default();
+
+    let index = cache
+        .embeddings()
+        .read()
+        .map_err(|_| embedding_error("Embedding index lock poisoned"))?;
+    let scored = match filtered {
+        Some(filtered) => index.search_within(&query_embedding, filtered.keys().map(|id| id.as_str()), MAX_RESULTS),
+        None => index.search(&query_embedding, MAX_RESULTS),
+    };
+    Ok(scored
+        .into_iter()
+        .map(|(id, score)| (id.to_string(), score.max(0.0)))
+        .filter(|(_, score)| *score > 0.05)
+        .collect())
+}
+
+/// FTS5 query matching any word of `query`; each word is quoted so that user input is never
+/// read as FTS5 syntax, and IDs like `BBa_J23100` match as a phrase of their tokens.
+fn fts_query(query: &str) -> Option<String> {
+    let words = query
+        .split_whitespace()
+        .filter(|w| w.chars().any(|c| c.is_alphanumeric()))
+        .map(|w| format!("\"{}\"", w.replace('"', "\"\"")))
+        .collect::<Vec<_>>();
+    (!words.is_empty()).then(|| words.join(" OR "))
+}
+
+/// Embeds a part when it is new or when its text or the model changed since the stored embedding.
+pub fn index_part(cache: &SqliteCache, id_normalized: &str, biobrick: &Biobrick) -> Result<(), rusqlite::Error> {
+    let text = biobrick_to_text(biobrick);
+    let hash = text_hash(&text);
+    if cache.get_embedding_version(id_normalized) == Some((EMBEDDING_MODEL.to_string(), hash.clone())) {
+        return Ok(());
+    }
+
+    let embedding = embed(vec![text])?.pop().unwrap_or_default();
+    cache.put_embedding(id_normalized, EMBEDDING_MODEL, &hash, &embedding)?;
+
+    // The lock is held while saving so concurrent inserts cannot persist stale links
+    let mut index = cache
+        .embeddings()
+        .write()
+        .map_err(|_| embedding_error("Embedding index lock poisoned"))?;
+    let touched = index.upsert(id_normalized, embedding);
+    cache.put_graph_nodes(&index.graph_nodes(&touched))
+}
+
+/// Loads stored embeddings and their graph at startup, embedding only the parts that are
+/// missing or stale. The graph is rebuilt when it no longer matches the stored embeddings.
+pub fn sync_index(cache: &SqliteCache) -> Result<usize, rusqlite::Error> {
+    let params = cache
+        .embeddings()
+        .read()
+        .map_err(|_| embedding_error("Embedding index lock poisoned"))?
+        .graph
+        .params();
+    let mut stored = cache
+        .list_embeddings(EMBEDDING_MODEL)?
+        .into_iter()
+        .map(|(id, hash, embedding)| (id, (hash, embedding)))
+        .collect::<HashMap<_, _>>();
+
+    let graph_nodes = cache.list_graph_nodes()?;
+    let restorable = graph_nodes
+        .iter()
+        .enumerate()
+        .all(|(i, (position, id, _))| *position == i && stored.contains_key(id));
+    let restored = restorable
+        .then(|| {
+            let nodes = graph_nodes
+                .into_iter()
+                .map(|(_, id, links)| {
+                    let embedding = stored[&id].1.clone();
+                    (id, embedding, links)
+                })
+                .collect();
+            EmbeddingIndex::restore(params, nodes)
+        })
+        .flatten();
+    let mut index = match restored {
+        Some(index) => index,
+        None => {
+            cache.clear_graph()?;
+            EmbeddingIndex::new(params)
+        }
+    };
+
+    let mut touched = BTreeSet::new();
+    let mut stale = Vec::new();
+    for (id, part) in cache.list_part_entries()? {
+        let text = biobrick_to_text(&part);
+        let hash = text_hash(&text);
+        match stored.remove(&id) {
+            Some((stored_hash, embedding)) if stored_hash == hash => {
+                if !index.contains(&id) {
+                    touched.extend(index.upsert(&id, embedding));
+                }
+            }
+            _ => stale.push((id, hash, text)),
+        }
+    }
+
+    for chunk in stale.chunks(64) {
+        let embeddings = embed(chunk.iter().map(|(_, _, text)| text.clone()).collect())?;
+        for ((id, hash, _), embedding) in chunk.iter().zip(embeddings) {
+            cache.put_embedding(id, EMBEDDING_MODEL, hash, &embedding)?;
+            touched.extend(index.upsert(id, embedding));
+        }
+    }
+
+    cache.put_graph_nodes(&index.graph_nodes(&touched))?;
+    let count = index.len();
+    *cache.embeddings().write().map_err(|_| embedding_error("Embedding index lock poisoned"))? = index;
+    Ok(count)
+}
+
+fn embed(texts: Vec<String>) -> Result<Vec<Vec<f32>>, rusqlite::Error> {
+    let model_mutex = embedding_model().map_err(rusqlite::Error::ToSqlConversionFailure)?;
+    let model = model_mutex
+        .lock()
+        .map_err(|_| embedding_error("Embedding model lock poisoned"))?;
+
+    model
+        .embed(texts, None)
+        .map_err(|e| embedding_error(&e.to_string()))
+}
+
+fn embedding_error(message: &str) -> rusqlite::Error {
+    rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(message.to_string())))
 }
 
 fn embedding_model() -> Result<&'static Mutex<TextEmbedding>, Box<dyn std::error::Error + Send + Sync>> {
@@ -77,6 +451,14 @@ fn embedding_model() -> Result<&'static Mutex<TextEmbedding>, Box<dyn std::error
     Ok(MODEL.get_or_init(|| Mutex::new(embedding)))
 }
 
+/// FNV-1a, stable across builds unlike `DefaultHasher`.
+fn text_hash(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
 fn biobrick_to_text(biobrick: &Biobrick) -> String {
     let mut text = String::new();
     text.push_str("id: ");
@@ -116,77 +498,22 @@ fn biobrick_to_text(biobrick: &Biobrick) -> String {
     text
 }
 
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
-    if a.is_empty() || b.is_empty() || a.len() != b.len() {
-        return 0.0;
-    }
-
-    let mut dot = 0.0_f64;
-    let mut norm_a = 0.0_f64;
-    let mut norm_b = 0.0_f64;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    for i in 0..a.len() {
-        let av = a[i] as f64;
-        let bv = b[i] as f64;
-        dot += av * bv;
-        norm_a += av * av;
-        norm_b += bv * bv;
+    fn ranked(id: &str, key: f64, relevance: f64) -> Ranked {
+        Ranked { id: id.to_string(), key, relevance }
     }
 
-    if norm_a == 0.0 || norm_b == 0.0 {
-        return 0.0;
-    }
-
-    (dot / (norm_a.sqrt() * norm_b.sqrt())).clamp(-1.0, 1.0)
-}
-
-fn ann_candidates(query_embedding: &[f32], doc_embeddings: &[Vec<f32>], n: usize) -> Vec<usize> {
-    const ANN_THRESHOLD: usize = 200;
-    if doc_embeddings.len() <= ANN_THRESHOLD {
-        return (0..doc_embeddings.len()).collect();
+    #[test]
+    fn cursor_resumes_after_last_result() {
+        // An ascending ENC sort: keys are negated, ties broken by relevance then ID
+        let page = [ranked("b", -31.5, 0.9), ranked("a", -31.5, 0.4), ranked("c", -31.5, 0.4), ranked("d", -40.0, 1.0)];
+        let cursor = Cursor::decode(&Cursor::encode(&page[1])).unwrap();
+        assert_eq!(
+            page.iter().map(|r| cursor.precedes(r)).collect::<Vec<_>>(),
+            [false, false, true, true]
+        );
     }
-
-    let dim = query_embedding.len();
-    let planes = random_planes(dim, 64);
-    let q_sig = signature(query_embedding, &planes);
-
-    let mut hamming_ranked = doc_embeddings
-        .iter()
-        .enumerate()
-        .map(|(i, emb)| (i, (q_sig ^ signature(emb, &planes)).count_ones()))
-        .collect::<Vec<_>>();
-
-    hamming_ranked.sort_by_key(|(_, dist)| *dist);
-    let k = (n * 12).max(64).min(doc_embeddings.len());
-    hamming_ranked.into_iter().take(k).map(|(i, _)| i).collect()
-}
-
-fn random_planes(dim: usize, count: usize) -> Vec<Vec<f32>> {
-    let mut seed: u64 = 0x9E3779B97F4A7C15;
-    (0..count)
-        .map(|_| {
-            (0..dim)
-                .map(|_| {
-                    seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
-                    let bits = ((seed >> 33) as u32) | 1;
-                    let v = (bits as f32) / (u32::MAX as f32);
-                    (v * 2.0) - 1.0
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect()
 }
-
-fn signature(embedding: &[f32], planes: &[Vec<f32>]) -> u64 {
-    let mut sig = 0_u64;
-    for (bit, plane) in planes.iter().take(64).enumerate() {
-        let mut dot = 0.0_f32;
-        for i in 0..embedding.len().min(plane.len()) {
-            dot += embedding[i] * plane[i];
-        }
-        if dot >= 0.0 {
-            sig |= 1_u64 << bit;
-        }
-    }
-    sig
-}
\ No newline at end of file
@@ -0,0 +1,272 @@
+//! Sequence statistics: GC content, melting temperature, molecular weight, homopolymer runs and
+//! repeats.
+
+use std::collections::HashMap;
+
+use crate::types::{GcWindow, Homopolymer, MolecularWeight, Repeat};
+
+use super::{complement, location, slice};
+
+/// Nearest-neighbor melting temperatures are only meaningful for short sequences.
+pub const MAX_TM_LENGTH: usize = 100;
+/// Occurrences of a repeat seed paired with each other. Seeds of low-complexity stretches occur
+/// all along them, and pairing every occurrence would be quadratic in their length.
+const MAX_SEED_OCCURRENCES: usize = 64;
+
+pub fn round(value: f64, digits: i32) -> f64 {
+    let scale = 10f64.powi(digits);
+    (value * scale).round() / scale
+}
+
+fn is_base(base: u8) -> bool {
+    matches!(base, b'A' | b'C' | b'G' | b'T')
+}
+
+/// Fraction of G and C bases (S included) in `sequence`.
+pub fn gc_content(sequence: &str) -> f64 {
+    if sequence.is_empty() {
+        return 0.0;
+    }
+    let gc = sequence.bytes().filter(|b| matches!(b.to_ascii_uppercase(), b'G' | b'C' | b'S')).count();
+    round(gc as f64 / sequence.len() as f64, 3)
+}
+
+/// GC content of windows of `window` bases every `step` bases. Windows of circular sequences
+/// wrap around the origin; the last window of a linear one ends with the sequence.
+pub fn gc_windows(sequence: &str, circular: bool, window: usize, step: usize) -> Vec<GcWindow> {
+    let len = sequence.len();
+    if len == 0 {
+        return vec![];
+    }
+    let window = window.min(len);
+    let mut starts = if circular && window < len {
+        (0..len).step_by(step.max(1)).collect::<Vec<_>>()
+    } else {
+        (0..=len - window).step_by(step.max(1)).collect::<Vec<_>>()
+    };
+    if !circular && starts.last() != Some(&(len - window)) {
+        starts.push(len - window);
+    }
+    starts
+        .into_iter()
+        .map(|start| {
+            let location = location(start as i64, window, true, len as i64);
+            GcWindow {
+                start: location.start,
+                end: location.end,
+                gc: gc_content(&slice(sequence, start as i64, window)),
+            }
+        })
+        .collect()
+}
+
+/// Anhydrous weight of a deoxynucleotide monophosphate, in g/mol.
+fn residue_weight(base: u8) -> f64 {
+    match base {
+        b'A' => 313.21,
+        b'C' => 289.18,
+        b'G' => 329.21,
+        b'T' => 304.2,
+        _ => 308.95, // average, for ambiguous bases
+    }
+}
+
+fn strand_weight(bases: impl Iterator<Item = u8>, circular: bool) -> f64 {
+    let weight = bases.map(residue_weight).sum::<f64>();
+    // A linear strand has 5' and 3' hydroxyls instead of a closing phosphodiester bond
+    if circular {
+        weight
+    } else {
+        weight - 61.96
+    }
+}
+
+pub fn molecular_weight(sequence: &str, circular: bool) -> MolecularWeight {
+    let bases = sequence.to_uppercase().into_bytes();
+    if bases.is_empty() {
+        return MolecularWeight { single_stranded: 0.0, double_stranded: 0.0 };
+    }
+    let single = strand_weight(bases.iter().copied(), circular);
+    let double = single + strand_weight(bases.iter().map(|&b| complement(b)), circular);
+    MolecularWeight {
+        single_stranded: round(single, 2),
+        double_stranded: round(double, 2),
+    }
+}
+
+/// Runs of at least `min_length` identical bases, spanning the origin of circular sequences.
+pub fn homopolymers(sequence: &str, circular: bool, min_length: usize) -> Vec<Homopolymer> {
+    let bases = sequence.to_uppercase().into_bytes();
+    let len = bases.len();
+    if len == 0 {
+        return vec![];
+    }
+    // Start from a run boundary so that no run is split by the origin
+    let offset = if circular {
+        (0..len).find(|&i| bases[i] != bases[(i + len - 1) % len]).unwrap_or(0)
+    } else {
+        0
+    };
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for i in 1..=len {
+        if i == len || bases[(offset + i) % len] != bases[(offset + start) % len] {
+            let length = i - start;
+            if length >= min_length.max(1) {
+                let base = bases[(offset + start) % len];
+                runs.push(Homopolymer {
+                    base: (base as char).to_string(),
+                    length,
+                    location: location((offset + start) as i64, length, true, len as i64),
+                });
+            }
+            start = i;
+        }
+    }
+    runs.sort_by_key(|r| r.location.start);
+    runs
+}
+
+/// Direct and inverted repeats of at least `min_length` bases, longest first. Each is reported
+/// once, at its maximal extent, with non-overlapping copies; the second copy of an inverted
+/// repeat is on the reverse strand. Only the first occurrences of frequent seeds are paired.
+pub fn repeats(sequence: &str, circular: bool, min_length: usize) -> Vec<Repeat> {
+    let mut bases = sequence.to_uppercase().into_bytes();
+    let len = bases.len();
+    let k = min_length.max(1);
+    if len < 2 * k {
+        return vec![];
+    }
+    let seeds = if circular { len } else { len - k + 1 };
+    if circular {
+        let head = bases[..k - 1].to_vec();
+        bases.extend(head);
+    }
+    let base = |p: usize| bases[p % len];
+
+    let mut index: HashMap<&[u8], Vec<usize>> = HashMap::new();
+    for p in 0..seeds {
+        let kmer = &bases[p..p + k];
+        if kmer.iter().all(|&b| is_base(b)) {
+            let positions = index.entry(kmer).or_default();
+            if positions.len() < MAX_SEED_OCCURRENCES {
+                positions.push(p);
+            }
+        }
+    }
+
+    let repeat = |kind: &str, first: usize, second: usize, length: usize| Repeat {
+        kind: kind.to_string(),
+        length,
+        sequence: slice(sequence, first as i64, length).to_uppercase(),
+        first: location(first as i64, length, true, len as i64),
+        second: location(second as i64, length, kind == "direct", len as i64),
+    };
+
+    let mut found = Vec::new();
+    for positions in index.values() {
+        for (x, &i) in positions.iter().enumerate() {
+            for &j in &positions[x + 1..] {
+                let distance = j - i;
+                let max = if circular { distance.min(len - distance) } else { distance.min(len - j) };
+                // Seeds that extend to the left belong to a repeat found from an earlier seed
+                let extends = (circular || i > 0) && is_base(base(i + len - 1)) && base(i + len - 1) == base(j + len - 1);
+                if max < k || extends {
+                    continue;
+                }
+                let mut length = k;
+                while length < max && is_base(base(i + length)) && base(i + length) == base(j + length) {
+                    length += 1;
+                }
+                found.push(repeat("direct", i, j, length));
+            }
+        }
+    }
+
+    for i in 0..seeds {
+        let target = bases[i..i + k].iter().rev().map(|&b| complement(b)).collect::<Vec<_>>();
+        let Some(positions) = index.get(target.as_slice()) else {
+            continue;
+        };
+        for &j in positions.iter().filter(|&&j| j >= i + k && (!circular || j + k <= i + len)) {
+            // Arms grow outward from an earlier seed unless they reach an end or each other
+            let blocked = if circular { j + k == i + len } else { i == 0 || j + k == len };
+            if !blocked && is_base(base(i + len - 1)) && base(i + len - 1) == complement(base(j + k)) {
+                continue;
+            }
+            let mut length = k;
+            while i + 2 * length + 2 <= j + k
+                && is_base(base(i + length))
+                && base(i + length) == complement(base(j + k - length - 1))
+            {
+                length += 1;
+            }
+            found.push(repeat("inverted", i, j + k - length, length));
+        }
+    }
+
+    found.sort_by(|a, b| b.length.cmp(&a.length).then(a.first.start.cmp(&b.first.start)));
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(repeat: &Repeat) -> (&str, (i32, i32), (i32, i32, bool)) {
+        (
+            repeat.kind.as_str(),
+            (repeat.first.start, repeat.first.end),
+            (repeat.second.start, repeat.second.end, repeat.second.forward),
+        )
+    }
+
+    #[test]
+    fn wraps_gc_windows_around_the_origin() {
+        let windows = gc_windows("GGGGAAAAAA", true, 4, 2);
+        let windows = windows.iter().map(|w| (w.start, w.end, w.gc)).collect::<Vec<_>>();
+        assert_eq!(windows, [(1, 4, 1.0), (3, 6, 0.5), (5, 8, 0.0), (7, 10, 0.0), (9, 2, 0.5)]);
+
+        let windows = gc_windows("GGGGAAAAAAC", false, 4, 3);
+        let windows = windows.iter().map(|w| (w.start, w.end, w.gc)).collect::<Vec<_>>();
+        assert_eq!(windows, [(1, 4, 1.0), (4, 7, 0.25), (7, 10, 0.0), (8, 11, 0.25)]);
+    }
+
+    #[test]
+    fn computes_molecular_weights() {
+        // OligoCalc anhydrous weights: dNMP sums, less 61.96 for the ends of linear strands
+        let linear = molecular_weight("ATGC", false);
+        assert_eq!((linear.single_stranded, linear.double_stranded), (1173.84, 2347.68));
+        let circular = molecular_weight("atgc", true);
+        assert_eq!((circular.single_stranded, circular.double_stranded), (1235.8, 2471.6));
+        let single = molecular_weight("AAAA", false);
+        assert_eq!((single.single_stranded, single.double_stranded), (1190.88, 2345.72));
+    }
+
+    #[test]
+    fn finds_direct_and_inverted_repeats() {
+        let direct = repeats("CCGATTACAGGCCCTTTGATTACAGGTTC", false, 8);
+        assert_eq!(direct.iter().map(spans).collect::<Vec<_>>(), [("direct", (3, 11), (18, 26, true))]);
+        assert_eq!(direct[0].sequence, "GATTACAGG");
+
+        let inverted = repeats("CCGATTACAGGCCCTTTCCTGTAATCTT", false, 8);
+        assert_eq!(inverted.iter().map(spans).collect::<Vec<_>>(), [("inverted", (3, 11), (18, 26, false))]);
+
+        // Copies split by the origin of a circular part
+        let circular = repeats("ACAGGCCCTTTGATTACAGGTTCGATT", true, 8);
+        assert_eq!(circular.iter().map(spans).collect::<Vec<_>>(), [("direct", (12, 20), (24, 5, true))]);
+    }
+
+    #[test]
+    fn bounds_low_complexity_stretches() {
+        // Copies are only looked for among the first occurrences of each seed
+        let sequence = "A".repeat(20_000);
+        let found = repeats(&sequence, false, 8);
+        assert_eq!(found.len(), MAX_SEED_OCCURRENCES - 8);
+        assert_eq!(spans(&found[0]), ("direct", (1, 63), (64, 126, true)));
+
+        let tandem = "AT".repeat(10_000);
+        let found = repeats(&tandem, false, 8);
+        assert!(!found.is_empty() && found.len() < MAX_SEED_OCCURRENCES * MAX_SEED_OCCURRENCES);
+    }
+}
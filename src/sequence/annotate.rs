@@ -0,0 +1,328 @@
+//! Feature annotation of a sequence against cached BioBricks and the bundled feature library.
+//! Short DNA references are matched exactly, longer ones are seeded with minimizers and aligned,
+//! and proteins are searched in the six-frame translation.
+
+use std::collections::HashMap;
+
+use crate::cache::SqliteCache;
+use crate::ontology::{find_canonical, OntologyEntrySerializable};
+use crate::types::{Annotation, Location, MetaFeature};
+
+use super::library::FEATURES;
+use super::motif::{find_sites, iupac_mask};
+use super::similarity::{banded_alignment, diagonal_clusters, SequenceIndex};
+use super::{reverse_complement, translate};
+
+pub const MIN_IDENTITY: f64 = 0.9;
+pub const MIN_COVERAGE: f64 = 0.9;
+
+/// Types of the cached parts used as references.
+const REFERENCE_TYPES: &[&str] = &["promoter", "ribosome-entry-site", "terminator", "coding-sequence"];
+/// Cached parts shorter than this would match by chance.
+const MIN_REFERENCE_LENGTH: usize = 10;
+/// DNA references shorter than this are matched exactly rather than seeded.
+const MIN_SEEDED_LENGTH: usize = 40;
+/// Proteins shorter than this (tags) are matched exactly rather than seeded.
+const MIN_SEEDED_PROTEIN: usize = 20;
+const PROTEIN_SEED: usize = 4;
+/// Seeds a protein needs on one diagonal before it is aligned.
+const MIN_PROTEIN_SEEDS: usize = 3;
+
+struct Reference {
+    name: String,
+    r#type: OntologyEntrySerializable,
+    source: &'static str,
+}
+
+struct Candidate {
+    reference: usize,
+    identity: f64,
+    coverage: f64,
+    location: Location,
+}
+
+/// Features of `sequence` found in the bundled library and among cached promoters, RBSs,
+/// terminators and CDSs other than `exclude`, ordered by position.
+pub fn annotate(cache: &SqliteCache, sequence: &str, circular: bool, exclude: Option<&str>) -> Result<Vec<Annotation>, rusqlite::Error> {
+    let sequence = sequence.to_uppercase();
+    let len = sequence.len();
+    if len == 0 {
+        return Ok(vec![]);
+    }
+
+    let index = SequenceIndex::new(&sequence, circular);
+    let frames = Frames::new(&sequence, circular);
+    let mut references: Vec<Reference> = Vec::new();
+    let mut candidates: Vec<Candidate> = Vec::new();
+
+    for feature in FEATURES {
+        let Some(entry) = find_canonical(feature.r#type) else {
+            continue;
+        };
+        references.push(Reference {
+            name: feature.name.to_string(),
+            r#type: entry.into(),
+            source: "library",
+        });
+        let reference = references.len() - 1;
+        if feature.protein {
+            candidates.extend(frames.find(feature.sequence.as_bytes(), reference));
+        } else {
+            candidates.extend(find_dna(&index, &sequence, circular, feature.sequence, reference));
+        }
+    }
+
+    for (id, part) in cache.list_parts_by_type(REFERENCE_TYPES)? {
+        let reference_length = part.sequence.len();
+        if Some(id.as_str()) == exclude || reference_length < MIN_REFERENCE_LENGTH || reference_length > len {
+            continue;
+        }
+        references.push(Reference {
+            name: part.metadata.id.clone(),
+            r#type: part.metadata.r#type.clone(),
+            source: "cache",
+        });
+        let reference = references.len() - 1;
+        candidates.extend(find_dna(&index, &sequence, circular, &part.sequence.to_uppercase(), reference));
+    }
+
+    // Best hits first; a hit is dropped when it mostly overlaps a better one of the same type
+    let score = |c: &Candidate| c.identity * c.coverage * span(&c.location, len) as f64;
+    candidates.sort_by(|a, b| score(b).total_cmp(&score(a)).then_with(|| a.reference.cmp(&b.reference)));
+    let mut kept: Vec<Candidate> = Vec::new();
+    for candidate in candidates {
+        let redundant = kept.iter().any(|k| {
+            references[k.reference].r#type.canonical == references[candidate.reference].r#type.canonical
+                && 2 * overlap(&k.location, &candidate.location, len)
+                    >= span(&k.location, len).min(span(&candidate.location, len))
+        });
+        if !redundant {
+            kept.push(candidate);
+        }
+    }
+    kept.sort_by_key(|c| (c.location.start, c.location.end));
+
+    Ok(kept
+        .into_iter()
+        .map(|c| {
+            let reference = &references[c.reference];
+            Annotation {
+                feature: MetaFeature {
+                    id: format!("{}_{}", reference.name, c.location.start),
+                    name: reference.name.clone(),
+                    r#type: reference.r#type.clone(),
+                    location: c.location,
+                    translation: None,
+                },
+                source: reference.source.to_string(),
+                identity: c.identity,
+                coverage: c.coverage,
+            }
+        })
+        .collect())
+}
+
+fn find_dna(index: &SequenceIndex, sequence: &str, circular: bool, reference: &str, id: usize) -> Vec<Candidate> {
+    if reference.len() < MIN_SEEDED_LENGTH {
+        let masks = reference.bytes().map(iupac_mask).collect::<Vec<_>>();
+        if masks.contains(&0) {
+            return vec![];
+        }
+        return find_sites(sequence, circular, &[masks], 0)
+            .into_iter()
+            .map(|site| Candidate {
+                reference: id,
+                identity: 1.0,
+                coverage: 1.0,
+                location: site.location,
+            })
+            .collect();
+    }
+
+    index
+        .find(reference)
+        .into_iter()
+        .filter(|hit| hit.identity >= MIN_IDENTITY && hit.coverage >= MIN_COVERAGE)
+        .map(|hit| Candidate {
+            reference: id,
+            identity: hit.identity,
+            coverage: hit.coverage,
+            location: hit.location,
+        })
+        .collect()
+}
+
+/// Six-frame translation of a sequence, with an index of the protein seeds of each frame.
+struct Frames {
+    len: usize,
+    extended: usize,
+    frames: Vec<(bool, usize, Vec<u8>)>, // forward, offset, protein
+    seeds: Vec<HashMap<Vec<u8>, Vec<usize>>>,
+}
+
+impl Frames {
+    fn new(sequence: &str, circular: bool) -> Self {
+        // Circular sequences are translated twice over so proteins can span the origin
+        let extended = if circular { sequence.repeat(2) } else { sequence.to_string() };
+        let reverse = reverse_complement(&extended);
+        let mut frames = Vec::new();
+        for (forward, strand) in [(true, &extended), (false, &reverse)] {
+            for offset in 0..3.min(strand.len()) {
+                frames.push((forward, offset, translate(&strand[offset..]).into_bytes()));
+            }
+        }
+        let seeds = frames
+            .iter()
+            .map(|(_, _, protein)| {
+                let mut seeds: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+                for (position, seed) in protein.windows(PROTEIN_SEED).enumerate() {
+                    seeds.entry(seed.to_vec()).or_default().push(position);
+                }
+                seeds
+            })
+            .collect();
+
+        Frames {
+            len: sequence.len(),
+            extended: extended.len(),
+            frames,
+            seeds,
+        }
+    }
+
+    fn find(&self, protein: &[u8], reference: usize) -> Vec<Candidate> {
+        let mut found: Vec<Candidate> = Vec::new();
+        for (frame, (forward, offset, translation)) in self.frames.iter().enumerate() {
+            let mut hits = Vec::new();
+            if protein.len() < MIN_SEEDED_PROTEIN {
+                for (position, window) in translation.windows(protein.len()).enumerate() {
+                    if window == protein {
+                        hits.push((position, position + protein.len(), 1.0, 1.0));
+                    }
+                }
+            } else {
+                let mut diagonals = Vec::new();
+                for (query_position, seed) in protein.windows(PROTEIN_SEED).enumerate() {
+                    for target_position in self.seeds[frame].get(seed).into_iter().flatten() {
+                        diagonals.push(*target_position as i64 - query_position as i64);
+                    }
+                }
+                for (diagonal, count) in diagonal_clusters(diagonals) {
+                    if count < MIN_PROTEIN_SEEDS {
+                        continue;
+                    }
+                    let Some(alignment) = banded_alignment(protein, translation, diagonal) else {
+                        continue;
+                    };
+                    let identity = alignment.matches as f64 / alignment.columns as f64;
+                    let coverage = (alignment.query_end - alignment.query_start) as f64 / protein.len() as f64;
+                    // Full-length proteins also cover their stop codon
+                    let end = if alignment.query_end == protein.len() && translation.get(alignment.target_end) == Some(&b'*') {
+                        alignment.target_end + 1
+                    } else {
+                        alignment.target_end
+                    };
+                    hits.push((alignment.target_start, end, identity, coverage));
+                }
+            }
+
+            for (start, end, identity, coverage) in hits {
+                if identity < MIN_IDENTITY || coverage < MIN_COVERAGE {
+                    continue;
+                }
+                let (dna_start, dna_end) = (offset + 3 * start, offset + 3 * end);
+                let (dna_start, dna_end) = if *forward {
+                    (dna_start, dna_end)
+                } else {
+                    (self.extended - dna_end, self.extended - dna_start)
+                };
+                if dna_end - dna_start > self.len {
+                    continue;
+                }
+                let location = Location {
+                    start: (dna_start % self.len + 1) as i32,
+                    end: ((dna_end - 1) % self.len + 1) as i32,
+                    forward: *forward,
+                    segments: vec![],
+                    partial_start: false,
+                    partial_end: false,
+                };
+                let duplicate = found.iter().any(|f| f.location.start == location.start && f.location.forward == location.forward);
+                if !duplicate {
+                    found.push(Candidate {
+                        reference,
+                        identity,
+                        coverage,
+                        location,
+                    });
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Ranges of a location on a sequence of length `len`, split at the origin.
+fn intervals(location: &Location, len: usize) -> Vec<(usize, usize)> {
+    let (start, end) = (location.start as usize, location.end as usize);
+    if start <= end {
+        vec![(start, end)]
+    } else {
+        vec![(start, len), (1, end)]
+    }
+}
+
+fn span(location: &Location, len: usize) -> usize {
+    intervals(location, len).iter().map(|(s, e)| e + 1 - s).sum()
+}
+
+fn overlap(a: &Location, b: &Location, len: usize) -> usize {
+    let mut total = 0;
+    for (a_start, a_end) in intervals(a, len) {
+        for (b_start, b_end) in intervals(b, len) {
+            total += (a_end.min(b_end) + 1).saturating_sub(a_start.max(b_start));
+        }
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One codon per amino acid, unlike any natural gene, so only the protein can match.
+    fn back_translate(protein: &str) -> String {
+        protein
+            .chars()
+            .map(|aa| match aa {
+                'A' => "GCG", 'C' => "TGC", 'D' => "GAC", 'E' => "GAG", 'F' => "TTC",
+                'G' => "GGC", 'H' => "CAC", 'I' => "ATC", 'K' => "AAG", 'L' => "CTG",
+                'M' => "ATG", 'N' => "AAC", 'P' => "CCG", 'Q' => "CAG", 'R' => "CGC",
+                'S' => "AGC", 'T' => "ACC", 'V' => "GTG", 'W' => "TGG", 'Y' => "TAC",
+                _ => "TAA",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn annotates_library_features() {
+        let gfp = FEATURES.iter().find(|f| f.name == "GFP").unwrap();
+        let t7 = FEATURES.iter().find(|f| f.name == "T7 promoter").unwrap();
+        let cds = back_translate(&format!("{}*", gfp.sequence));
+        // T7 promoter, then GFP on the reverse strand
+        let sequence = format!("CC{}AAAA{}GG", t7.sequence, reverse_complement(&cds));
+
+        let cache = SqliteCache::new(":memory:").unwrap();
+        let annotations = annotate(&cache, &sequence, false, None).unwrap();
+        let found = annotations
+            .iter()
+            .map(|a| (a.feature.name.as_str(), a.feature.location.start, a.feature.location.end, a.feature.location.forward))
+            .collect::<Vec<_>>();
+        let end = 24 + cds.len() as i32;
+        assert_eq!(found, [("T7 promoter", 3, 20, true), ("GFP", 25, end, false)]);
+
+        let gfp = &annotations[1];
+        assert_eq!((gfp.source.as_str(), gfp.identity, gfp.coverage), ("library", 1.0, 1.0));
+        assert_eq!(gfp.feature.r#type.canonical, "coding-sequence");
+    }
+}
@@ -0,0 +1,119 @@
+//! Codon usage of common expression hosts, in codons per thousand, after the Kazusa codon usage
+//! database, and their tRNA gene copy numbers after GtRNAdb. Codons are indexed as in
+//! `genetic_code`: TTT, TTC, TTA, TTG, TCT... GGG.
+
+use super::genetic_code::GeneticCode;
+
+/// Codons used for less than this fraction of their amino acid are avoided when recoding.
+pub const RARE_CODON: f64 = 0.1;
+
+pub struct CodonUsage {
+    pub host: &'static str,
+    pub organism: &'static str,
+    pub table: u8, // genetic code
+    pub frequencies: [f64; 64],
+    /// tRNA gene copies by anticodon. The bacterial tRNA-Ile with a lysidine (LAU anticodon),
+    /// which reads ATA, is listed as TAT.
+    pub trna: &'static [(&'static str, u32)],
+}
+
+/// Used when scoring without a host.
+pub const DEFAULT_HOST: &str = "e-coli";
+
+pub const HOSTS: &[CodonUsage] = &[
+    CodonUsage {
+        host: "e-coli",
+        organism: "Escherichia coli K-12",
+        table: 11,
+        frequencies: [
+            22.4, 16.6, 13.9, 13.7, 8.5, 8.6, 7.2, 8.9, 16.3, 12.3, 2.0, 0.2, 5.2, 6.5, 0.9, 15.3, // T
+            11.0, 11.1, 3.9, 52.6, 7.0, 5.5, 8.4, 23.2, 12.9, 9.7, 15.4, 28.8, 20.9, 22.0, 3.6, 5.4, // C
+            30.3, 25.0, 4.4, 27.8, 9.0, 23.4, 7.1, 14.4, 17.7, 21.7, 33.6, 10.3, 8.8, 16.1, 2.1, 1.2, // A
+            18.3, 15.3, 10.9, 26.4, 15.3, 25.8, 20.3, 33.6, 32.2, 19.1, 39.6, 17.8, 24.9, 29.6, 8.0, 11.1, // G
+        ],
+        trna: &[
+            ("GGC", 2), ("TGC", 3), ("ACG", 4), ("CCG", 1), ("CCT", 1), ("TCT", 1), ("GTT", 4),
+            ("GTC", 3), ("GCA", 1), ("CTG", 2), ("TTG", 2), ("TTC", 4), ("CCC", 1), ("GCC", 4), ("TCC", 1),
+            ("GTG", 1), ("GAT", 3), ("TAT", 2), ("CAG", 4), ("GAG", 1), ("TAG", 1), ("CAA", 1), ("TAA", 1),
+            ("TTT", 6), ("CAT", 8), ("GAA", 2), ("CGG", 1), ("GGG", 1), ("TGG", 1), ("CGA", 1), ("GCT", 1),
+            ("GGA", 2), ("TGA", 1), ("CGT", 1), ("GGT", 2), ("TGT", 1), ("CCA", 1), ("GTA", 3), ("GAC", 2),
+            ("TAC", 5),
+        ],
+    },
+    CodonUsage {
+        host: "b-subtilis",
+        organism: "Bacillus subtilis 168",
+        table: 11,
+        frequencies: [
+            31.5, 13.8, 19.9, 15.8, 12.9, 5.5, 14.5, 5.6, 22.7, 12.6, 2.0, 0.5, 3.5, 4.4, 0.9, 10.6, // T
+            22.5, 10.9, 5.0, 24.3, 10.3, 3.3, 7.1, 15.4, 15.2, 7.5, 19.6, 14.3, 7.4, 8.4, 4.0, 6.6, // C
+            37.2, 26.8, 9.0, 26.6, 8.6, 5.6, 21.6, 14.2, 21.9, 17.6, 49.0, 21.1, 6.9, 15.9, 10.5, 4.1, // A
+            17.8, 17.8, 13.4, 17.7, 18.8, 15.7, 21.2, 20.0, 33.3, 18.8, 48.8, 23.1, 12.6, 23.1, 21.6, 11.1, // G
+        ],
+        trna: &[
+            ("TGC", 6), ("ACG", 4), ("CCG", 1), ("CCT", 1), ("TCT", 2), ("GTT", 4), ("GTC", 5), ("GCA", 1),
+            ("TTG", 4), ("TTC", 6), ("GCC", 6), ("TCC", 3), ("GTG", 2), ("GAT", 6), ("TAT", 1), ("CAG", 1),
+            ("GAG", 1), ("TAG", 3), ("CAA", 1), ("TAA", 2), ("TTT", 5), ("CAT", 6), ("GAA", 4), ("GGG", 1),
+            ("TGG", 3), ("GCT", 1), ("GGA", 3), ("TGA", 2), ("GGT", 2), ("TGT", 3), ("CCA", 1), ("GTA", 3),
+            ("GAC", 1), ("TAC", 4),
+        ],
+    },
+    CodonUsage {
+        host: "s-cerevisiae",
+        organism: "Saccharomyces cerevisiae",
+        table: 1,
+        frequencies: [
+            26.1, 18.4, 26.2, 27.2, 23.5, 14.2, 18.7, 8.6, 18.8, 14.8, 1.1, 0.5, 8.1, 4.8, 0.7, 10.4, // T
+            12.3, 5.4, 13.4, 10.5, 13.5, 6.8, 18.3, 5.3, 13.6, 7.8, 27.3, 12.1, 6.4, 2.6, 3.0, 1.7, // C
+            30.1, 17.2, 17.8, 20.9, 20.3, 12.7, 17.8, 8.0, 35.7, 24.8, 41.9, 30.8, 14.2, 9.8, 21.3, 9.2, // A
+            22.1, 11.8, 11.8, 10.8, 21.2, 12.6, 16.2, 6.2, 37.6, 20.2, 45.6, 19.2, 23.9, 9.8, 10.9, 6.0, // G
+        ],
+        trna: &[
+            ("AGC", 11), ("TGC", 5), ("ACG", 6), ("CCG", 1), ("CCT", 1), ("TCT", 11), ("GTT", 10), ("GTC", 15),
+            ("GCA", 4), ("CTG", 1), ("TTG", 9), ("CTC", 2), ("TTC", 14), ("CCC", 2), ("GCC", 16), ("TCC", 3),
+            ("GTG", 7), ("AAT", 13), ("TAT", 2), ("CAA", 10), ("GAG", 1), ("TAA", 7), ("TAG", 3), ("CTT", 14),
+            ("TTT", 7), ("CAT", 10), ("GAA", 10), ("AGG", 2), ("TGG", 10), ("AGA", 11), ("CGA", 1), ("GCT", 4),
+            ("TGA", 3), ("AGT", 11), ("CGT", 1), ("TGT", 4), ("CCA", 6), ("GTA", 8), ("AAC", 14), ("CAC", 2),
+            ("TAC", 2),
+        ],
+    },
+    CodonUsage {
+        host: "h-sapiens",
+        organism: "Homo sapiens",
+        table: 1,
+        frequencies: [
+            17.6, 20.3, 7.7, 12.9, 15.2, 17.7, 12.2, 4.4, 12.2, 15.3, 1.0, 0.8, 10.6, 12.6, 1.6, 13.2, // T
+            13.2, 19.6, 7.2, 39.6, 17.5, 19.8, 16.9, 6.9, 10.9, 15.1, 12.3, 34.2, 4.5, 10.4, 6.2, 11.4, // C
+            16.0, 20.8, 7.5, 22.0, 13.1, 18.9, 15.1, 6.1, 17.0, 19.1, 24.4, 31.9, 12.1, 19.5, 12.2, 12.0, // A
+            11.0, 14.5, 7.1, 28.1, 18.4, 27.7, 15.8, 7.4, 21.8, 25.1, 29.0, 39.6, 10.8, 22.2, 16.5, 16.5, // G
+        ],
+        trna: &[
+            ("AGC", 22), ("CGC", 4), ("TGC", 8), ("ACG", 7), ("CCG", 4), ("CCT", 5), ("TCG", 6), ("TCT", 6),
+            ("GTT", 33), ("GTC", 19), ("GCA", 30), ("CTG", 21), ("TTG", 6), ("CTC", 13), ("TTC", 7), ("CCC", 5),
+            ("GCC", 14), ("TCC", 9), ("GTG", 11), ("AAT", 14), ("GAT", 3), ("TAT", 5), ("AAG", 10), ("CAA", 6),
+            ("CAG", 10), ("TAA", 4), ("TAG", 3), ("CTT", 17), ("TTT", 16), ("CAT", 20), ("GAA", 10), ("AGG", 10),
+            ("CGG", 4), ("TGG", 7), ("AGA", 11), ("CGA", 4), ("GCT", 8), ("TGA", 4), ("AGT", 10), ("CGT", 6),
+            ("TGT", 6), ("CCA", 9), ("GTA", 14), ("AAC", 11), ("CAC", 11), ("TAC", 5),
+        ],
+    },
+];
+
+pub fn find_host(name: &str) -> Option<&'static CodonUsage> {
+    let name = name.trim();
+    HOSTS.iter().find(|h| h.host.eq_ignore_ascii_case(name) || h.organism.eq_ignore_ascii_case(name))
+}
+
+impl CodonUsage {
+    /// Codons coding for the same amino acid as `index` in `code`, itself included, with the
+    /// share of that amino acid they code for in this host, most used first.
+    pub fn synonyms(&self, code: &GeneticCode, index: usize) -> Vec<(usize, f64)> {
+        let synonyms = (0..64).filter(|&i| code.amino_acids[i] == code.amino_acids[index]).collect::<Vec<_>>();
+        let total = synonyms.iter().map(|&i| self.frequencies[i]).sum::<f64>();
+        let mut ranked = synonyms
+            .into_iter()
+            .map(|i| (i, if total > 0.0 { self.frequencies[i] / total } else { 0.0 }))
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked
+    }
+}
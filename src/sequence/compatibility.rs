@@ -0,0 +1,245 @@
+//! Assembly standard compliance: forbidden restriction sites, and standard prefix and suffix.
+
+use crate::types::{Biobrick, Flank, Location, RestrictionSite, StandardReport};
+
+use super::enzymes::find_enzyme;
+use super::motif::{find_sites, parse_motif};
+
+/// How far from the ends of a linear sequence a prefix or suffix is looked for.
+const FLANK_WINDOW: i32 = 20;
+
+pub struct Standard {
+    pub name: &'static str,
+    pub enzymes: &'static [&'static str],
+    pub flanks: Flanks,
+}
+
+pub enum Flanks {
+    /// Fixed prefix and suffix sequences
+    Sequences { prefix: &'static str, suffix: &'static str },
+    /// Type IIS sites pointing into the part, cutting out its overhangs
+    TypeIIS { enzyme: &'static str },
+}
+
+pub const BIOBRICK_PREFIX: &str = "GAATTCGCGGCCGCTTCTAGAG";
+/// Prefix of coding sequences, whose XbaI site ends with the A of their start codon.
+pub const BIOBRICK_CDS_PREFIX: &str = "GAATTCGCGGCCGCTTCTAG";
+pub const BIOBRICK_SUFFIX: &str = "TACTAGTAGCGGCCGCTGCAG";
+/// Part of the prefix found in both forms, up to the end of the XbaI site.
+const BIOBRICK_PREFIX_SITES: &str = BIOBRICK_PREFIX.split_at(BIOBRICK_CDS_PREFIX.len() + 1).0;
+
+pub const STANDARDS: &[Standard] = &[
+    Standard {
+        name: "RFC10",
+        enzymes: &["EcoRI", "XbaI", "SpeI", "PstI", "NotI"],
+        flanks: Flanks::Sequences { prefix: BIOBRICK_PREFIX_SITES, suffix: BIOBRICK_SUFFIX },
+    },
+    Standard {
+        name: "RFC1000",
+        enzymes: &["EcoRI", "XbaI", "SpeI", "PstI", "NotI", "BsaI", "SapI"],
+        flanks: Flanks::Sequences { prefix: BIOBRICK_PREFIX_SITES, suffix: BIOBRICK_SUFFIX },
+    },
+    Standard {
+        name: "MoClo",
+        enzymes: &["BsaI", "BsmBI", "BbsI"],
+        flanks: Flanks::TypeIIS { enzyme: "BsaI" },
+    },
+    Standard {
+        name: "iGEM Type IIS",
+        enzymes: &["BsaI", "SapI"],
+        flanks: Flanks::TypeIIS { enzyme: "BsaI" },
+    },
+];
+
+/// Standard by name, ignoring case, spaces and dashes.
+pub fn find_standard(name: &str) -> Option<&'static Standard> {
+    let normalize = |s: &str| s.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_lowercase();
+    STANDARDS.iter().find(|s| normalize(s.name) == normalize(name))
+}
+
+pub fn check_standards(biobrick: &Biobrick) -> Vec<StandardReport> {
+    STANDARDS.iter().map(|standard| check_standard(biobrick, standard)).collect()
+}
+
+/// Sites of the standard's enzymes, other than those of its own prefix and suffix.
+pub fn check_standard(biobrick: &Biobrick, standard: &Standard) -> StandardReport {
+    let sequence = biobrick.sequence.to_uppercase();
+    let circular = biobrick.metadata.circular;
+    let (prefix, suffix) = match standard.flanks {
+        Flanks::Sequences { prefix, suffix } => (
+            find_flank(&sequence, circular, prefix, true, false),
+            find_flank(&sequence, circular, suffix, true, true),
+        ),
+        // A lone Type IIS site is more likely internal than a flank
+        Flanks::TypeIIS { enzyme } => match (
+            type_iis_flank(&sequence, circular, enzyme, true),
+            type_iis_flank(&sequence, circular, enzyme, false),
+        ) {
+            (Some(prefix), Some(suffix)) => (Some(prefix), Some(suffix)),
+            _ => (None, None),
+        },
+    };
+
+    let mut sites = Vec::new();
+    for name in standard.enzymes {
+        let Some(enzyme) = find_enzyme(name) else {
+            continue;
+        };
+        for site in enzyme.sites(&sequence, circular) {
+            let in_flank = [&prefix, &suffix]
+                .into_iter()
+                .flatten()
+                .any(|flank| contains(&flank.location, &site.location, sequence.len() as i32));
+            if !in_flank {
+                sites.push(RestrictionSite {
+                    enzyme: enzyme.name.to_string(),
+                    location: site.location,
+                });
+            }
+        }
+    }
+    sites.sort_by_key(|s| s.location.start);
+
+    StandardReport {
+        name: standard.name.to_string(),
+        compatible: sites.is_empty(),
+        enzymes: standard.enzymes.iter().map(|e| e.to_string()).collect(),
+        sites,
+        prefix,
+        suffix,
+    }
+}
+
+/// Forward occurrence of `flank`: anywhere in circular sequences, otherwise near the start
+/// (or the end, for suffixes).
+fn find_flank(sequence: &str, circular: bool, flank: &str, forward: bool, at_end: bool) -> Option<Flank> {
+    let variants = parse_motif(flank).ok()?;
+    let len = sequence.len() as i32;
+    let mut sites = find_sites(sequence, circular, &variants, 0)
+        .into_iter()
+        .filter(|s| s.location.forward == forward);
+    let site = if circular {
+        sites.next()
+    } else if at_end {
+        sites.rfind(|s| s.location.end > len - FLANK_WINDOW)
+    } else {
+        sites.find(|s| s.location.start <= FLANK_WINDOW)
+    }?;
+    Some(Flank {
+        location: site.location,
+        overhang: None,
+    })
+}
+
+/// Type IIS site cutting towards the part: forward near the start for prefixes, reverse near
+/// the end for suffixes. The flank runs from the site to the end of its overhang.
+fn type_iis_flank(sequence: &str, circular: bool, enzyme: &str, prefix: bool) -> Option<Flank> {
+    let enzyme = find_enzyme(enzyme)?;
+    let flank = find_flank(sequence, circular, enzyme.site, prefix, !prefix)?;
+    let len = sequence.len() as i32;
+    let site_len = enzyme.site.len() as i32;
+    let (start, end) = if prefix {
+        (flank.location.start, flank.location.start + enzyme.complement_cut - 1)
+    } else {
+        (flank.location.start + site_len - enzyme.complement_cut, flank.location.end)
+    };
+    if !circular && (start < 1 || end > len) {
+        return None;
+    }
+
+    let wrap = |p: i32| (p - 1).rem_euclid(len) + 1;
+    let overhang_length = enzyme.complement_cut - enzyme.cut;
+    let overhang_start = if prefix { end - overhang_length + 1 } else { start };
+    let overhang = (overhang_start..overhang_start + overhang_length)
+        .map(|p| sequence.as_bytes()[(wrap(p) - 1) as usize] as char)
+        .collect();
+    Some(Flank {
+        location: Location {
+            start: wrap(start),
+            end: wrap(end),
+            forward: prefix,
+            segments: vec![],
+            partial_start: false,
+            partial_end: false,
+        },
+        overhang: Some(overhang),
+    })
+}
+
+/// Whether `inner` lies within `outer`, both possibly spanning the origin.
+fn contains(outer: &Location, inner: &Location, len: i32) -> bool {
+    let unwrap = |l: &Location| if l.start <= l.end { (l.start, l.end) } else { (l.start, l.end + len) };
+    let (outer_start, outer_end) = unwrap(outer);
+    let (inner_start, inner_end) = unwrap(inner);
+    [0, len].iter().any(|shift| inner_start + shift >= outer_start && inner_end + shift <= outer_end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ontology::find_canonical;
+    use crate::types::MetaBiobrick;
+
+    fn part(sequence: String) -> Biobrick {
+        Biobrick {
+            metadata: MetaBiobrick {
+                id: "part".to_string(),
+                name: "part".to_string(),
+                description: String::new(),
+                creation: String::new(),
+                size: sequence.len() as i32,
+                circular: false,
+                r#type: find_canonical("engineered-region").unwrap().into(),
+                authors: vec![],
+                providers: vec![],
+            },
+            sequence,
+            features: vec![],
+        }
+    }
+
+    fn sites(report: &StandardReport) -> Vec<(&str, i32, i32, bool)> {
+        report
+            .sites
+            .iter()
+            .map(|s| (s.enzyme.as_str(), s.location.start, s.location.end, s.location.forward))
+            .collect()
+    }
+
+    fn span(flank: &Option<Flank>) -> Option<(i32, i32)> {
+        flank.as_ref().map(|f| (f.location.start, f.location.end))
+    }
+
+    #[test]
+    fn reports_forbidden_sites() {
+        let biobrick = part(format!("{}AAGAATTCAA{}", BIOBRICK_PREFIX, BIOBRICK_SUFFIX));
+        let report = check_standard(&biobrick, find_standard("RFC10").unwrap());
+        assert!(!report.compatible);
+        assert_eq!(sites(&report), [("EcoRI", 25, 30, true)]);
+
+        // Non-palindromic sites are reported on their strand
+        let biobrick = part(format!("{}AAGAGACCAA{}", BIOBRICK_PREFIX, BIOBRICK_SUFFIX));
+        assert!(check_standard(&biobrick, find_standard("RFC10").unwrap()).compatible);
+        let report = check_standard(&biobrick, find_standard("RFC1000").unwrap());
+        assert_eq!(sites(&report), [("BsaI", 25, 30, false)]);
+    }
+
+    #[test]
+    fn finds_prefix_and_suffix() {
+        let biobrick = part(format!("{}TTTTTTTTTT{}", BIOBRICK_PREFIX, BIOBRICK_SUFFIX));
+        let report = check_standard(&biobrick, find_standard("RFC10").unwrap());
+        assert!(report.compatible);
+        assert_eq!(span(&report.prefix), Some((1, 21)));
+        assert_eq!(span(&report.suffix), Some((33, 53)));
+
+        // Coding sequences start right after the XbaI site
+        let biobrick = part(format!("{}ATGAAATAA{}", BIOBRICK_CDS_PREFIX, BIOBRICK_SUFFIX));
+        let report = check_standard(&biobrick, find_standard("RFC10").unwrap());
+        assert!(report.compatible);
+        assert_eq!(span(&report.prefix), Some((1, 21)));
+        assert_eq!(span(&report.suffix), Some((30, 50)));
+
+        let report = check_standard(&part("TTTTTTTTTTTTTTTTTTTT".to_string()), find_standard("RFC10").unwrap());
+        assert!(report.compatible && report.prefix.is_none() && report.suffix.is_none());
+    }
+}
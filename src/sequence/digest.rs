@@ -0,0 +1,148 @@
+//! In-silico restriction digest.
+
+use crate::types::{Fragment, FragmentEnd, RestrictionSite};
+
+use super::enzymes::Enzyme;
+
+struct Cut {
+    enzyme: &'static Enzyme,
+    top: i64,
+    bottom: i64,
+}
+
+/// Sites of `enzymes` and the fragments they leave, in top strand coordinates. Cuts falling
+/// outside a linear sequence are ignored; an uncut circular sequence is a single fragment.
+pub fn digest(sequence: &str, circular: bool, enzymes: &[&'static Enzyme]) -> (Vec<RestrictionSite>, Vec<Fragment>) {
+    let sequence = sequence.to_uppercase();
+    let len = sequence.len() as i64;
+    let mut sites = Vec::new();
+    let mut cuts: Vec<Cut> = Vec::new();
+    for enzyme in enzymes {
+        for site in enzyme.sites(&sequence, circular) {
+            let (top, bottom) = enzyme.cuts_at(site.location.start as i64 - 1, site.location.forward);
+            let (top, bottom) = if circular {
+                (top.rem_euclid(len), top.rem_euclid(len) + bottom - top)
+            } else if top.min(bottom) > 0 && top.max(bottom) < len {
+                (top, bottom)
+            } else {
+                continue;
+            };
+            sites.push(RestrictionSite {
+                enzyme: enzyme.name.to_string(),
+                location: site.location,
+            });
+            cuts.push(Cut { enzyme, top, bottom });
+        }
+    }
+    sites.sort_by_key(|s| s.location.start);
+    cuts.sort_by_key(|c| c.top);
+    cuts.dedup_by_key(|c| c.top);
+
+    let end = |cut: &Cut| {
+        let overhang = (cut.top.min(cut.bottom)..cut.top.max(cut.bottom))
+            .map(|p| sequence.as_bytes()[p.rem_euclid(len) as usize] as char)
+            .collect();
+        FragmentEnd {
+            enzyme: cut.enzyme.name.to_string(),
+            overhang,
+            r#type: cut.enzyme.overhang(),
+        }
+    };
+    let fragment = |start: i64, length: i64, left: Option<&Cut>, right: Option<&Cut>| Fragment {
+        start: (start.rem_euclid(len) + 1) as i32,
+        end: ((start + length - 1).rem_euclid(len) + 1) as i32,
+        length: length as usize,
+        left: left.map(end),
+        right: right.map(end),
+    };
+
+    let mut fragments = Vec::new();
+    if len == 0 {
+        return (sites, fragments);
+    }
+    if circular {
+        if cuts.is_empty() {
+            fragments.push(fragment(0, len, None, None));
+        }
+        for (i, cut) in cuts.iter().enumerate() {
+            let next = &cuts[(i + 1) % cuts.len()];
+            let length = match (next.top - cut.top).rem_euclid(len) {
+                0 => len,
+                length => length,
+            };
+            fragments.push(fragment(cut.top, length, Some(cut), Some(next)));
+        }
+    } else {
+        let mut start = 0;
+        let mut left = None;
+        for cut in &cuts {
+            fragments.push(fragment(start, cut.top - start, left, Some(cut)));
+            start = cut.top;
+            left = Some(cut);
+        }
+        fragments.push(fragment(start, len - start, left, None));
+    }
+    (sites, fragments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequence::enzymes::find_enzyme;
+    use crate::types::OverhangType;
+
+    fn bounds(fragments: &[Fragment]) -> Vec<(i32, i32, usize)> {
+        fragments.iter().map(|f| (f.start, f.end, f.length)).collect()
+    }
+
+    #[test]
+    fn cuts_linear_sequence() {
+        let ecori = find_enzyme("EcoRI").unwrap();
+        let (sites, fragments) = digest("aaaaaGAATTCttttt", false, &[ecori]);
+        assert_eq!(sites.len(), 1);
+        assert_eq!((sites[0].location.start, sites[0].location.end), (6, 11));
+        assert_eq!(bounds(&fragments), [(1, 6, 6), (7, 16, 10)]);
+
+        let (left, right) = (fragments[0].right.as_ref().unwrap(), fragments[1].left.as_ref().unwrap());
+        assert_eq!((left.overhang.as_str(), right.overhang.as_str()), ("AATT", "AATT"));
+        assert!(matches!(left.r#type, OverhangType::FivePrime));
+        assert!(fragments[0].left.is_none() && fragments[1].right.is_none());
+    }
+
+    #[test]
+    fn opens_circular_sequence_once() {
+        let ecori = find_enzyme("EcoRI").unwrap();
+        let (_, fragments) = digest("AAAAAGAATTCTTTTT", true, &[ecori]);
+        assert_eq!(bounds(&fragments), [(7, 6, 16)]);
+        assert_eq!(fragments[0].left.as_ref().unwrap().overhang, "AATT");
+        assert_eq!(fragments[0].right.as_ref().unwrap().overhang, "AATT");
+    }
+
+    #[test]
+    fn cuts_site_across_origin() {
+        let ecori = find_enzyme("EcoRI").unwrap();
+        let sequence = "ATTCCCCCCCCCGA";
+        let (sites, fragments) = digest(sequence, true, &[ecori]);
+        assert_eq!((sites[0].location.start, sites[0].location.end), (13, 4));
+        assert_eq!(bounds(&fragments), [(14, 13, 14)]);
+        assert_eq!(fragments[0].left.as_ref().unwrap().overhang, "AATT");
+
+        // Only circular sequences have the site
+        let (sites, fragments) = digest(sequence, false, &[ecori]);
+        assert!(sites.is_empty());
+        assert_eq!(bounds(&fragments), [(1, 14, 14)]);
+    }
+
+    #[test]
+    fn cuts_type_iis_site_on_reverse_strand() {
+        let bsai = find_enzyme("BsaI").unwrap();
+        // GAGACC reads GGTCTC on the bottom strand, cutting upstream of itself
+        assert_eq!(bsai.cuts_at(15, false), (10, 14));
+        assert_eq!(bsai.cuts_at(15, true), (22, 26));
+
+        let (sites, fragments) = digest("TTTTTTTTTTACGTAGAGACCTTTTT", false, &[bsai]);
+        assert!(!sites[0].location.forward);
+        assert_eq!(bounds(&fragments), [(1, 10, 10), (11, 26, 16)]);
+        assert_eq!(fragments[0].right.as_ref().unwrap().overhang, "ACGT");
+    }
+}
@@ -0,0 +1,121 @@
+//! Restriction enzyme database, after REBASE: recognition site and cut offsets of common
+//! commercial enzymes.
+
+use crate::types::{EnzymeInfo, MotifSite, OverhangType};
+
+use super::motif::{find_sites, parse_motif};
+
+pub struct Enzyme {
+    pub name: &'static str,
+    pub site: &'static str, // IUPAC recognition sequence, 5' to 3'
+    pub cut: i32, // top strand cut, in bases after the start of the site
+    pub complement_cut: i32, // bottom strand cut, in top strand coordinates
+}
+
+impl Enzyme {
+    /// Recognition sites in `sequence`; palindromic sites are reported on the forward strand.
+    pub fn sites(&self, sequence: &str, circular: bool) -> Vec<MotifSite> {
+        match parse_motif(self.site) {
+            Ok(variants) => find_sites(sequence, circular, &variants, 0),
+            Err(_) => vec![],
+        }
+    }
+
+    pub fn overhang(&self) -> OverhangType {
+        match self.cut.cmp(&self.complement_cut) {
+            std::cmp::Ordering::Less => OverhangType::FivePrime,
+            std::cmp::Ordering::Greater => OverhangType::ThreePrime,
+            std::cmp::Ordering::Equal => OverhangType::Blunt,
+        }
+    }
+
+    /// Top and bottom strand cuts of a site starting at 0-based `position`, mirrored for sites
+    /// on the reverse strand.
+    pub fn cuts_at(&self, position: i64, forward: bool) -> (i64, i64) {
+        let length = self.site.len() as i64;
+        if forward {
+            (position + self.cut as i64, position + self.complement_cut as i64)
+        } else {
+            (position + length - self.complement_cut as i64, position + length - self.cut as i64)
+        }
+    }
+}
+
+impl From<&Enzyme> for EnzymeInfo {
+    fn from(enzyme: &Enzyme) -> Self {
+        EnzymeInfo {
+            name: enzyme.name.to_string(),
+            site: enzyme.site.to_string(),
+            cut: enzyme.cut,
+            complement_cut: enzyme.complement_cut,
+            overhang: enzyme.overhang(),
+        }
+    }
+}
+
+pub const ENZYMES: &[Enzyme] = &[
+    Enzyme { name: "AatII", site: "GACGTC", cut: 5, complement_cut: 1 },
+    Enzyme { name: "AflII", site: "CTTAAG", cut: 1, complement_cut: 5 },
+    Enzyme { name: "AgeI", site: "ACCGGT", cut: 1, complement_cut: 5 },
+    Enzyme { name: "AluI", site: "AGCT", cut: 2, complement_cut: 2 },
+    Enzyme { name: "ApaI", site: "GGGCCC", cut: 5, complement_cut: 1 },
+    Enzyme { name: "AscI", site: "GGCGCGCC", cut: 2, complement_cut: 6 },
+    Enzyme { name: "AvrII", site: "CCTAGG", cut: 1, complement_cut: 5 },
+    Enzyme { name: "BamHI", site: "GGATCC", cut: 1, complement_cut: 5 },
+    Enzyme { name: "BbsI", site: "GAAGAC", cut: 8, complement_cut: 12 },
+    Enzyme { name: "BglII", site: "AGATCT", cut: 1, complement_cut: 5 },
+    Enzyme { name: "BsaI", site: "GGTCTC", cut: 7, complement_cut: 11 },
+    Enzyme { name: "BsiWI", site: "CGTACG", cut: 1, complement_cut: 5 },
+    Enzyme { name: "BsmBI", site: "CGTCTC", cut: 7, complement_cut: 11 },
+    Enzyme { name: "BspEI", site: "TCCGGA", cut: 1, complement_cut: 5 },
+    Enzyme { name: "BspHI", site: "TCATGA", cut: 1, complement_cut: 5 },
+    Enzyme { name: "BsrGI", site: "TGTACA", cut: 1, complement_cut: 5 },
+    Enzyme { name: "BstBI", site: "TTCGAA", cut: 2, complement_cut: 4 },
+    Enzyme { name: "BtgZI", site: "GCGATG", cut: 16, complement_cut: 20 },
+    Enzyme { name: "ClaI", site: "ATCGAT", cut: 2, complement_cut: 4 },
+    Enzyme { name: "DpnII", site: "GATC", cut: 0, complement_cut: 4 },
+    Enzyme { name: "DraI", site: "TTTAAA", cut: 3, complement_cut: 3 },
+    Enzyme { name: "EagI", site: "CGGCCG", cut: 1, complement_cut: 5 },
+    Enzyme { name: "EcoRI", site: "GAATTC", cut: 1, complement_cut: 5 },
+    Enzyme { name: "EcoRV", site: "GATATC", cut: 3, complement_cut: 3 },
+    Enzyme { name: "Esp3I", site: "CGTCTC", cut: 7, complement_cut: 11 },
+    Enzyme { name: "FseI", site: "GGCCGGCC", cut: 6, complement_cut: 2 },
+    Enzyme { name: "HaeIII", site: "GGCC", cut: 2, complement_cut: 2 },
+    Enzyme { name: "HhaI", site: "GCGC", cut: 3, complement_cut: 1 },
+    Enzyme { name: "HindIII", site: "AAGCTT", cut: 1, complement_cut: 5 },
+    Enzyme { name: "HpaI", site: "GTTAAC", cut: 3, complement_cut: 3 },
+    Enzyme { name: "KpnI", site: "GGTACC", cut: 5, complement_cut: 1 },
+    Enzyme { name: "MfeI", site: "CAATTG", cut: 1, complement_cut: 5 },
+    Enzyme { name: "MluI", site: "ACGCGT", cut: 1, complement_cut: 5 },
+    Enzyme { name: "MspI", site: "CCGG", cut: 1, complement_cut: 3 },
+    Enzyme { name: "NcoI", site: "CCATGG", cut: 1, complement_cut: 5 },
+    Enzyme { name: "NdeI", site: "CATATG", cut: 2, complement_cut: 4 },
+    Enzyme { name: "NheI", site: "GCTAGC", cut: 1, complement_cut: 5 },
+    Enzyme { name: "NotI", site: "GCGGCCGC", cut: 2, complement_cut: 6 },
+    Enzyme { name: "NruI", site: "TCGCGA", cut: 3, complement_cut: 3 },
+    Enzyme { name: "NsiI", site: "ATGCAT", cut: 5, complement_cut: 1 },
+    Enzyme { name: "PacI", site: "TTAATTAA", cut: 5, complement_cut: 3 },
+    Enzyme { name: "PaqCI", site: "CACCTGC", cut: 11, complement_cut: 15 },
+    Enzyme { name: "PmeI", site: "GTTTAAAC", cut: 4, complement_cut: 4 },
+    Enzyme { name: "PstI", site: "CTGCAG", cut: 5, complement_cut: 1 },
+    Enzyme { name: "PvuI", site: "CGATCG", cut: 4, complement_cut: 2 },
+    Enzyme { name: "PvuII", site: "CAGCTG", cut: 3, complement_cut: 3 },
+    Enzyme { name: "SacI", site: "GAGCTC", cut: 5, complement_cut: 1 },
+    Enzyme { name: "SalI", site: "GTCGAC", cut: 1, complement_cut: 5 },
+    Enzyme { name: "SapI", site: "GCTCTTC", cut: 8, complement_cut: 11 },
+    Enzyme { name: "SbfI", site: "CCTGCAGG", cut: 6, complement_cut: 2 },
+    Enzyme { name: "ScaI", site: "AGTACT", cut: 3, complement_cut: 3 },
+    Enzyme { name: "SfiI", site: "GGCCNNNNNGGCC", cut: 8, complement_cut: 5 },
+    Enzyme { name: "SmaI", site: "CCCGGG", cut: 3, complement_cut: 3 },
+    Enzyme { name: "SpeI", site: "ACTAGT", cut: 1, complement_cut: 5 },
+    Enzyme { name: "SphI", site: "GCATGC", cut: 5, complement_cut: 1 },
+    Enzyme { name: "StuI", site: "AGGCCT", cut: 3, complement_cut: 3 },
+    Enzyme { name: "TaqI", site: "TCGA", cut: 1, complement_cut: 3 },
+    Enzyme { name: "XbaI", site: "TCTAGA", cut: 1, complement_cut: 5 },
+    Enzyme { name: "XhoI", site: "CTCGAG", cut: 1, complement_cut: 5 },
+    Enzyme { name: "XmaI", site: "CCCGGG", cut: 1, complement_cut: 5 },
+];
+
+pub fn find_enzyme(name: &str) -> Option<&'static Enzyme> {
+    ENZYMES.iter().find(|e| e.name.eq_ignore_ascii_case(name.trim()))
+}
@@ -0,0 +1,210 @@
+//! Expression host suitability of coding sequences: codon adaptation index (Sharp and Li, 1987),
+//! tRNA adaptation index (dos Reis et al., 2004), effective number of codons (Wright, 1990) and
+//! clusters of rare codons.
+
+use std::collections::BTreeMap;
+
+use crate::types::{Biobrick, CodingSequenceScore, ExpressionReport, Location, RareCodonCluster};
+
+use super::analysis::round;
+use super::codon_usage::{CodonUsage, RARE_CODON};
+use super::genetic_code::{codon_index, find_code, GeneticCode};
+use super::recode::{coding_sequences, Cds};
+use super::reverse_complement;
+
+/// Weight of codons the host never uses, which would otherwise zero the index.
+const MIN_WEIGHT: f64 = 0.01;
+/// Rare codons cluster when at least `CLUSTER_RARE` of them fall within `CLUSTER_WINDOW` codons.
+const CLUSTER_WINDOW: usize = 10;
+const CLUSTER_RARE: usize = 3;
+
+fn geometric_mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v.ln(), count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        (sum / count as f64).exp()
+    }
+}
+
+/// Relative adaptiveness of each codon in `host`: its usage over that of the most used synonym.
+fn codon_weights(host: &CodonUsage, code: &GeneticCode) -> [f64; 64] {
+    let mut weights = [0.0; 64];
+    for (index, weight) in weights.iter_mut().enumerate() {
+        let synonyms = host.synonyms(code, index);
+        let best = synonyms[0].1;
+        let share = synonyms.iter().find(|s| s.0 == index).map_or(0.0, |s| s.1);
+        *weight = if best > 0.0 { (share / best).max(MIN_WEIGHT) } else { 1.0 };
+    }
+    weights
+}
+
+/// Decoding capacity of each codon from the host's tRNA genes, including wobble pairing, over
+/// that of the best decoded codon. Codons without a tRNA get the geometric mean of the others.
+fn trna_weights(host: &CodonUsage) -> [f64; 64] {
+    let code = find_code(host.table).expect("host genetic code");
+    let mut copies = [0.0; 64]; // by the codon each anticodon pairs with
+    for (anticodon, count) in host.trna {
+        if let Some(index) = codon_index(reverse_complement(anticodon).as_bytes()) {
+            copies[index] += *count as f64;
+        }
+    }
+
+    // Anticodons ANN are read as INN; wobble selective constraints of dos Reis et al.
+    let mut weights = [0.0; 64];
+    for (index, weight) in weights.iter_mut().enumerate() {
+        let family = index - index % 4;
+        *weight = match index % 4 {
+            0 => copies[index] + (1.0 - 0.41) * copies[family + 1], // NNT, also read by GNN
+            1 => copies[index] + (1.0 - 0.28) * copies[family],     // NNC, also read by INN
+            2 => copies[index] + (1.0 - 0.9999) * copies[family],   // NNA, also read by INN
+            _ => copies[index] + (1.0 - 0.68) * copies[family + 2], // NNG, also read by UNN
+        };
+    }
+    let sense = (0..64).filter(|&i| code.amino_acids[i] != b'*').collect::<Vec<_>>();
+    let best = sense.iter().map(|&i| weights[i]).fold(0.0, f64::max);
+    if best == 0.0 {
+        return [1.0; 64];
+    }
+    let mean = geometric_mean(sense.iter().map(|&i| weights[i] / best).filter(|&w| w > 0.0));
+    weights.map(|w| if w > 0.0 { w / best } else { mean })
+}
+
+/// Effective number of codons of `indices`. Degeneracy classes without a sampled amino acid
+/// count as if their codons were used evenly.
+fn effective_codons(code: &GeneticCode, indices: &[usize]) -> f64 {
+    let mut counts = [0usize; 64];
+    for &index in indices {
+        counts[index] += 1;
+    }
+    let mut families: BTreeMap<u8, Vec<usize>> = BTreeMap::new();
+    for index in (0..64).filter(|&i| code.amino_acids[i] != b'*') {
+        families.entry(code.amino_acids[index]).or_default().push(index);
+    }
+
+    // Per degeneracy: amino acids, and their homozygosities
+    let mut classes: BTreeMap<usize, (usize, Vec<f64>)> = BTreeMap::new();
+    for codons in families.values() {
+        let class = classes.entry(codons.len()).or_default();
+        class.0 += 1;
+        let n = codons.iter().map(|&i| counts[i]).sum::<usize>() as f64;
+        if n > 1.0 {
+            let squares = codons.iter().map(|&i| (counts[i] as f64 / n).powi(2)).sum::<f64>();
+            class.1.push((n * squares - 1.0) / (n - 1.0));
+        }
+    }
+    let enc = classes
+        .iter()
+        .map(|(&degeneracy, (amino_acids, homozygosities))| {
+            let mean = if homozygosities.is_empty() {
+                0.0
+            } else {
+                homozygosities.iter().sum::<f64>() / homozygosities.len() as f64
+            };
+            *amino_acids as f64 / mean.max(1.0 / degeneracy as f64)
+        })
+        .sum::<f64>();
+    let sense = families.values().map(|c| c.len()).sum::<usize>() as f64;
+    enc.min(sense)
+}
+
+/// Top strand location of codons `first` to `last` of `cds`.
+fn codons_location(cds: &Cds, first: usize, last: usize) -> Location {
+    let (start, end) = if cds.forward {
+        (cds.codons[first][0], cds.codons[last][2])
+    } else {
+        (cds.codons[last][2], cds.codons[first][0])
+    };
+    Location {
+        start: start as i32 + 1,
+        end: end as i32 + 1,
+        forward: cds.forward,
+        segments: vec![],
+        partial_start: false,
+        partial_end: false,
+    }
+}
+
+/// Windows of `CLUSTER_WINDOW` codons with at least `CLUSTER_RARE` rare ones, merged where they
+/// overlap and trimmed to their first and last rare codons.
+fn rare_clusters(cds: &Cds, rare: &[bool]) -> Vec<RareCodonCluster> {
+    let mut regions: Vec<(usize, usize)> = Vec::new();
+    for start in 0..rare.len().saturating_sub(CLUSTER_WINDOW - 1) {
+        let end = start + CLUSTER_WINDOW - 1;
+        if rare[start..=end].iter().filter(|&&r| r).count() < CLUSTER_RARE {
+            continue;
+        }
+        match regions.last_mut() {
+            Some(region) if start <= region.1 => region.1 = end,
+            _ => regions.push((start, end)),
+        }
+    }
+    regions
+        .into_iter()
+        .filter_map(|(start, end)| {
+            let first = (start..=end).find(|&k| rare[k])?;
+            let last = (start..=end).rfind(|&k| rare[k])?;
+            Some(RareCodonCluster {
+                first_codon: first + 1,
+                last_codon: last + 1,
+                rare_codons: rare[first..=last].iter().filter(|&&r| r).count(),
+                location: codons_location(cds, first, last),
+            })
+        })
+        .collect()
+}
+
+fn score_cds(biobrick: &Biobrick, cds: &Cds, host: &CodonUsage, trna: &[f64; 64]) -> CodingSequenceScore {
+    let bases = biobrick.sequence.to_uppercase().into_bytes();
+    let weights = codon_weights(host, cds.code);
+    let indices = (0..cds.codons.len()).map(|k| codon_index(&cds.read(&bases, k))).collect::<Vec<_>>();
+    // The start codon is fixed, and stops are not decoded by tRNAs
+    let sense = indices
+        .iter()
+        .skip(1)
+        .flatten()
+        .copied()
+        .filter(|&i| cds.code.amino_acids[i] != b'*')
+        .collect::<Vec<_>>();
+    let degenerate = |i: usize| (0..64).filter(|&j| cds.code.amino_acids[j] == cds.code.amino_acids[i]).count() > 1;
+
+    let rare = indices
+        .iter()
+        .map(|index| {
+            index.is_some_and(|i| {
+                cds.code.amino_acids[i] != b'*'
+                    && degenerate(i)
+                    && host.synonyms(cds.code, i).iter().any(|s| s.0 == i && s.1 < RARE_CODON)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    CodingSequenceScore {
+        feature: cds.feature.clone(),
+        name: biobrick.features.iter().find(|f| f.id == cds.feature).map(|f| f.name.clone()).unwrap_or_default(),
+        codons: cds.codons.len(),
+        cai: round(geometric_mean(sense.iter().filter(|&&i| degenerate(i)).map(|&i| weights[i])), 3),
+        tai: round(geometric_mean(sense.iter().filter(|&&i| cds.code.amino_acids[i] != b'M').map(|&i| trna[i])), 3),
+        enc: round(effective_codons(cds.code, &sense), 1),
+        rare_codons: rare.iter().filter(|&&r| r).count(),
+        rare_clusters: rare_clusters(cds, &rare),
+    }
+}
+
+/// Scores of each coding sequence of `biobrick` for expression in `host`.
+pub fn score(biobrick: &Biobrick, host: &CodonUsage) -> ExpressionReport {
+    let mut warnings = Vec::new();
+    let cdss = coding_sequences(biobrick, &mut warnings);
+    let trna = trna_weights(host);
+    let host_code = find_code(host.table).expect("host genetic code");
+    for cds in cdss.iter().filter(|cds| cds.code.amino_acids != host_code.amino_acids) {
+        warnings.push(format!("{} uses genetic code {}, which {} does not", cds.feature, cds.code.id, host.organism));
+    }
+    ExpressionReport {
+        id: biobrick.metadata.id.clone(),
+        host: host.host.to_string(),
+        organism: host.organism.to_string(),
+        coding_sequences: cdss.iter().map(|cds| score_cds(biobrick, cds, host, &trna)).collect(),
+        warnings,
+    }
+}
@@ -0,0 +1,155 @@
+//! NCBI genetic code tables, as amino acids and start codons indexed by codon with T = 0,
+//! C = 1, A = 2, G = 3.
+
+pub struct GeneticCode {
+    pub id: u8,
+    pub amino_acids: &'static [u8; 64],
+    pub starts: &'static [u8; 64], // `M` for initiation codons
+}
+
+pub const CODES: &[GeneticCode] = &[
+    // Standard
+    GeneticCode {
+        id: 1,
+        amino_acids: b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"---M------**--*----M---------------M----------------------------",
+    },
+    // Vertebrate Mitochondrial
+    GeneticCode {
+        id: 2,
+        amino_acids: b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+        starts: b"----------**--------------------MMMM----------**---M------------",
+    },
+    // Yeast Mitochondrial
+    GeneticCode {
+        id: 3,
+        amino_acids: b"FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"----------**----------------------MM---------------M------------",
+    },
+    // Mold, Protozoan, and Coelenterate Mitochondrial; Mycoplasma; Spiroplasma
+    GeneticCode {
+        id: 4,
+        amino_acids: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"--MM------**-------M------------MMMM---------------M------------",
+    },
+    // Invertebrate Mitochondrial
+    GeneticCode {
+        id: 5,
+        amino_acids: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG",
+        starts: b"---M------**--------------------MMMM---------------M------------",
+    },
+    // Ciliate, Dasycladacean and Hexamita Nuclear
+    GeneticCode {
+        id: 6,
+        amino_acids: b"FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"--------------*--------------------M----------------------------",
+    },
+    // Echinoderm and Flatworm Mitochondrial
+    GeneticCode {
+        id: 9,
+        amino_acids: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: b"-----------------------------------M---------------M------------",
+    },
+    // Euplotid Nuclear
+    GeneticCode {
+        id: 10,
+        amino_acids: b"FFLLSSSSYY**CCCWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"-----------------------------------M----------------------------",
+    },
+    // Bacterial, Archaeal and Plant Plastid
+    GeneticCode {
+        id: 11,
+        amino_acids: b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"---M------**--*----M------------MMMM---------------M------------",
+    },
+    // Alternative Yeast Nuclear
+    GeneticCode {
+        id: 12,
+        amino_acids: b"FFLLSSSSYY**CC*WLLLSPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"-------------------M---------------M----------------------------",
+    },
+    // Ascidian Mitochondrial
+    GeneticCode {
+        id: 13,
+        amino_acids: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSGGVVVVAAAADDEEGGGG",
+        starts: b"---M------------------------------MM---------------M------------",
+    },
+    // Alternative Flatworm Mitochondrial
+    GeneticCode {
+        id: 14,
+        amino_acids: b"FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: b"-----------------------------------M----------------------------",
+    },
+    // Chlorophycean Mitochondrial
+    GeneticCode {
+        id: 16,
+        amino_acids: b"FFLLSSSSYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"-----------------------------------M----------------------------",
+    },
+    // Trematode Mitochondrial
+    GeneticCode {
+        id: 21,
+        amino_acids: b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: b"-----------------------------------M---------------M------------",
+    },
+    // Scenedesmus obliquus Mitochondrial
+    GeneticCode {
+        id: 22,
+        amino_acids: b"FFLLSS*SYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"-----------------------------------M----------------------------",
+    },
+    // Thraustochytrium Mitochondrial
+    GeneticCode {
+        id: 23,
+        amino_acids: b"FF*LSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"--------------------------------M--M---------------M------------",
+    },
+];
+
+pub const STANDARD: &GeneticCode = &CODES[0];
+/// Default for features and ORFs, as most parts are expressed in bacteria.
+pub const BACTERIAL: u8 = 11;
+
+pub fn find_code(id: u8) -> Option<&'static GeneticCode> {
+    CODES.iter().find(|c| c.id == id)
+}
+
+pub fn codon_index(codon: &[u8]) -> Option<usize> {
+    let mut index = 0;
+    for base in codon {
+        let bits = match base.to_ascii_uppercase() {
+            b'T' | b'U' => 0,
+            b'C' => 1,
+            b'A' => 2,
+            b'G' => 3,
+            _ => return None,
+        };
+        index = index * 4 + bits;
+    }
+    (codon.len() == 3).then_some(index)
+}
+
+/// Codon at `index`, the inverse of `codon_index`.
+pub fn codon(index: usize) -> String {
+    [index / 16, index / 4 % 4, index % 4].iter().map(|&i| b"TCAG"[i] as char).collect()
+}
+
+impl GeneticCode {
+    /// Amino acid of a codon, `X` when it contains an ambiguous base.
+    pub fn translate_codon(&self, codon: &[u8]) -> u8 {
+        codon_index(codon).map_or(b'X', |i| self.amino_acids[i])
+    }
+
+    pub fn is_start(&self, codon: &[u8]) -> bool {
+        codon_index(codon).is_some_and(|i| self.starts[i] == b'M')
+    }
+
+    pub fn is_stop(&self, codon: &[u8]) -> bool {
+        self.translate_codon(codon) == b'*'
+    }
+
+    /// Translation of every complete codon of `sequence` in frame, stops included as `*`.
+    pub fn translate(&self, sequence: &str) -> String {
+        sequence.as_bytes().chunks_exact(3).map(|codon| self.translate_codon(codon) as char).collect()
+    }
+}
@@ -0,0 +1,194 @@
+//! Bundled library of common plasmid features, used by the annotation engine alongside cached
+//! BioBricks. Coding sequences are given as proteins so codon-optimized variants are found too.
+
+pub struct LibraryFeature {
+    pub name: &'static str,
+    pub r#type: &'static str, // ontology canonical
+    pub sequence: &'static str,
+    pub protein: bool,
+}
+
+pub const FEATURES: &[LibraryFeature] = &[
+    // Promoters and operators
+    LibraryFeature {
+        name: "T7 promoter",
+        r#type: "promoter",
+        sequence: "TAATACGACTCACTATAG",
+        protein: false,
+    },
+    LibraryFeature {
+        name: "T3 promoter",
+        r#type: "promoter",
+        sequence: "AATTAACCCTCACTAAAGG",
+        protein: false,
+    },
+    LibraryFeature {
+        name: "SP6 promoter",
+        r#type: "promoter",
+        sequence: "ATTTAGGTGACACTATAG",
+        protein: false,
+    },
+    LibraryFeature {
+        name: "lac promoter",
+        r#type: "promoter",
+        sequence: "TTTACACTTTATGCTTCCGGCTCGTATGTTG",
+        protein: false,
+    },
+    LibraryFeature {
+        name: "lac operator",
+        r#type: "operator",
+        sequence: "TTGTGAGCGGATAACAA",
+        protein: false,
+    },
+    LibraryFeature {
+        name: "tet operator",
+        r#type: "operator",
+        sequence: "TCCCTATCAGTGATAGAGA",
+        protein: false,
+    },
+    // Terminators
+    LibraryFeature {
+        name: "T7 terminator",
+        r#type: "terminator",
+        sequence: "CTAGCATAACCCCTTGGGGCCTCTAAACGGGTCTTGAGGGGTTTTTTG",
+        protein: false,
+    },
+    LibraryFeature {
+        name: "rrnB T1 terminator",
+        r#type: "terminator",
+        sequence: "CAAATAAAACGAAAGGCTCAGTCGAAAGACTGGGCCTTTCGTTTTATCTGTTGTTTGTCGGTGAACGCTCTC",
+        protein: false,
+    },
+    // Origins of replication
+    LibraryFeature {
+        name: "pUC ori",
+        r#type: "origin-of-replication",
+        sequence: "TTGAGATCCTTTTTTTCTGCGCGTAATCTGCTGCTTGCAAACAAAAAAACCACCGCTACCAGCGGTGGTTTGTTTGCCGGATCAAGAGCTACCAACTCTTTTTCCGAAGGTAACTGGCTTCAGCAGAGCGCAGATACCAAATACTGTTCTTCTAGTGTAGCCGTAGTTAGGCCACCACTTCAAGAACTCTGTAGCACCGCCTACATACCTCGCTCTGCTAATCCTGTTACCAGTGGCTGCTGCCAGTGGCGATAAGTCGTGTCTTACCGGGTTGGACTCAAGACGATAGTTACCGGATAAGGCGCAGCGGTCGGGCTGAACGGGGGGTTCGTGCACACAGCCCAGCTTGGAGCGAACGACCTACACCGAACTGAGATACCTACAGCGTGAGCTATGAGAAAGCGCCACGCTTCCCGAAGGGAGAAAGGCGGACAGGTATCCGGTAAGCGGCAGGGTCGGAACAGGAGAGCGCACGAGGGAGCTTCCAGGGGGAAACGCCTGGTATCTTTATAGTCCTGTCGGGTTTCGCCACCTCTGACTTGAGCGTCGATTTTTGTGATGCTCGTCAGGGGGGCGGAGCCTATGGAAAAA",
+        protein: false,
+    },
+    // Primer binding and recombination sites
+    LibraryFeature {
+        name: "M13 fwd",
+        r#type: "primer-binding-site",
+        sequence: "GTAAAACGACGGCCAGT",
+        protein: false,
+    },
+    LibraryFeature {
+        name: "M13 rev",
+        r#type: "primer-binding-site",
+        sequence: "CAGGAAACAGCTATGAC",
+        protein: false,
+    },
+    LibraryFeature {
+        name: "loxP",
+        r#type: "recombination-site",
+        sequence: "ATAACTTCGTATAGCATACATTATACGAAGTTAT",
+        protein: false,
+    },
+    LibraryFeature {
+        name: "FRT",
+        r#type: "recombination-site",
+        sequence: "GAAGTTCCTATTCTCTAGAAAGTATAGGAACTTC",
+        protein: false,
+    },
+    LibraryFeature {
+        name: "attB1",
+        r#type: "recombination-site",
+        sequence: "ACAAGTTTGTACAAAAAAGCAGGCT",
+        protein: false,
+    },
+    LibraryFeature {
+        name: "attB2",
+        r#type: "recombination-site",
+        sequence: "ACCACTTTGTACAAGAAAGCTGGGT",
+        protein: false,
+    },
+    // Resistance markers
+    LibraryFeature {
+        name: "AmpR",
+        r#type: "coding-sequence",
+        sequence: "MSIQHFRVALIPFFAAFCLPVFAHPETLVKVKDAEDQLGARVGYIELDLNSGKILESFRPEERFPMMSTFKVLLCGAVLSRIDAGQEQLGRRIHYSQNDLVEYSPVTEKHLTDGMTVRELCSAAITMSDNTAANLLLTTIGGPKELTAFLHNMGDHVTRLDRWEPELNEAIPNDERDTTMPVAMATTLRKLLTGELLTLASRQQLIDWMEADKVAGPLLRSALPAGWFIADKSGAGERGSRGIIAALGPDGKPSRIVVIYTTGSQATMDERNRQIAEIGASLIKHW",
+        protein: true,
+    },
+    LibraryFeature {
+        name: "KanR",
+        r#type: "coding-sequence",
+        sequence: "MSHIQRETSCSRPRLNSNMDADLYGYKWARDNVGQSGATIYRLYGKPDAPELFLKHGKGSVANDVTDEMVRLNWLTEFMPLPTIKHFIRTPDDAWLLTTAIPGKTAFQVLEEYPDSGENIVDALAVFLRRLHSIPVCNCPFNSDRVFRLAQAQSRMNNGLVDASDFDDERNGWPVEQVWKEMHKLLPFSPDSVVTHGDFSLDNLIFDEGKLIGCIDVGRVGIADRYQDLAILWNCLGEFSPSLQKRLFQKYGIDNPDMNKLQFHLMLDEFF",
+        protein: true,
+    },
+    LibraryFeature {
+        name: "NeoR/KanR",
+        r#type: "coding-sequence",
+        sequence: "MIEQDGLHAGSPAAWVERLFGYDWAQQTIGCSDAAVFRLSAQGRPVLFVKTDLSGALNELQDEAARLSWLATTGVPCAAVLDVVTEAGRDWLLLGEVPGQDLLSSHLAPAEKVSIMADAMRRLHTLDPATCPFDHQAKHRIERARTRMEAGLVDQDDLDEEHQGLAPAELFARLKARMPDGEDLVVTHGDACLPNIMVENGRFSGFIDCGRLGVADRYQDIALATRDIAEELGGEWADRFLVLYGIAAPDSQRIAFYRLLDEFF",
+        protein: true,
+    },
+    LibraryFeature {
+        name: "CmR",
+        r#type: "coding-sequence",
+        sequence: "MEKKITGYTTVDISQWHRKEHFEAFQSVAQCTYNQTVQLDITAFLKTVKKNKHKFYPAFIHILARLMNAHPEFRMAMKDGELVIWDSVHPCYTVFHEQTETFSSLWSEYHDDFRQFLHIYSQDVACYGENLAYFPKGFIENMFFVSANPWVSFTSFDLNVANMDNFFAPVFTMGKYYTQGDKVLMPLAIQVHHAVCDGFHVGRMLNELQQYCDEWQGGA",
+        protein: true,
+    },
+    // Regulators
+    LibraryFeature {
+        name: "lacI",
+        r#type: "coding-sequence",
+        sequence: "MKPVTLYDVAEYAGVSYQTVSRVVNQASHVSAKTREKVEAAMAELNYIPNRVAQQLAGKQSLLIGVATSSLALHAPSQIVAAIKSRADQLGASVVVSMVERSGVEACKAAVHNLLAQRVSGLIINYPLDDQDAIAVEAACTNVPALFLDVSDQTPINSIIFSHEDGTRLGVEHLVALGHQQIAMLAGPLSSVSARLRLAGWHKYLTRNQIQPIAEREGDWSAMSGFQQTMQMLNEGIVPTAMLVANDQMALGAMRAITESGLRVGADISVVGYDDTEDSSCYIPPLTTIKQDFRLLGQTSVDRLLQLSQGQAVKGNQLLPVSLVKRKTTLAPNTQTASPRALADSLMQLARQVSRLESGQ",
+        protein: true,
+    },
+    LibraryFeature {
+        name: "tetR",
+        r#type: "coding-sequence",
+        sequence: "MSRLDKSKVINSALELLNEVGIEGLTTRKLAQKLGVEQPTLYWHVKNKRALLDALAIEMLDRHHTHFCPLEGESWQDFLRNNAKSFRCALLSHRDGAKVHLGTRPTEKQYETLENQLAFLCQQGFSLENALYALSAVGHFTLGCVLEDQEHQVAKEERETPTTDSMPPLLRQAIELFDHQGAEPAFLFGLELIICGLEKQLKCESGS",
+        protein: true,
+    },
+    // Fluorescent proteins
+    LibraryFeature {
+        name: "GFP",
+        r#type: "coding-sequence",
+        sequence: "MVSKGEELFTGVVPILVELDGDVNGHKFSVSGEGEGDATYGKLTLKFICTTGKLPVPWPTLVTTLTYGVQCFSRYPDHMKQHDFFKSAMPEGYVQERTIFFKDDGNYKTRAEVKFEGDTLVNRIELKGIDFKEDGNILGHKLEYNYNSHNVYIMADKQKNGIKVNFKIRHNIEDGSVQLADHYQQNTPIGDGPVLLPDNHYLSTQSALSKDPNEKRDHMVLLEFVTAAGITLGMDELYK",
+        protein: true,
+    },
+    LibraryFeature {
+        name: "mCherry",
+        r#type: "coding-sequence",
+        sequence: "MVSKGEEDNMAIIKEFMRFKVHMEGSVNGHEFEIEGEGEGRPYEGTQTAKLKVTKGGPLPFAWDILSPQFMYGSKAYVKHPADIPDYLKLSFPEGFKWERVMNFEDGGVVTVTQDSSLQDGEFIYKVKLRGTNFPSDGPVMQKKTMGWEASSERMYPEDGALKGEIKQRLKLKDGGHYDAEVKTTYKAKKPVQLPGAYNVNIKLDITSHNEDYTIVEQYERAEGRHSTGGMDELYK",
+        protein: true,
+    },
+    // Tags
+    LibraryFeature {
+        name: "6xHis",
+        r#type: "protein-tag",
+        sequence: "HHHHHH",
+        protein: true,
+    },
+    LibraryFeature {
+        name: "FLAG",
+        r#type: "protein-tag",
+        sequence: "DYKDDDDK",
+        protein: true,
+    },
+    LibraryFeature {
+        name: "HA",
+        r#type: "protein-tag",
+        sequence: "YPYDVPDYA",
+        protein: true,
+    },
+    LibraryFeature {
+        name: "Myc",
+        r#type: "protein-tag",
+        sequence: "EQKLISEEDL",
+        protein: true,
+    },
+    LibraryFeature {
+        name: "V5",
+        r#type: "protein-tag",
+        sequence: "GKPIPNPLLGLDST",
+        protein: true,
+    },
+    LibraryFeature {
+        name: "Strep-tag II",
+        r#type: "protein-tag",
+        sequence: "WSHPQFEK",
+        protein: true,
+    },
+];
@@ -0,0 +1,103 @@
+//! DNA sequence utilities shared by the sequence search and analysis endpoints.
+
+pub mod analysis;
+pub mod annotate;
+pub mod codon_usage;
+pub mod compatibility;
+pub mod digest;
+pub mod enzymes;
+pub mod expression;
+pub mod genetic_code;
+pub mod library;
+pub mod motif;
+pub mod orfs;
+pub mod primers;
+pub mod recode;
+pub mod similarity;
+pub mod synthesis;
+
+/// Complement of an IUPAC nucleotide code, keeping case. Unknown characters are kept as is.
+pub fn complement(base: u8) -> u8 {
+    match base {
+        b'A' => b'T',
+        b'T' | b'U' => b'A',
+        b'G' => b'C',
+        b'C' => b'G',
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'K' => b'M',
+        b'M' => b'K',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        b'S' | b'W' | b'N' => base,
+        b'a'..=b'z' => complement(base.to_ascii_uppercase()).to_ascii_lowercase(),
+        _ => base,
+    }
+}
+
+pub fn reverse_complement(sequence: &str) -> String {
+    sequence.bytes().rev().map(|b| complement(b) as char).collect()
+}
+
+/// Uppercase IUPAC sequence from user input, accepting raw or FASTA text with whitespace and
+/// position numbers. `None` when it contains anything else.
+pub fn clean_sequence(input: &str) -> Option<String> {
+    let mut sequence = String::new();
+    for line in input.lines().filter(|l| !l.trim_start().starts_with('>')) {
+        for c in line.chars().filter(|c| !c.is_whitespace() && !c.is_ascii_digit()) {
+            let c = c.to_ascii_uppercase();
+            if !"ACGTURYKMSWBDHVN".contains(c) {
+                return None;
+            }
+            sequence.push(if c == 'U' { 'T' } else { c });
+        }
+    }
+    Some(sequence)
+}
+
+/// Translation of every complete codon of `sequence` in frame in the standard code, stops
+/// included as `*`.
+pub fn translate(sequence: &str) -> String {
+    genetic_code::STANDARD.translate(sequence)
+}
+
+/// Bases of `location` in `sequence`, read on its strand. Segments spanning the origin wrap.
+pub fn feature_sequence(sequence: &str, location: &crate::types::Location) -> String {
+    let bytes = sequence.as_bytes();
+    let len = bytes.len() as i32;
+    if len == 0 {
+        return String::new();
+    }
+    let mut top = String::new();
+    for segment in location.ranges() {
+        let length = (segment.end - segment.start).rem_euclid(len) + 1;
+        top.extend((0..length).map(|k| bytes[((segment.start - 1 + k).rem_euclid(len)) as usize] as char));
+    }
+    if location.forward {
+        top
+    } else {
+        reverse_complement(&top)
+    }
+}
+
+/// `length` bases from 0-based `start`, wrapping around the end of `sequence`.
+pub fn slice(sequence: &str, start: i64, length: usize) -> String {
+    let bytes = sequence.as_bytes();
+    let len = bytes.len() as i64;
+    (0..length as i64).map(|k| bytes[(start + k).rem_euclid(len) as usize] as char).collect()
+}
+
+/// Location of `length` bases from 0-based `start` in a sequence of `len` bases, wrapping
+/// around the origin.
+pub fn location(start: i64, length: usize, forward: bool, len: i64) -> crate::types::Location {
+    crate::types::Location {
+        start: (start.rem_euclid(len) + 1) as i32,
+        end: ((start + length as i64 - 1).rem_euclid(len) + 1) as i32,
+        forward,
+        segments: vec![],
+        partial_start: false,
+        partial_end: false,
+    }
+}
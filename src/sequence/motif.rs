@@ -0,0 +1,260 @@
+//! IUPAC motif search with mismatches, on both strands and across the origin of circular parts.
+
+use std::collections::HashSet;
+
+use crate::cache::SqliteCache;
+use crate::types::{Location, MotifHit, MotifSite};
+
+use super::complement;
+
+const MAX_LENGTH: usize = 100;
+const MAX_VARIANTS: usize = 64;
+const SNIPPET_CONTEXT: usize = 10;
+
+/// Bitmask of the bases an IUPAC code stands for (A = 1, C = 2, G = 4, T = 8).
+pub fn iupac_mask(code: u8) -> u8 {
+    match code.to_ascii_uppercase() {
+        b'A' => 1,
+        b'C' => 2,
+        b'G' => 4,
+        b'T' | b'U' => 8,
+        b'R' => 1 | 4,
+        b'Y' => 2 | 8,
+        b'S' => 2 | 4,
+        b'W' => 1 | 8,
+        b'K' => 4 | 8,
+        b'M' => 1 | 2,
+        b'B' => 2 | 4 | 8,
+        b'D' => 1 | 4 | 8,
+        b'H' => 1 | 2 | 8,
+        b'V' => 1 | 2 | 4,
+        b'N' | b'.' => 15,
+        _ => 0,
+    }
+}
+
+/// Fixed-length variants of a motif, as IUPAC masks. Codes may be followed by `{n}` or `{min,max}`
+/// to repeat them, e.g. `TTGACAN{15,19}TATAAT`; `.` stands for `N`.
+pub fn parse_motif(motif: &str) -> Result<Vec<Vec<u8>>, String> {
+    let mut variants: Vec<Vec<u8>> = vec![vec![]];
+    let mut chars = motif.trim().bytes().filter(|b| !b.is_ascii_whitespace()).peekable();
+    while let Some(code) = chars.next() {
+        let mask = iupac_mask(code);
+        if mask == 0 {
+            return Err(format!("Invalid IUPAC code: {}", code as char));
+        }
+
+        let (min, max) = if chars.peek() == Some(&b'{') {
+            chars.next();
+            let mut range = String::new();
+            let mut closed = false;
+            for c in chars.by_ref() {
+                if c == b'}' {
+                    closed = true;
+                    break;
+                }
+                range.push(c as char);
+            }
+            let mut bounds = range.split(',').map(|b| b.trim().parse::<usize>());
+            match (bounds.next(), bounds.next(), bounds.next()) {
+                (Some(Ok(n)), None, None) if closed => (n, n),
+                (Some(Ok(min)), Some(Ok(max)), None) if closed && min <= max => (min, max),
+                _ => return Err(format!("Invalid repeat: {{{}}}", range)),
+            }
+        } else {
+            (1, 1)
+        };
+
+        variants = variants
+            .into_iter()
+            .flat_map(|variant| {
+                (min..=max).map(move |count| {
+                    let mut extended = variant.clone();
+                    extended.extend(std::iter::repeat_n(mask, count));
+                    extended
+                })
+            })
+            .collect();
+        if variants.len() > MAX_VARIANTS {
+            return Err(format!("Motif expands to more than {} variants", MAX_VARIANTS));
+        }
+        if variants.iter().any(|v| v.len() > MAX_LENGTH) {
+            return Err(format!("Motif is longer than {} bp", MAX_LENGTH));
+        }
+    }
+
+    variants.retain(|v| !v.is_empty());
+    if variants.is_empty() {
+        return Err("Empty motif".to_string());
+    }
+    Ok(variants)
+}
+
+/// Sites of any motif variant in `sequence`, with at most `mismatches` mismatching positions,
+/// sorted by position. Palindromic sites are reported once, on the forward strand.
+pub fn find_sites(sequence: &str, circular: bool, variants: &[Vec<u8>], mismatches: usize) -> Vec<MotifSite> {
+    let bases = sequence.as_bytes();
+    let len = bases.len();
+    let longest = variants.iter().map(|v| v.len()).max().unwrap_or(0);
+    if len == 0 || longest == 0 {
+        return vec![];
+    }
+
+    let extended = if circular {
+        bases.iter().chain(bases.iter().cycle().take(longest.min(len) - 1)).copied().collect::<Vec<_>>()
+    } else {
+        bases.to_vec()
+    };
+    let masks = extended.iter().map(|&b| iupac_mask(b)).collect::<Vec<_>>();
+
+    let mut sites: Vec<MotifSite> = Vec::new();
+    let mut seen: HashSet<(usize, usize)> = HashSet::new();
+    for variant in variants {
+        // The reverse strand is searched with the reverse complement of the motif
+        let reverse = variant
+            .iter()
+            .rev()
+            .map(|&m| (0..4).filter(|bit| m & (1 << bit) != 0).fold(0, |acc, bit| acc | (1 << (3 - bit))))
+            .collect::<Vec<u8>>();
+        for (forward, pattern) in [(true, variant), (false, &reverse)] {
+            for start in 0..len.min((masks.len() + 1).saturating_sub(pattern.len())) {
+                let mut found = 0;
+                for (offset, &motif_mask) in pattern.iter().enumerate() {
+                    // Ambiguous bases of the part match only codes covering all their bases
+                    let base_mask = masks[start + offset];
+                    if base_mask == 0 || base_mask & !motif_mask != 0 {
+                        found += 1;
+                        if found > mismatches {
+                            break;
+                        }
+                    }
+                }
+                if found > mismatches {
+                    continue;
+                }
+
+                let end = start + pattern.len();
+                if !seen.insert((start, end)) {
+                    continue;
+                }
+                let site = String::from_utf8_lossy(&extended[start..end]).to_uppercase();
+                sites.push(MotifSite {
+                    location: Location {
+                        start: start as i32 + 1,
+                        end: ((end - 1) % len) as i32 + 1,
+                        forward,
+                        segments: vec![],
+                        partial_start: false,
+                        partial_end: false,
+                    },
+                    mismatches: found,
+                    sequence: if forward {
+                        site
+                    } else {
+                        site.bytes().rev().map(|b| complement(b) as char).collect()
+                    },
+                });
+            }
+        }
+    }
+
+    sites.sort_by_key(|s| (s.location.start, !s.location.forward, s.sequence.len()));
+    sites
+}
+
+/// Forward-strand text around a site, with the site highlighted like lexical search snippets.
+pub fn snippet(sequence: &str, circular: bool, site: &MotifSite) -> String {
+    let bases = sequence.as_bytes();
+    let len = bases.len() as i64;
+    let start = site.location.start as i64 - 1;
+    let site_len = site.sequence.len() as i64;
+    let at = |i: i64| -> Option<char> {
+        if circular {
+            Some(bases[i.rem_euclid(len) as usize] as char)
+        } else {
+            (0..len).contains(&i).then(|| bases[i as usize] as char)
+        }
+    };
+
+    let context = SNIPPET_CONTEXT as i64;
+    let before = (start - context..start).filter_map(at).collect::<String>().to_lowercase();
+    let matched = (start..start + site_len).filter_map(at).collect::<String>().to_uppercase();
+    let after = (start + site_len..start + site_len + context).filter_map(at).collect::<String>().to_lowercase();
+    let leading = if circular || start > context { "…" } else { "" };
+    let trailing = if circular || start + site_len + context < len { "…" } else { "" };
+    format!("{}{}<mark>{}</mark>{}{}", leading, before, matched, after, trailing)
+}
+
+/// Cached parts with at least one site, best site first, then by number of sites.
+pub fn motif_search(cache: &SqliteCache, variants: &[Vec<u8>], mismatches: usize, n: usize) -> Result<(usize, Vec<MotifHit>), rusqlite::Error> {
+    let mut hits = Vec::new();
+    for biobrick in cache.list_parts()? {
+        let circular = biobrick.metadata.circular;
+        let sites = find_sites(&biobrick.sequence, circular, variants, mismatches);
+        let Some(best) = sites.iter().min_by_key(|s| (s.mismatches, s.location.start)) else {
+            continue;
+        };
+        hits.push(MotifHit {
+            r#match: 1.0 - best.mismatches as f64 / best.sequence.len() as f64,
+            snippet: Some(snippet(&biobrick.sequence, circular, best)),
+            sites,
+            biobrick,
+        });
+    }
+
+    hits.sort_by(|a, b| {
+        b.r#match
+            .total_cmp(&a.r#match)
+            .then_with(|| b.sites.len().cmp(&a.sites.len()))
+            .then_with(|| a.biobrick.metadata.id.cmp(&b.biobrick.metadata.id))
+    });
+    let total = hits.len();
+    hits.truncate(n);
+    Ok((total, hits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sites(sequence: &str, circular: bool, motif: &str, mismatches: usize) -> Vec<(i32, i32, bool, usize, String)> {
+        let variants = parse_motif(motif).unwrap();
+        find_sites(sequence, circular, &variants, mismatches)
+            .into_iter()
+            .map(|s| (s.location.start, s.location.end, s.location.forward, s.mismatches, s.sequence))
+            .collect()
+    }
+
+    #[test]
+    fn expands_repeats() {
+        let variants = parse_motif("TTGACAN{15,19}TATAAT").unwrap();
+        assert_eq!(variants.iter().map(|v| v.len()).collect::<Vec<_>>(), [27, 28, 29, 30, 31]);
+        assert!(variants.iter().all(|v| v[6..v.len() - 6].iter().all(|&m| m == 15)));
+
+        assert_eq!(parse_motif("AN{0,9}CN{0,9}G"), Err(format!("Motif expands to more than {} variants", MAX_VARIANTS)));
+        assert!(parse_motif("AN{3").is_err());
+        assert!(parse_motif("AXG").is_err());
+    }
+
+    #[test]
+    fn finds_reverse_strand_sites() {
+        assert_eq!(sites("AAAGAGACCAAA", false, "GGTCTC", 0), [(4, 9, false, 0, "GGTCTC".to_string())]);
+    }
+
+    #[test]
+    fn reports_palindromes_once() {
+        assert_eq!(sites("AAGAATTCAA", false, "GAATTC", 0), [(3, 8, true, 0, "GAATTC".to_string())]);
+    }
+
+    #[test]
+    fn finds_sites_across_the_origin() {
+        assert_eq!(sites("TTCAAAAGAA", true, "GAATTC", 0), [(8, 3, true, 0, "GAATTC".to_string())]);
+        assert!(sites("TTCAAAAGAA", false, "GAATTC", 0).is_empty());
+    }
+
+    #[test]
+    fn counts_mismatches() {
+        assert_eq!(sites("CCGACTTCCC", false, "GAATTC", 1), [(3, 8, true, 1, "GACTTC".to_string())]);
+        assert!(sites("CCGACTTCCC", false, "GAATTC", 0).is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+//! Open reading frames, and translation of annotated coding sequences.
+
+use std::collections::HashMap;
+
+use crate::ontology::find_canonical;
+use crate::types::{Biobrick, Location, MetaFeature, Orf, Translation};
+
+use super::genetic_code::{find_code, GeneticCode, BACTERIAL};
+use super::{feature_sequence, reverse_complement};
+
+/// ORFs of at least `min_length` codons, from a start codon to the first stop in frame, on both
+/// strands. Nested starts are folded into the longest ORF ending at each stop.
+pub fn find_orfs(sequence: &str, circular: bool, code: &GeneticCode, min_length: usize, alternative_starts: bool) -> Vec<Orf> {
+    let sequence = sequence.to_uppercase();
+    let n = sequence.len();
+    if n == 0 {
+        return vec![];
+    }
+    let is_start = |codon: &[u8]| if alternative_starts { code.is_start(codon) } else { codon == b"ATG" };
+
+    // Longest ORF per strand and stop codon, as (start, end) in strand coordinates
+    let mut longest: HashMap<(bool, usize), (usize, usize)> = HashMap::new();
+    for forward in [true, false] {
+        let strand = if forward { sequence.clone() } else { reverse_complement(&sequence) };
+        // Circular sequences are read twice over so ORFs can span the origin
+        let scan = if circular { format!("{}{}", strand, strand) } else { strand };
+        let bytes = scan.as_bytes();
+        for frame in 0..3 {
+            let mut start = None;
+            for position in (frame..bytes.len().saturating_sub(2)).step_by(3) {
+                let codon = &bytes[position..position + 3];
+                if start.is_none() && position < n && is_start(codon) {
+                    start = Some(position);
+                } else if code.is_stop(codon) {
+                    if let Some(first) = start.take() {
+                        let end = position + 3;
+                        let best = longest.entry((forward, (end - 1) % n)).or_insert((first, end));
+                        if end - first > best.1 - best.0 {
+                            *best = (first, end);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut orfs = longest
+        .into_iter()
+        .filter(|((_, _), (start, end))| (end - start) / 3 > min_length && end - start <= n)
+        .map(|((forward, _), (start, end))| {
+            let (low, high) = if forward {
+                (start % n, (end - 1) % n)
+            } else {
+                ((2 * n - end) % n, (2 * n - start - 1) % n)
+            };
+            let location = Location {
+                start: low as i32 + 1,
+                end: high as i32 + 1,
+                forward,
+                segments: vec![],
+                partial_start: false,
+                partial_end: false,
+            };
+            let bases = feature_sequence(&sequence, &location);
+            let mut protein = code.translate(&bases);
+            protein.pop();
+            protein.replace_range(..1, "M");
+            let frame = (start % n % 3 + 1) as i8;
+            (location, if forward { frame } else { -frame }, protein)
+        })
+        .collect::<Vec<_>>();
+    orfs.sort_by_key(|(location, _, _)| (location.start, location.end));
+
+    let r#type = find_canonical("coding-sequence").expect("coding-sequence type");
+    orfs.into_iter()
+        .enumerate()
+        .map(|(i, (location, frame, protein))| {
+            let name = format!("ORF{}", i + 1);
+            Orf {
+                feature: MetaFeature {
+                    id: format!("{}_{}", name, location.start),
+                    name,
+                    r#type: r#type.into(),
+                    location,
+                    translation: None,
+                },
+                frame,
+                length: protein.len(),
+                protein,
+            }
+        })
+        .collect()
+}
+
+/// Translation of a coding sequence feature, in the genetic code of its previous translation
+/// (`/transl_table` in GenBank) or the bacterial one, checked against the reported one.
+pub fn translate_feature(sequence: &str, feature: &MetaFeature) -> Translation {
+    let previous = feature.translation.clone().unwrap_or_default();
+    let code = find_code(previous.table)
+        .or_else(|| find_code(BACTERIAL))
+        .expect("bacterial genetic code");
+    let bases = feature_sequence(sequence, &feature.location).to_uppercase();
+    let codons = bases.as_bytes().chunks_exact(3).collect::<Vec<_>>();
+
+    let missing_start = codons.first().is_none_or(|c| !code.is_start(c));
+    let missing_stop = !bases.len().is_multiple_of(3) || codons.last().is_none_or(|c| !code.is_stop(c));
+    let mut protein = codons.iter().map(|c| code.translate_codon(c) as char).collect::<String>();
+    if !missing_stop {
+        protein.pop();
+    }
+    // Alternative initiation codons still code for methionine
+    if !missing_start && !feature.location.partial_start {
+        protein.replace_range(..1, "M");
+    }
+    let matches_reported = previous.reported.as_ref().map(|r| r.trim_end_matches('*') == protein);
+
+    Translation {
+        internal_stop: protein.contains('*'),
+        protein,
+        table: code.id,
+        missing_start,
+        missing_stop,
+        reported: previous.reported,
+        matches_reported,
+    }
+}
+
+/// Fills in the translation of every coding sequence feature of `biobrick`.
+pub fn translate_features(biobrick: &mut Biobrick) {
+    let sequence = biobrick.sequence.clone();
+    for feature in &mut biobrick.features {
+        if feature.r#type.canonical == "coding-sequence" {
+            feature.translation = Some(translate_feature(&sequence, feature));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cds(start: i32, end: i32, forward: bool, translation: Option<Translation>) -> MetaFeature {
+        MetaFeature {
+            id: "cds".to_string(),
+            name: "cds".to_string(),
+            r#type: find_canonical("coding-sequence").unwrap().into(),
+            location: Location { start, end, forward, segments: vec![], partial_start: false, partial_end: false },
+            translation,
+        }
+    }
+
+    fn summary(orfs: &[Orf]) -> Vec<(i32, i32, i8, &str)> {
+        orfs.iter().map(|o| (o.feature.location.start, o.feature.location.end, o.frame, o.protein.as_str())).collect()
+    }
+
+    #[test]
+    fn finds_orfs_on_both_strands() {
+        // MKKK in frame 2, and MPPP on the reverse strand
+        let sequence = "CATGAAAAAAAAATAATTTTAGGGGGGGGGCAT";
+        let code = find_code(BACTERIAL).unwrap();
+        let orfs = find_orfs(sequence, false, code, 3, false);
+        assert_eq!(summary(&orfs), [(2, 16, 2, "MKKK"), (19, 33, -1, "MPPP")]);
+        assert!(!orfs[1].feature.location.forward);
+        assert_eq!(orfs[0].length, 4);
+    }
+
+    #[test]
+    fn finds_orfs_across_the_origin() {
+        let sequence = "AAAAAATAATTTTTTATGAAA";
+        let code = find_code(BACTERIAL).unwrap();
+        assert_eq!(summary(&find_orfs(sequence, true, code, 3, false)), [(16, 9, 1, "MKKK")]);
+        assert!(find_orfs(sequence, false, code, 3, false).is_empty());
+    }
+
+    #[test]
+    fn flags_internal_stops_and_missing_starts() {
+        let translation = translate_feature("ATGAAATAAAAATAA", &cds(1, 15, true, None));
+        assert_eq!(translation.protein, "MK*K");
+        assert!(translation.internal_stop && !translation.missing_start && !translation.missing_stop);
+
+        let translation = translate_feature("CCCAAATAA", &cds(1, 9, true, None));
+        assert_eq!(translation.protein, "PK");
+        assert!(translation.missing_start && !translation.internal_stop);
+
+        // Reverse strand, with no stop codon
+        let translation = translate_feature("TTTCAT", &cds(1, 6, false, None));
+        assert_eq!(translation.protein, "MK");
+        assert!(translation.missing_stop && !translation.missing_start);
+    }
+
+    #[test]
+    fn translates_in_the_reported_table() {
+        let mycoplasma = Translation { table: 4, ..Default::default() };
+        let translation = translate_feature("ATGTGAAAATAA", &cds(1, 12, true, Some(mycoplasma)));
+        assert_eq!((translation.protein.as_str(), translation.table, translation.internal_stop), ("MWK", 4, false));
+
+        let translation = translate_feature("ATGTGAAAATAA", &cds(1, 12, true, None));
+        assert_eq!((translation.protein.as_str(), translation.table, translation.internal_stop), ("M*K", 11, true));
+    }
+
+    #[test]
+    fn checks_the_reported_translation() {
+        let reported = |protein: &str| Translation { reported: Some(protein.to_string()), ..Default::default() };
+        let translation = translate_feature("ATGAAATAA", &cds(1, 9, true, Some(reported("MKK"))));
+        assert_eq!(translation.matches_reported, Some(false));
+        let translation = translate_feature("ATGAAATAA", &cds(1, 9, true, Some(reported("MK*"))));
+        assert_eq!(translation.matches_reported, Some(true));
+        assert_eq!(translate_feature("ATGAAATAA", &cds(1, 9, true, None)).matches_reported, None);
+    }
+}
@@ -0,0 +1,355 @@
+//! Primer thermodynamics, after SantaLucia (1998) unified nearest-neighbor parameters: melting
+//! temperature, hairpins and self-dimers.
+
+use crate::ontology::find_canonical;
+use crate::types::{Biobrick, Location, MetaFeature, Primer, PrimerSite};
+
+use super::motif::{find_sites, parse_motif};
+use super::{complement, location, reverse_complement, slice};
+
+/// Monovalent cation and primer concentrations, in mol/L.
+const SODIUM: f64 = 0.05;
+const PRIMER: f64 = 250e-9;
+const GAS_CONSTANT: f64 = 1.987;
+const KELVIN: f64 = 273.15;
+
+/// Structures more stable than these, in kcal/mol at 37 °C, are reported.
+pub const HAIRPIN_LIMIT: f64 = -3.0;
+pub const SELF_DIMER_LIMIT: f64 = -6.0;
+/// Primers longer than this are costly and more error-prone to synthesize.
+pub const MAX_PRIMER_LENGTH: usize = 60;
+/// Primer 3' bases looked for elsewhere in the template, with at most one mismatch.
+const MISPRIMING_END: usize = 12;
+/// Sequencing reads start this far after the primer 3' end.
+const READ_OFFSET: std::ops::RangeInclusive<i64> = 30..=60;
+
+/// Enthalpy (kcal/mol) and entropy (cal/K/mol) of the stack 5'-xy-3' on its complement.
+fn stack(x: u8, y: u8) -> Option<(f64, f64)> {
+    let pair = match (x, y) {
+        (b'A', b'A') | (b'T', b'T') => (-7.9, -22.2),
+        (b'A', b'T') => (-7.2, -20.4),
+        (b'T', b'A') => (-7.2, -21.3),
+        (b'C', b'A') | (b'T', b'G') => (-8.5, -22.7),
+        (b'G', b'T') | (b'A', b'C') => (-8.4, -22.4),
+        (b'C', b'T') | (b'A', b'G') => (-7.8, -21.0),
+        (b'G', b'A') | (b'T', b'C') => (-8.2, -22.2),
+        (b'C', b'G') => (-10.6, -27.2),
+        (b'G', b'C') => (-9.8, -24.4),
+        (b'G', b'G') | (b'C', b'C') => (-8.0, -19.9),
+        _ => return None,
+    };
+    Some(pair)
+}
+
+fn initiation(base: u8) -> (f64, f64) {
+    match base {
+        b'G' | b'C' => (0.1, -2.8),
+        _ => (2.3, 4.1),
+    }
+}
+
+fn free_energy((enthalpy, entropy): (f64, f64)) -> f64 {
+    enthalpy - (KELVIN + 37.0) * entropy / 1000.0
+}
+
+/// Melting temperature of `sequence` on its perfect complement, in °C. Ambiguous bases are
+/// skipped.
+pub fn melting_temperature(sequence: &str) -> f64 {
+    let bases = sequence.to_uppercase().into_bytes();
+    if bases.len() < 2 {
+        return 0.0;
+    }
+    let (mut enthalpy, mut entropy) = (0.0, 0.0);
+    for end in [bases[0], bases[bases.len() - 1]] {
+        let (h, s) = initiation(end);
+        enthalpy += h;
+        entropy += s;
+    }
+    for pair in bases.windows(2) {
+        if let Some((h, s)) = stack(pair[0], pair[1]) {
+            enthalpy += h;
+            entropy += s;
+        }
+    }
+    entropy += 0.368 * (bases.len() - 1) as f64 * SODIUM.ln();
+    enthalpy * 1000.0 / (entropy + GAS_CONSTANT * (PRIMER / 4.0).ln()) - KELVIN
+}
+
+fn pairs(x: u8, y: u8) -> bool {
+    matches!(x, b'A' | b'C' | b'G' | b'T') && complement(x) == y
+}
+
+/// Free energy of the stacks of `length` paired bases from `start`, on their complement.
+fn helix(bases: &[u8], start: usize, length: usize) -> f64 {
+    (0..length.saturating_sub(1))
+        .filter_map(|k| stack(bases[start + k], bases[start + k + 1]))
+        .map(free_energy)
+        .sum()
+}
+
+/// Loop initiation free energy of a hairpin closing `length` unpaired bases.
+fn hairpin_loop(length: usize) -> f64 {
+    match length {
+        0..=2 => f64::INFINITY,
+        3 | 4 => 3.5,
+        5 => 3.3,
+        6 => 4.0,
+        7 => 4.2,
+        8 => 4.3,
+        9 => 4.5,
+        10 => 4.6,
+        _ => 4.6 + 1.75 * GAS_CONSTANT * (KELVIN + 37.0) / 1000.0 * (length as f64 / 10.0).ln(),
+    }
+}
+
+/// Free energy of the most stable hairpin of `sequence`, in kcal/mol; 0 when none forms.
+pub fn hairpin(sequence: &str) -> f64 {
+    let bases = sequence.to_uppercase().into_bytes();
+    let mut best: f64 = 0.0;
+    for i in 0..bases.len() {
+        for j in i + 4..bases.len() {
+            // Innermost pair of a stem, extended outwards
+            if !pairs(bases[i], bases[j]) || (j - i > 5 && pairs(bases[i + 1], bases[j - 1])) {
+                continue;
+            }
+            let mut length = 1;
+            while length <= i && j + length < bases.len() && pairs(bases[i - length], bases[j + length]) {
+                length += 1;
+            }
+            if length >= 3 {
+                let stem = helix(&bases, i + 1 - length, length);
+                best = best.min(stem + hairpin_loop(j - i - 1));
+            }
+        }
+    }
+    best
+}
+
+/// Free energy of the most stable duplex `sequence` forms with a copy of itself, in kcal/mol;
+/// 0 when none forms.
+pub fn self_dimer(sequence: &str) -> f64 {
+    let bases = sequence.to_uppercase().into_bytes();
+    let n = bases.len();
+    let mut best: f64 = 0.0;
+    // The copy runs antiparallel: base i faces base `diagonal - i`
+    for diagonal in 0..(2 * n).saturating_sub(1) {
+        let mut run = 0;
+        for i in diagonal.saturating_sub(n - 1)..n.min(diagonal + 1) {
+            if pairs(bases[i], bases[diagonal - i]) {
+                run += 1;
+            } else {
+                run = 0;
+            }
+            if run >= 3 {
+                best = best.min(helix(&bases, i + 1 - run, run) + 1.96);
+            }
+        }
+    }
+    best
+}
+
+/// Length of the shortest prefix of `template` melting at `target` °C or above, between `min`
+/// and `max` bases, and never longer than the template.
+pub fn binding_length(template: &str, target: f64, min: usize, max: usize) -> usize {
+    let max = max.min(template.len());
+    (min.min(max)..=max)
+        .find(|&length| melting_temperature(&template[..length]) >= target)
+        .unwrap_or(max)
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// Primer `sequence` whose last `binding` bases anneal to the template, with its properties and
+/// warnings about them.
+pub fn primer(name: &str, sequence: &str, binding: usize, target: f64) -> Primer {
+    let sequence = sequence.to_uppercase();
+    let annealing = &sequence[sequence.len() - binding..];
+    let tm = melting_temperature(annealing);
+    let gc = annealing.bytes().filter(|b| matches!(b, b'G' | b'C')).count() as f64 / binding.max(1) as f64;
+    let hairpin = hairpin(&sequence);
+    let self_dimer = self_dimer(&sequence);
+
+    let mut warnings = Vec::new();
+    if tm < target - 2.0 {
+        warnings.push(format!("Binding region melts at {:.1} °C, below the {:.1} °C target", tm, target));
+    }
+    if hairpin < HAIRPIN_LIMIT {
+        warnings.push(format!("Hairpin of {:.1} kcal/mol", hairpin));
+    }
+    if self_dimer < SELF_DIMER_LIMIT {
+        warnings.push(format!("Self-dimer of {:.1} kcal/mol", self_dimer));
+    }
+    if sequence.len() > MAX_PRIMER_LENGTH {
+        warnings.push(format!("{} nt long, over {} nt", sequence.len(), MAX_PRIMER_LENGTH));
+    }
+    if !annealing.ends_with(['G', 'C']) {
+        warnings.push("No G or C at the 3' end".to_string());
+    }
+
+    Primer {
+        name: name.to_string(),
+        length: sequence.len(),
+        binding,
+        tm: round(tm),
+        gc: (gc * 100.0).round() / 100.0,
+        hairpin: round(hairpin),
+        self_dimer: round(self_dimer),
+        warnings,
+        sequence,
+    }
+}
+
+/// Reverse primer annealing to the last `binding` bases of `template`, behind `tail`.
+pub fn reverse_primer(name: &str, template: &str, binding: usize, tail: &str, target: f64) -> Primer {
+    let annealing = &template[template.len() - binding..];
+    primer(name, &reverse_complement(&format!("{}{}", annealing, tail)), binding, target)
+}
+
+/// Sites other than `site` where the 3' end of `binding` anneals.
+pub fn misprimes(sequence: &str, circular: bool, binding: &str, site: &Location) -> Vec<Location> {
+    let end = &binding[binding.len().saturating_sub(MISPRIMING_END)..];
+    let Ok(variants) = parse_motif(end) else {
+        return vec![];
+    };
+    let len = sequence.len() as i32;
+    let site_length = (site.end - site.start).rem_euclid(len) + 1;
+    find_sites(sequence, circular, &variants, 1)
+        .into_iter()
+        .map(|s| s.location)
+        .filter(|l| l.forward != site.forward || (l.start - site.start).rem_euclid(len) + end.len() as i32 > site_length)
+        .collect()
+}
+
+fn penalty(primer: &Primer, target: f64) -> f64 {
+    (primer.tm - target).abs() + 3.0 * primer.warnings.len() as f64
+}
+
+/// Best primer among `candidates`, given as 0-based top strand start and length of the bases it
+/// binds. The few best by their own properties are then checked for mispriming.
+fn choose(
+    biobrick: &Biobrick,
+    name: &str,
+    tail: &str,
+    target: f64,
+    forward: bool,
+    candidates: impl Iterator<Item = (i64, usize)>,
+) -> Option<PrimerSite> {
+    let sequence = biobrick.sequence.to_uppercase();
+    let len = sequence.len() as i64;
+    let mut primers = candidates
+        .map(|(start, length)| {
+            let top = slice(&sequence, start, length);
+            let binding = if forward { top } else { reverse_complement(&top) };
+            let primer = primer(name, &format!("{}{}", tail, binding), length, target);
+            (location(start, length, forward, len), primer)
+        })
+        .collect::<Vec<_>>();
+    primers.sort_by(|a, b| penalty(&a.1, target).total_cmp(&penalty(&b.1, target)));
+
+    let (location, mut primer, misprimes) = primers
+        .into_iter()
+        .take(5)
+        .map(|(location, primer)| {
+            let binding = &primer.sequence[primer.length - primer.binding..];
+            let misprimes = misprimes(&sequence, biobrick.metadata.circular, binding, &location);
+            (location, primer, misprimes)
+        })
+        .min_by(|a, b| {
+            let score = |c: &(Location, Primer, Vec<Location>)| penalty(&c.1, target) + 10.0 * c.2.len() as f64;
+            score(a).total_cmp(&score(b))
+        })?;
+    if !misprimes.is_empty() {
+        primer.warnings.push(format!("3' end also anneals at {} other site(s)", misprimes.len()));
+    }
+    let binding_site = find_canonical("primer-binding-site").expect("primer-binding-site type");
+    Some(PrimerSite {
+        feature: MetaFeature {
+            id: format!("{}_{}", name, location.start),
+            name: name.to_string(),
+            r#type: binding_site.into(),
+            location,
+            translation: None,
+        },
+        primer,
+        misprimes,
+    })
+}
+
+/// Number of bases from `start` to `end`, 1-based and inclusive, across the origin if needed.
+pub fn region_length(biobrick: &Biobrick, start: i32, end: i32) -> usize {
+    ((end - start).rem_euclid(biobrick.sequence.len() as i32) + 1) as usize
+}
+
+/// Forward and reverse primers amplifying `start..=end`, behind their 5' tails.
+pub fn amplification_primers(
+    biobrick: &Biobrick,
+    start: i32,
+    end: i32,
+    tails: (&str, &str),
+    target: f64,
+) -> Vec<PrimerSite> {
+    let id = &biobrick.metadata.id;
+    let max = 30.min(region_length(biobrick, start, end) / 2);
+    let (start, end) = (start as i64 - 1, end as i64);
+    let forward = choose(biobrick, &format!("{}_F", id), tails.0, target, true, (18..=max).map(|l| (start, l)));
+    let reverse = choose(biobrick, &format!("{}_R", id), tails.1, target, false, (18..=max).map(|l| (end - l as i64, l)));
+    forward.into_iter().chain(reverse).collect()
+}
+
+/// Forward primers every `spacing` bases of `start..=end`, each placed for its read to start
+/// around the tile it covers.
+pub fn sequencing_primers(biobrick: &Biobrick, start: i32, end: i32, spacing: usize, target: f64) -> Vec<PrimerSite> {
+    let len = biobrick.sequence.len() as i64;
+    let circular = biobrick.metadata.circular;
+    let mut sites = Vec::new();
+    for (i, tile) in (0..region_length(biobrick, start, end)).step_by(spacing).enumerate() {
+        let read = start as i64 - 1 + tile as i64;
+        let candidates = READ_OFFSET
+            .flat_map(|offset| (18..=24).map(move |length| (read - offset - length as i64 + 1, length)))
+            .filter(|(s, length)| circular || (*s >= 0 && s + *length as i64 <= len))
+            .collect::<Vec<_>>();
+        // Too close to the start of a linear part: read from its first bases
+        let candidates = if candidates.is_empty() {
+            (0..=10).flat_map(|s| (18..=24).map(move |length| (s, length))).collect()
+        } else {
+            candidates
+        };
+        let name = format!("{}_seq{}", biobrick.metadata.id, i + 1);
+        sites.extend(choose(biobrick, &name, "", target, true, candidates.into_iter()));
+    }
+    sites
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_nearest_neighbor_tm() {
+        // M13 fwd and rev, at 50 mM Na+ and 250 nM primer
+        assert!((melting_temperature("GTAAAACGACGGCCAGT") - 51.67).abs() < 0.01);
+        assert!((melting_temperature("CAGGAAACAGCTATGAC") - 46.09).abs() < 0.01);
+        assert_eq!(melting_temperature("gtaaaacgacggccagt"), melting_temperature("GTAAAACGACGGCCAGT"));
+        assert_eq!(melting_temperature("A"), 0.0);
+    }
+
+    #[test]
+    fn flags_hairpins_and_self_dimers() {
+        let clean = primer("clean", "GTAAAACGACGGCCAGT", 17, 50.0);
+        assert_eq!(clean.warnings, ["No G or C at the 3' end"]);
+        assert!(clean.hairpin >= HAIRPIN_LIMIT && clean.self_dimer >= SELF_DIMER_LIMIT);
+
+        // Eight base stem around a four base loop
+        let folded = primer("folded", "TTCCGAGGCGAAAACGCCTCGGTTC", 25, 50.0);
+        assert!(folded.hairpin < HAIRPIN_LIMIT);
+        assert!(folded.warnings.iter().any(|w| w.starts_with("Hairpin")));
+
+        // Palindromic 3' end, too short to fold back on itself
+        let dimer = primer("dimer", "TTTTTTGCGGCCGC", 14, 40.0);
+        assert_eq!(dimer.hairpin, 0.0);
+        assert!(dimer.self_dimer < SELF_DIMER_LIMIT);
+        assert!(dimer.warnings.iter().any(|w| w.starts_with("Self-dimer")));
+    }
+}
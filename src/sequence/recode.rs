@@ -0,0 +1,354 @@
+//! Synonymous recoding of coding sequences: codon optimization for an expression host, and
+//! domestication against the forbidden sites of an assembly standard.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::types::{Biobrick, CodonChange, RecodeResponse};
+
+use super::codon_usage::{CodonUsage, RARE_CODON};
+use super::compatibility::{check_standard, Standard};
+use super::complement;
+use super::genetic_code::{codon, codon_index, find_code, GeneticCode, BACTERIAL};
+use super::orfs::translate_features;
+
+/// Domestication gives up after this many sites, fixed or not.
+const MAX_SITES: usize = 200;
+
+/// Coding sequence feature, as the top strand positions of its codons read on its strand.
+pub struct Cds {
+    pub feature: String,
+    pub forward: bool,
+    pub code: &'static GeneticCode,
+    pub codons: Vec<[usize; 3]>,
+    mutable: Vec<usize>, // codons that can change: not the start codon, nor any shared with another CDS
+}
+
+impl Cds {
+    pub fn read(&self, bases: &[u8], k: usize) -> [u8; 3] {
+        self.codons[k].map(|p| if self.forward { bases[p] } else { complement(bases[p]) })
+    }
+
+    fn write(&self, bases: &mut [u8], k: usize, codon: &[u8]) {
+        for (&p, &base) in self.codons[k].iter().zip(codon) {
+            bases[p] = if self.forward { base } else { complement(base) };
+        }
+    }
+}
+
+/// Coding sequence features with a known reading frame, each once.
+pub fn coding_sequences(biobrick: &Biobrick, warnings: &mut Vec<String>) -> Vec<Cds> {
+    let len = biobrick.sequence.len() as i32;
+    let mut seen = HashSet::new();
+    let mut cdss = Vec::new();
+    for feature in biobrick.features.iter().filter(|f| f.r#type.canonical == "coding-sequence") {
+        if feature.location.partial_start {
+            warnings.push(format!("{} starts outside the part, its reading frame is unknown", feature.id));
+            continue;
+        }
+        let mut positions = feature
+            .location
+            .ranges()
+            .into_iter()
+            .flat_map(|s| (0..(s.end - s.start).rem_euclid(len) + 1).map(move |k| (s.start - 1 + k).rem_euclid(len) as usize))
+            .collect::<Vec<_>>();
+        if !feature.location.forward {
+            positions.reverse();
+        }
+        if positions.len() < 6 || !seen.insert(positions.clone()) {
+            continue;
+        }
+        let table = feature.translation.as_ref().map_or(BACTERIAL, |t| t.table);
+        cdss.push(Cds {
+            feature: feature.id.clone(),
+            forward: feature.location.forward,
+            code: find_code(table).or_else(|| find_code(BACTERIAL)).expect("bacterial genetic code"),
+            codons: positions.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect(),
+            mutable: vec![],
+        });
+    }
+    cdss
+}
+
+/// Codon usage of the part's own coding sequences, to rank codons when no host is given.
+fn part_usage(bases: &[u8], cdss: &[Cds]) -> CodonUsage {
+    let mut frequencies = [0.0; 64];
+    for cds in cdss {
+        for k in 0..cds.codons.len() {
+            if let Some(index) = codon_index(&cds.read(bases, k)) {
+                frequencies[index] += 1.0;
+            }
+        }
+    }
+    CodonUsage {
+        host: "part",
+        organism: "part",
+        table: BACTERIAL,
+        frequencies,
+        trna: &[],
+    }
+}
+
+/// Spreads the codons of each amino acid over the host's synonyms in proportion to its usage,
+/// leaving out rare codons.
+fn optimize(bases: &mut [u8], cdss: &[Cds], host: &CodonUsage, reasons: &mut HashMap<(usize, usize), String>) {
+    let mut used = [0.0; 64];
+    let mut counts: HashMap<u8, f64> = HashMap::new();
+    for (c, cds) in cdss.iter().enumerate() {
+        for &k in &cds.mutable {
+            let Some(index) = codon_index(&cds.read(bases, k)) else {
+                continue;
+            };
+            let choices = host
+                .synonyms(cds.code, index)
+                .into_iter()
+                .filter(|&(_, share)| share >= RARE_CODON)
+                .collect::<Vec<_>>();
+            let count = counts.entry(cds.code.amino_acids[index]).or_insert(0.0);
+            *count += 1.0;
+            // Codon furthest behind its share so far, the most used first on ties
+            let Some(&(best, _)) = choices
+                .iter()
+                .max_by(|a, b| (a.1 * *count - used[a.0]).total_cmp(&(b.1 * *count - used[b.0])).then(b.0.cmp(&a.0)))
+            else {
+                continue;
+            };
+            used[best] += 1.0;
+            if best != index {
+                cds.write(bases, k, codon(best).as_bytes());
+                reasons.insert((c, k), "host".to_string());
+            }
+        }
+    }
+}
+
+fn with_sequence(biobrick: &Biobrick, bases: &[u8]) -> Biobrick {
+    let mut recoded = biobrick.clone();
+    recoded.sequence = String::from_utf8_lossy(bases).to_string();
+    recoded
+}
+
+/// Removes the forbidden sites of `standard` one at a time, with the synonymous change that
+/// removes the site without adding another one and uses the most common codon.
+fn domesticate(
+    biobrick: &Biobrick,
+    bases: &mut [u8],
+    cdss: &[Cds],
+    ranking: &CodonUsage,
+    standard: &Standard,
+    reasons: &mut HashMap<(usize, usize), String>,
+    warnings: &mut Vec<String>,
+) {
+    let len = bases.len() as i32;
+    let mut given_up = HashSet::new();
+    for _ in 0..MAX_SITES {
+        let sites = check_standard(&with_sequence(biobrick, bases), standard).sites;
+        let Some(site) = sites.iter().find(|s| !given_up.contains(&(s.enzyme.clone(), s.location.start))) else {
+            return;
+        };
+        let covered = (0..(site.location.end - site.location.start).rem_euclid(len) + 1)
+            .map(|k| (site.location.start - 1 + k).rem_euclid(len) as usize)
+            .collect::<HashSet<_>>();
+
+        let mut best: Option<(f64, usize, usize, usize)> = None;
+        let mut reachable = false;
+        for (c, cds) in cdss.iter().enumerate() {
+            for &k in cds.mutable.iter().filter(|&&k| cds.codons[k].iter().any(|p| covered.contains(p))) {
+                reachable = true;
+                let Some(index) = codon_index(&cds.read(bases, k)) else {
+                    continue;
+                };
+                for (alternative, share) in ranking.synonyms(cds.code, index) {
+                    if alternative == index || best.is_some_and(|b| b.0 >= share) {
+                        continue;
+                    }
+                    let mut candidate = bases.to_vec();
+                    cds.write(&mut candidate, k, codon(alternative).as_bytes());
+                    if check_standard(&with_sequence(biobrick, &candidate), standard).sites.len() < sites.len() {
+                        best = Some((share, c, k, alternative));
+                    }
+                }
+            }
+        }
+        match best {
+            Some((_, c, k, alternative)) => {
+                cdss[c].write(bases, k, codon(alternative).as_bytes());
+                reasons.insert((c, k), format!("{} site", site.enzyme));
+            }
+            None => {
+                given_up.insert((site.enzyme.clone(), site.location.start));
+                let why = if reachable { "cannot be removed by a synonymous change" } else { "is outside coding sequences" };
+                warnings.push(format!("{} site at {}..{} {}", site.enzyme, site.location.start, site.location.end, why));
+            }
+        }
+    }
+    warnings.push(format!("Domestication stopped after {} sites", MAX_SITES));
+}
+
+/// `biobrick` with its coding sequences recoded for `host` and free of the sites of `standard`,
+/// and the codons changed. Proteins, and all bases outside coding sequences, stay the same.
+pub fn recode(biobrick: &Biobrick, host: Option<&CodonUsage>, standard: Option<&Standard>) -> Result<RecodeResponse, String> {
+    let mut warnings = Vec::new();
+    let mut cdss = coding_sequences(biobrick, &mut warnings);
+    if cdss.is_empty() {
+        return Err(format!("{} has no coding sequence feature to recode", biobrick.metadata.id));
+    }
+    let original = biobrick.sequence.to_uppercase().into_bytes();
+    let mut bases = original.clone();
+
+    // Bases read by several coding sequences, in different frames or strands
+    let mut readers: HashMap<usize, usize> = HashMap::new();
+    for cds in &cdss {
+        for &p in cds.codons.iter().flatten() {
+            *readers.entry(p).or_default() += 1;
+        }
+    }
+    for cds in &mut cdss {
+        cds.mutable = (1..cds.codons.len()).filter(|&k| cds.codons[k].iter().all(|p| readers[p] == 1)).collect();
+    }
+
+    let mut reasons = HashMap::new();
+    if let Some(host) = host {
+        let host_code = find_code(host.table).expect("host genetic code");
+        for cds in cdss.iter().filter(|cds| cds.code.amino_acids != host_code.amino_acids) {
+            warnings.push(format!("{} uses genetic code {}, which {} does not", cds.feature, cds.code.id, host.organism));
+        }
+        optimize(&mut bases, &cdss, host, &mut reasons);
+    }
+    let report = standard.map(|standard| {
+        let own;
+        let ranking = match host {
+            Some(host) => host,
+            None => {
+                own = part_usage(&original, &cdss);
+                &own
+            }
+        };
+        domesticate(biobrick, &mut bases, &cdss, ranking, standard, &mut reasons, &mut warnings);
+        check_standard(&with_sequence(biobrick, &bases), standard)
+    });
+
+    let mut changes = Vec::new();
+    for (c, cds) in cdss.iter().enumerate() {
+        for k in 0..cds.codons.len() {
+            let (from, to) = (cds.read(&original, k), cds.read(&bases, k));
+            if from == to {
+                continue;
+            }
+            let first = if cds.forward { cds.codons[k][0] } else { cds.codons[k][2] };
+            changes.push(CodonChange {
+                feature: cds.feature.clone(),
+                codon: k + 1,
+                position: first as i32 + 1,
+                amino_acid: (cds.code.translate_codon(&from) as char).to_string(),
+                from: String::from_utf8_lossy(&from).to_string(),
+                to: String::from_utf8_lossy(&to).to_string(),
+                reason: reasons.get(&(c, k)).cloned().unwrap_or_default(),
+            });
+        }
+    }
+    changes.sort_by_key(|c| c.position);
+
+    // Keep the case of unchanged bases
+    let sequence = biobrick
+        .sequence
+        .bytes()
+        .zip(original.iter().zip(&bases))
+        .map(|(raw, (before, after))| if before == after { raw as char } else { *after as char })
+        .collect();
+    let mut recoded = biobrick.clone();
+    recoded.sequence = sequence;
+    translate_features(&mut recoded);
+    Ok(RecodeResponse {
+        host: host.map(|h| h.host.to_string()),
+        standard: report,
+        changes,
+        warnings,
+        biobrick: recoded,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ontology::find_canonical;
+    use crate::sequence::codon_usage::find_host;
+    use crate::sequence::compatibility::find_standard;
+    use crate::sequence::{feature_sequence, reverse_complement, translate};
+    use crate::types::{Location, MetaBiobrick, MetaFeature};
+
+    fn cds(id: &str, start: i32, end: i32, forward: bool) -> MetaFeature {
+        MetaFeature {
+            id: id.to_string(),
+            name: id.to_string(),
+            r#type: find_canonical("coding-sequence").unwrap().into(),
+            location: Location { start, end, forward, segments: vec![], partial_start: false, partial_end: false },
+            translation: None,
+        }
+    }
+
+    fn part(sequence: &str, features: Vec<MetaFeature>) -> Biobrick {
+        Biobrick {
+            metadata: MetaBiobrick {
+                id: "BBa_TEST".to_string(),
+                name: "test".to_string(),
+                description: String::new(),
+                creation: String::new(),
+                size: sequence.len() as i32,
+                circular: false,
+                r#type: find_canonical("coding-sequence").unwrap().into(),
+                authors: vec![],
+                providers: vec![],
+            },
+            sequence: sequence.to_string(),
+            features,
+        }
+    }
+
+    fn proteins(biobrick: &Biobrick) -> Vec<String> {
+        biobrick.features.iter().map(|f| translate(&feature_sequence(&biobrick.sequence, &f.location))).collect()
+    }
+
+    #[test]
+    fn removes_internal_site_keeping_protein() {
+        // GAA TTC, Glu Phe, holds an EcoRI site
+        let part = part("ATGAAAGAATTCAAACTGGGTTAA", vec![cds("cds", 1, 24, true)]);
+        let rfc10 = find_standard("RFC10").unwrap();
+        assert!(!check_standard(&part, rfc10).compatible);
+
+        let recoded = recode(&part, None, Some(rfc10)).unwrap();
+        assert!(recoded.standard.unwrap().compatible);
+        assert!(!recoded.biobrick.sequence.contains("GAATTC"));
+        assert_eq!(proteins(&recoded.biobrick), proteins(&part));
+        assert_eq!(recoded.changes.len(), 1);
+        assert_eq!((recoded.changes[0].codon, recoded.changes[0].reason.as_str()), (3, "EcoRI site"));
+    }
+
+    #[test]
+    fn leaves_shared_codons_alone() {
+        // Two coding sequences in the same frame, sharing bases 16 to 30; CTA is rare in E. coli
+        let sequence = format!("ATG{}TAA", "CTA".repeat(14));
+        let part = part(&sequence, vec![cds("a", 1, 30, true), cds("b", 16, 48, true)]);
+
+        let recoded = recode(&part, find_host("e-coli"), None).unwrap();
+        assert!(!recoded.changes.is_empty());
+        assert!(recoded.changes.iter().all(|c| !(16..=30).contains(&c.position) && c.reason == "host"));
+        assert_eq!(recoded.biobrick.sequence[15..30], sequence[15..30]);
+        assert_eq!(proteins(&recoded.biobrick), proteins(&part));
+    }
+
+    #[test]
+    fn recodes_reverse_strand() {
+        let coding = "ATGAAAGAATTCAAACTGGGTTAA";
+        let sequence = format!("CCCC{}CCCC", reverse_complement(coding));
+        let part = part(&sequence, vec![cds("cds", 5, 28, false)]);
+
+        let recoded = recode(&part, None, find_standard("RFC10")).unwrap();
+        let recoded_sequence = &recoded.biobrick.sequence;
+        assert!(!recoded_sequence.contains("GAATTC"));
+        assert_eq!((&recoded_sequence[..4], &recoded_sequence[28..]), ("CCCC", "CCCC"));
+        assert_eq!(proteins(&recoded.biobrick), ["MKEFKLG*"]);
+        // Codons read on the reverse strand, placed by their first base on the forward strand
+        let change = &recoded.changes[0];
+        assert_eq!((change.codon, change.position, change.from.as_str()), (3, 20, "GAA"));
+    }
+}
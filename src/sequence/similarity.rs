@@ -0,0 +1,393 @@
+//! Sequence similarity search: (w, k)-minimizer seeds from the `parts_kmers` index, then banded
+//! Smith-Waterman extension around the densest diagonal of each candidate part.
+
+use std::collections::HashMap;
+
+use crate::cache::SqliteCache;
+use crate::types::{Location, SequenceHit};
+
+use super::reverse_complement;
+
+pub const K: usize = 15;
+pub const W: usize = 10;
+
+/// Half-width of the diagonal band explored around the seeds, i.e. the largest indel drift.
+const BAND: usize = 32;
+/// Parts aligned per query, picked by seed count.
+const MAX_CANDIDATES: usize = 100;
+
+const MATCH: i32 = 2;
+const MISMATCH: i32 = -3;
+const GAP: i32 = -5;
+
+/// Minimizer hashes and their start positions. Circular sequences also get the k-mers
+/// spanning the origin; positions are always within the sequence.
+pub fn minimizers(sequence: &str, circular: bool) -> Vec<(u64, usize)> {
+    let bases = sequence.as_bytes();
+    let len = bases.len();
+    if len < K {
+        return vec![];
+    }
+
+    let extended = if circular {
+        bases.iter().chain(bases.iter().take(K + W - 2)).copied().collect::<Vec<_>>()
+    } else {
+        bases.to_vec()
+    };
+
+    let hashes = kmer_hashes(&extended);
+    let mut found: Vec<(u64, usize)> = Vec::new();
+    for window in 0..hashes.len().saturating_sub(W - 1).max(1) {
+        let best = hashes[window..(window + W).min(hashes.len())]
+            .iter()
+            .enumerate()
+            .filter_map(|(i, h)| h.map(|h| (h, window + i)))
+            .min_by_key(|(h, _)| *h);
+        if let Some((hash, position)) = best {
+            if position < len && found.last() != Some(&(hash, position)) {
+                found.push((hash, position));
+            }
+        }
+    }
+    found.sort_unstable_by_key(|(_, p)| *p);
+    found.dedup();
+    found
+}
+
+/// Hash of every k-mer, `None` when it contains a base other than A, C, G or T.
+fn kmer_hashes(bases: &[u8]) -> Vec<Option<u64>> {
+    let mask = (1_u64 << (2 * K)) - 1;
+    let mut code = 0_u64;
+    let mut valid = 0;
+    let mut hashes = Vec::with_capacity(bases.len());
+    for (i, base) in bases.iter().enumerate() {
+        let bits = match base.to_ascii_uppercase() {
+            b'A' => Some(0),
+            b'C' => Some(1),
+            b'G' => Some(2),
+            b'T' => Some(3),
+            _ => None,
+        };
+        match bits {
+            Some(bits) => {
+                code = ((code << 2) | bits) & mask;
+                valid += 1;
+            }
+            None => valid = 0,
+        }
+        if i + 1 >= K {
+            hashes.push((valid >= K).then(|| mix(code)));
+        }
+    }
+    hashes
+}
+
+/// Invertible 64-bit mixer, so minimizers are not biased towards poly-A k-mers. Kept to 63
+/// bits to fit SQLite integers.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 31)).wrapping_mul(0x7fb5d329728ea185);
+    x = (x ^ (x >> 27)).wrapping_mul(0x81dadef4bc2dd44d);
+    (x ^ (x >> 33)) & (i64::MAX as u64)
+}
+
+/// Local alignment, in 0-based half-open coordinates.
+#[derive(Debug, Clone)]
+pub struct Alignment {
+    pub score: i32,
+    pub query_start: usize,
+    pub query_end: usize,
+    pub target_start: usize,
+    pub target_end: usize,
+    pub matches: usize,
+    pub columns: usize,
+}
+
+/// Region of a part aligned with a query, see `SequenceHit`.
+#[derive(Debug, Clone)]
+pub struct LocalMatch {
+    pub identity: f64,
+    pub coverage: f64,
+    pub score: i32,
+    pub query_start: usize,
+    pub query_end: usize,
+    pub location: Location,
+}
+
+/// Local alignment of `query` against `target`, restricted to cells within `BAND` of the
+/// diagonal `target index = query index + diagonal`. Works on any byte alphabet.
+pub fn banded_alignment(query: &[u8], target: &[u8], diagonal: i64) -> Option<Alignment> {
+    let width = 2 * BAND + 1;
+    let rows = query.len() + 1;
+    let mut scores = vec![0_i32; rows * width];
+    // 0: stop, 1: diagonal, 2: up (gap in target), 3: left (gap in query)
+    let mut moves = vec![0_u8; rows * width];
+    let column = |i: usize, j: usize| -> Option<usize> {
+        let offset = j as i64 - (i as i64 + diagonal - BAND as i64);
+        (0..width as i64).contains(&offset).then_some(offset as usize)
+    };
+
+    let mut best = (0, 0, 0);
+    for i in 1..rows {
+        let low = (i as i64 + diagonal - BAND as i64).max(1);
+        let high = (i as i64 + diagonal + BAND as i64).min(target.len() as i64);
+        for j in low.max(0) as usize..=high.max(0) as usize {
+            let Some(c) = column(i, j) else {
+                continue;
+            };
+            let score_at = |i: usize, j: usize| column(i, j).map(|c| scores[i * width + c]).unwrap_or(0);
+            let substitution = if query[i - 1].eq_ignore_ascii_case(&target[j - 1]) { MATCH } else { MISMATCH };
+            let options = [
+                (score_at(i - 1, j - 1) + substitution, 1),
+                (score_at(i - 1, j) + GAP, 2),
+                (score_at(i, j - 1) + GAP, 3),
+            ];
+            let (score, step) = options.into_iter().max_by_key(|(s, _)| *s).unwrap_or((0, 0));
+            let (score, step) = if score > 0 { (score, step) } else { (0, 0) };
+            scores[i * width + c] = score;
+            moves[i * width + c] = step;
+            if score > best.0 {
+                best = (score, i, j);
+            }
+        }
+    }
+
+    let (score, mut i, mut j) = best;
+    if score == 0 {
+        return None;
+    }
+    let (query_end, target_end) = (i, j);
+    let (mut matches, mut columns) = (0, 0);
+    while let Some(c) = column(i, j).filter(|&c| moves[i * width + c] != 0) {
+        match moves[i * width + c] {
+            1 => {
+                if query[i - 1].eq_ignore_ascii_case(&target[j - 1]) {
+                    matches += 1;
+                }
+                i -= 1;
+                j -= 1;
+            }
+            2 => i -= 1,
+            _ => j -= 1,
+        }
+        columns += 1;
+    }
+
+    Some(Alignment {
+        score,
+        query_start: i,
+        query_end,
+        target_start: j,
+        target_end,
+        matches,
+        columns,
+    })
+}
+
+/// Diagonal with the most seeds within `BAND` of it, and that seed count.
+fn densest_diagonal(diagonals: Vec<i64>) -> (i64, usize) {
+    diagonal_clusters(diagonals)
+        .into_iter()
+        .fold((0, 0), |best, cluster| if cluster.1 > best.1 { cluster } else { best })
+}
+
+/// Groups of seed diagonals no more than `BAND` apart, as (median diagonal, seed count), so
+/// repeated copies of a query each get their own alignment.
+pub fn diagonal_clusters(mut diagonals: Vec<i64>) -> Vec<(i64, usize)> {
+    diagonals.sort_unstable();
+    let mut clusters = Vec::new();
+    let mut low = 0;
+    for high in 0..diagonals.len() {
+        if high + 1 == diagonals.len() || diagonals[high + 1] - diagonals[high] > BAND as i64 {
+            clusters.push((diagonals[(low + high) / 2], high - low + 1));
+            low = high + 1;
+        }
+    }
+    clusters
+}
+
+/// Aligns one strand of a query around a seed diagonal of `sequence`, in query and part
+/// coordinates. Circular parts are aligned against two copies so regions can span the origin.
+fn align_strand(strand: &str, forward: bool, sequence: &[u8], circular: bool, diagonal: i64) -> Option<LocalMatch> {
+    let len = sequence.len();
+    if len == 0 {
+        return None;
+    }
+    let (target, diagonal) = if circular {
+        let doubled = sequence.iter().chain(sequence.iter()).copied().collect::<Vec<_>>();
+        (doubled, diagonal.rem_euclid(len as i64))
+    } else {
+        (sequence.to_vec(), diagonal)
+    };
+    let alignment = banded_alignment(strand.as_bytes(), &target, diagonal)?;
+
+    let (query_start, query_end) = if forward {
+        (alignment.query_start + 1, alignment.query_end)
+    } else {
+        (strand.len() - alignment.query_end + 1, strand.len() - alignment.query_start)
+    };
+    Some(LocalMatch {
+        identity: alignment.matches as f64 / alignment.columns as f64,
+        coverage: (alignment.query_end - alignment.query_start) as f64 / strand.len() as f64,
+        score: alignment.score,
+        query_start,
+        query_end,
+        location: Location {
+            start: (alignment.target_start % len + 1) as i32,
+            end: ((alignment.target_end - 1) % len + 1) as i32,
+            forward,
+            segments: vec![],
+            partial_start: false,
+            partial_end: false,
+        },
+    })
+}
+
+/// In-memory minimizer index of one sequence, to find many queries in it.
+pub struct SequenceIndex<'a> {
+    sequence: &'a str,
+    circular: bool,
+    seeds: HashMap<u64, Vec<usize>>,
+}
+
+impl<'a> SequenceIndex<'a> {
+    pub fn new(sequence: &'a str, circular: bool) -> Self {
+        let mut seeds: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (hash, position) in minimizers(sequence, circular) {
+            seeds.entry(hash).or_default().push(position);
+        }
+        SequenceIndex { sequence, circular, seeds }
+    }
+
+    /// Every region aligned with `query` on either strand, one per seed cluster.
+    pub fn find(&self, query: &str) -> Vec<LocalMatch> {
+        let mut found: Vec<LocalMatch> = Vec::new();
+        for (forward, strand) in [(true, query.to_string()), (false, reverse_complement(query))] {
+            let mut diagonals = Vec::new();
+            for (hash, query_position) in minimizers(&strand, false) {
+                for target_position in self.seeds.get(&hash).into_iter().flatten() {
+                    diagonals.push(*target_position as i64 - query_position as i64);
+                }
+            }
+            if self.circular {
+                let len = self.sequence.len() as i64;
+                diagonals.iter_mut().for_each(|d| *d = d.rem_euclid(len));
+            }
+            for (diagonal, _) in diagonal_clusters(diagonals) {
+                let Some(hit) = align_strand(&strand, forward, self.sequence.as_bytes(), self.circular, diagonal) else {
+                    continue;
+                };
+                if !found.iter().any(|f| f.location.start == hit.location.start && f.location.forward == forward) {
+                    found.push(hit);
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Cached parts with a region similar to `query` on either strand, best first.
+pub fn sequence_search(cache: &SqliteCache, query: &str, n: usize, min_identity: f64) -> Result<Vec<SequenceHit>, rusqlite::Error> {
+    let reverse = reverse_complement(query);
+    let strands = [(true, query.to_string()), (false, reverse)];
+
+    // Seeds per (part, strand) as diagonals, with the query k-mer positions of each hash
+    let mut diagonals: HashMap<(String, bool), Vec<i64>> = HashMap::new();
+    for (forward, strand) in &strands {
+        let mut positions: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (hash, position) in minimizers(strand, false) {
+            positions.entry(hash).or_default().push(position);
+        }
+        let hashes = positions.keys().copied().collect::<Vec<_>>();
+        for (id, hash, target_position) in cache.kmer_hits(&hashes)? {
+            for query_position in &positions[&hash] {
+                diagonals
+                    .entry((id.clone(), *forward))
+                    .or_default()
+                    .push(target_position as i64 - *query_position as i64);
+            }
+        }
+    }
+
+    let mut candidates = diagonals
+        .into_iter()
+        .map(|(key, diagonals)| (key, densest_diagonal(diagonals)))
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| b.1 .1.cmp(&a.1 .1).then_with(|| a.0.cmp(&b.0)));
+    candidates.truncate(MAX_CANDIDATES);
+
+    let mut best: HashMap<String, SequenceHit> = HashMap::new();
+    for ((id, forward), (diagonal, _)) in candidates {
+        let Some(biobrick) = cache.get_part(&id) else {
+            continue;
+        };
+        let strand = if forward { &strands[0].1 } else { &strands[1].1 };
+        let sequence = biobrick.sequence.as_bytes();
+        let Some(aligned) = align_strand(strand, forward, sequence, biobrick.metadata.circular, diagonal) else {
+            continue;
+        };
+        if aligned.identity < min_identity || aligned.query_end + 1 - aligned.query_start < K {
+            continue;
+        }
+
+        let hit = SequenceHit {
+            r#match: aligned.identity * aligned.coverage,
+            identity: aligned.identity,
+            coverage: aligned.coverage,
+            score: aligned.score,
+            query_start: aligned.query_start,
+            query_end: aligned.query_end,
+            location: aligned.location,
+            biobrick,
+        };
+        if best.get(&id).is_none_or(|b| hit.r#match > b.r#match) {
+            best.insert(id, hit);
+        }
+    }
+
+    let mut hits = best.into_values().collect::<Vec<_>>();
+    hits.sort_by(|a, b| {
+        b.r#match
+            .total_cmp(&a.r#match)
+            .then_with(|| b.identity.total_cmp(&a.identity))
+            .then_with(|| a.biobrick.metadata.id.cmp(&b.biobrick.metadata.id))
+    });
+    hits.truncate(n);
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random bases, so minimizers are spread as in real sequences.
+    fn random_sequence(length: usize, mut seed: u64) -> String {
+        (0..length)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                b"ACGT"[(seed >> 33) as usize % 4] as char
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_reverse_fragment_across_origin() {
+        let sequence = random_sequence(400, 7);
+        // 80 bp spanning the origin, with one substitution, then reverse complemented
+        let mut fragment = format!("{}{}", &sequence[360..], &sequence[..40]).into_bytes();
+        fragment[50] = if fragment[50] == b'A' { b'C' } else { b'A' };
+        let query = reverse_complement(&String::from_utf8(fragment).unwrap());
+
+        let index = SequenceIndex::new(&sequence, true);
+        let hits = index.find(&query);
+        let hit = hits.iter().find(|h| !h.location.forward).expect("reverse strand hit");
+        assert_eq!((hit.location.start, hit.location.end), (361, 40));
+        assert_eq!((hit.query_start, hit.query_end), (1, 80));
+        assert_eq!(hit.identity, 79.0 / 80.0);
+        assert_eq!(hit.coverage, 1.0);
+        assert_eq!(hit.score, 79 * MATCH + MISMATCH);
+
+        // The same part read as linear only holds the two halves
+        let linear = SequenceIndex::new(&sequence, false);
+        assert!(linear.find(&query).iter().all(|h| h.coverage < 1.0));
+    }
+}
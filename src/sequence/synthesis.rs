@@ -0,0 +1,370 @@
+//! DNA synthesis feasibility, against rule sets modelled on the published constraints of
+//! common vendors.
+
+use crate::types::{Location, SynthesisParams, SynthesisRules, SynthesisViolation};
+
+use super::analysis::{gc_content, gc_windows, homopolymers, repeats};
+use super::location;
+
+/// Overlap between consecutive fragments of a split sequence, enough for Gibson assembly.
+pub const SPLIT_OVERLAP: usize = 30;
+/// Inverted repeats with a loop up to this length fold into hairpins.
+const HAIRPIN_LOOP: i32 = 20;
+
+pub struct Profile {
+    pub name: &'static str,
+    pub rules: SynthesisRules,
+}
+
+pub const PROFILES: &[Profile] = &[
+    // IDT gBlocks Gene Fragments
+    Profile {
+        name: "idt-gblock",
+        rules: SynthesisRules {
+            min_length: 125,
+            max_length: 3000,
+            gc_min: 0.25,
+            gc_max: 0.75,
+            window: 100,
+            window_gc_min: 0.15,
+            window_gc_max: 0.85,
+            max_homopolymer: 12,
+            max_gc_homopolymer: 8,
+            max_repeat: 20,
+            max_hairpin: 15,
+        },
+    },
+    // Twist Gene Fragments
+    Profile {
+        name: "twist-gene-fragment",
+        rules: SynthesisRules {
+            min_length: 300,
+            max_length: 1800,
+            gc_min: 0.25,
+            gc_max: 0.65,
+            window: 50,
+            window_gc_min: 0.35,
+            window_gc_max: 0.65,
+            max_homopolymer: 9,
+            max_gc_homopolymer: 9,
+            max_repeat: 20,
+            max_hairpin: 20,
+        },
+    },
+    // Twist Clonal Genes, delivered in a vector
+    Profile {
+        name: "twist-clonal-gene",
+        rules: SynthesisRules {
+            min_length: 300,
+            max_length: 5000,
+            gc_min: 0.25,
+            gc_max: 0.65,
+            window: 50,
+            window_gc_min: 0.35,
+            window_gc_max: 0.65,
+            max_homopolymer: 9,
+            max_gc_homopolymer: 9,
+            max_repeat: 20,
+            max_hairpin: 20,
+        },
+    },
+];
+
+pub const DEFAULT_PROFILE: &str = "idt-gblock";
+
+pub fn find_profile(name: &str) -> Option<&'static Profile> {
+    PROFILES.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+/// Rules of the requested profile, with the overrides of `params`.
+pub fn rules(params: &SynthesisParams) -> Result<SynthesisRules, String> {
+    let name = params.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+    let profile = find_profile(name).ok_or_else(|| format!("Unknown synthesis profile: {}", name))?;
+    let defaults = &profile.rules;
+    let rules = SynthesisRules {
+        min_length: params.min_length.unwrap_or(defaults.min_length),
+        max_length: params.max_length.unwrap_or(defaults.max_length),
+        gc_min: params.gc_min.unwrap_or(defaults.gc_min),
+        gc_max: params.gc_max.unwrap_or(defaults.gc_max),
+        window: params.window.unwrap_or(defaults.window),
+        window_gc_min: params.window_gc_min.unwrap_or(defaults.window_gc_min),
+        window_gc_max: params.window_gc_max.unwrap_or(defaults.window_gc_max),
+        max_homopolymer: params.max_homopolymer.unwrap_or(defaults.max_homopolymer),
+        max_gc_homopolymer: params.max_gc_homopolymer.unwrap_or(defaults.max_gc_homopolymer),
+        max_repeat: params.max_repeat.unwrap_or(defaults.max_repeat),
+        max_hairpin: params.max_hairpin.unwrap_or(defaults.max_hairpin),
+    };
+    if rules.max_length < 4 * SPLIT_OVERLAP || rules.min_length > rules.max_length {
+        return Err(format!("Maximum length must be at least {} bp, and above the minimum", 4 * SPLIT_OVERLAP));
+    }
+    if !(0.0..=1.0).contains(&rules.gc_min) || !(0.0..=1.0).contains(&rules.gc_max)
+        || !(0.0..=1.0).contains(&rules.window_gc_min) || !(0.0..=1.0).contains(&rules.window_gc_max)
+    {
+        return Err("GC bounds are fractions, from 0 to 1".to_string());
+    }
+    if !(10..=1000).contains(&rules.window) {
+        return Err("GC window must be 10 to 1000 bp".to_string());
+    }
+    if rules.max_repeat < 8 || rules.max_hairpin < 8 || rules.max_homopolymer < 2 || rules.max_gc_homopolymer < 2 {
+        return Err("Repeats and hairpins must be allowed up to 8 bp, homopolymers up to 2".to_string());
+    }
+    Ok(rules)
+}
+
+fn violation(rule: &str, severity: &str, message: String, location: Option<Location>) -> SynthesisViolation {
+    SynthesisViolation {
+        rule: rule.to_string(),
+        severity: severity.to_string(),
+        message,
+        location,
+    }
+}
+
+fn span(start: i32, end: i32) -> Location {
+    Location {
+        start,
+        end,
+        forward: true,
+        segments: vec![],
+        partial_start: false,
+        partial_end: false,
+    }
+}
+
+fn contains(location: &Location, position: usize, len: i32) -> bool {
+    (position as i32 + 1 - location.start).rem_euclid(len) <= (location.end - location.start).rem_euclid(len)
+}
+
+/// Windows above or below the GC bounds, merged into regions where they overlap.
+fn window_violations(sequence: &str, circular: bool, rules: &SynthesisRules) -> Vec<SynthesisViolation> {
+    let windows = gc_windows(sequence, circular, rules.window, 1);
+    let mut violations = Vec::new();
+    for high in [true, false] {
+        let outside = windows.iter().filter(|w| if high { w.gc > rules.window_gc_max } else { w.gc < rules.window_gc_min });
+        // Regions as (first window start, last window end, most extreme GC)
+        let mut regions: Vec<(i32, i32, f64)> = Vec::new();
+        for window in outside {
+            match regions.last_mut() {
+                Some(region) if window.start >= region.0 && window.start <= region.1 + 1 => {
+                    region.1 = window.end;
+                    region.2 = if high { region.2.max(window.gc) } else { region.2.min(window.gc) };
+                }
+                _ => regions.push((window.start, window.end, window.gc)),
+            }
+        }
+        let bound = if high { rules.window_gc_max } else { rules.window_gc_min };
+        for (start, end, extreme) in regions {
+            let message = format!(
+                "GC content of {} bp windows reaches {:.0}% in {}..{}, {} {:.0}%",
+                rules.window,
+                extreme * 100.0,
+                start,
+                end,
+                if high { "above" } else { "below" },
+                bound * 100.0
+            );
+            violations.push(violation("window-gc", "error", message, Some(span(start, end))));
+        }
+    }
+    violations
+}
+
+/// Violations of `rules` by `sequence`, sorted by position, whole-sequence ones first.
+pub fn check(sequence: &str, circular: bool, rules: &SynthesisRules) -> Vec<SynthesisViolation> {
+    let sequence = sequence.to_uppercase();
+    let len = sequence.len();
+    let mut violations = Vec::new();
+    if len < rules.min_length {
+        let message = format!("Sequence is {} bp long, below the minimum of {} bp", len, rules.min_length);
+        violations.push(violation("length", "error", message, None));
+    } else if len > rules.max_length {
+        let message = format!("Sequence is {} bp long, above the maximum of {} bp, and must be split", len, rules.max_length);
+        violations.push(violation("length", "warning", message, None));
+    }
+    let gc = gc_content(&sequence);
+    if gc < rules.gc_min || gc > rules.gc_max {
+        let message = format!(
+            "GC content is {:.0}%, outside {:.0}% to {:.0}%",
+            gc * 100.0,
+            rules.gc_min * 100.0,
+            rules.gc_max * 100.0
+        );
+        violations.push(violation("gc", "error", message, None));
+    }
+
+    let bytes = sequence.as_bytes();
+    let mut i = 0;
+    while i < len {
+        if matches!(bytes[i], b'A' | b'C' | b'G' | b'T') {
+            i += 1;
+            continue;
+        }
+        let first = i;
+        while i + 1 < len && !matches!(bytes[i + 1], b'A' | b'C' | b'G' | b'T') {
+            i += 1;
+        }
+        let message = format!("Ambiguous bases {} cannot be synthesized", &sequence[first..=i]);
+        violations.push(violation("ambiguous-base", "error", message, Some(span(first as i32 + 1, i as i32 + 1))));
+        i += 1;
+    }
+
+    violations.extend(window_violations(&sequence, circular, rules));
+
+    let shortest = rules.max_homopolymer.min(rules.max_gc_homopolymer) + 1;
+    for run in homopolymers(&sequence, circular, shortest) {
+        let limit = if matches!(run.base.as_str(), "G" | "C") { rules.max_gc_homopolymer } else { rules.max_homopolymer };
+        if run.length > limit {
+            let message = format!("Run of {} {} bases, above the maximum of {}", run.length, run.base, limit);
+            violations.push(violation("homopolymer", "error", message, Some(run.location)));
+        }
+    }
+
+    let shortest = rules.max_repeat.min(rules.max_hairpin) + 1;
+    for repeat in repeats(&sequence, circular, shortest) {
+        let (first, second) = (&repeat.first, &repeat.second);
+        let gap = (second.start - first.end - 1).rem_euclid(len as i32);
+        if repeat.kind == "inverted" && gap <= HAIRPIN_LOOP {
+            if repeat.length > rules.max_hairpin {
+                let message = format!(
+                    "Hairpin with a {} bp stem and a {} bp loop, above the maximum stem of {} bp",
+                    repeat.length, gap, rules.max_hairpin
+                );
+                violations.push(violation("hairpin", "warning", message, Some(span(first.start, second.end))));
+            }
+        } else if repeat.length > rules.max_repeat {
+            let message = format!(
+                "{} bp {} repeat, also at {}..{}, above the maximum of {} bp",
+                repeat.length, repeat.kind, second.start, second.end, rules.max_repeat
+            );
+            violations.push(violation("repeat", "error", message, Some(repeat.first)));
+        }
+    }
+
+    violations.sort_by_key(|v| v.location.as_ref().map(|l| l.start));
+    violations
+}
+
+/// Fragments of at most `rules.max_length` bases overlapping by `SPLIT_OVERLAP`, as few and as
+/// even as possible. Overlaps avoid the regions of `violations` and keep a GC content close to
+/// 50%. Circular sequences are split from their origin.
+pub fn split(sequence: &str, rules: &SynthesisRules, violations: &[SynthesisViolation]) -> Vec<Location> {
+    let len = sequence.len();
+    if len <= rules.max_length {
+        return vec![];
+    }
+    let step = rules.max_length - SPLIT_OVERLAP;
+    let count = (len - SPLIT_OVERLAP).div_ceil(step);
+    let regions = violations.iter().filter_map(|v| v.location.as_ref()).collect::<Vec<_>>();
+    let penalty = |end: usize| {
+        let overlap = &sequence[end - SPLIT_OVERLAP..end];
+        let blocked = (end - SPLIT_OVERLAP..end).any(|p| regions.iter().any(|r| contains(r, p, len as i32)));
+        (gc_content(overlap) - 0.5).abs() + if blocked { 1.0 } else { 0.0 }
+    };
+
+    let mut fragments = Vec::new();
+    let mut start = 0;
+    for remaining in (1..count).rev() {
+        // Even fragments from here on, then the best overlap nearby that leaves room for the rest
+        let ideal = start + (len - start + remaining * SPLIT_OVERLAP).div_ceil(remaining + 1);
+        let slack = step / 10;
+        let low = ideal.saturating_sub(slack).max(len.saturating_sub(remaining * step)).max(start + 2 * SPLIT_OVERLAP);
+        let high = (ideal + slack).min(start + rules.max_length).min(len);
+        let end = (low..=high)
+            .min_by(|a, b| penalty(*a).total_cmp(&penalty(*b)).then((a.abs_diff(ideal)).cmp(&b.abs_diff(ideal))))
+            .unwrap_or(ideal.min(start + rules.max_length));
+        fragments.push(location(start as i64, end - start, true, len as i64));
+        start = end - SPLIT_OVERLAP;
+    }
+    fragments.push(location(start as i64, len - start, true, len as i64));
+    fragments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_sequence(length: usize, mut seed: u64) -> String {
+        (0..length)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                b"ACGT"[(seed >> 33) as usize % 4] as char
+            })
+            .collect()
+    }
+
+    fn profile(params: serde_json::Value) -> SynthesisRules {
+        rules(&serde_json::from_value(params).unwrap()).unwrap()
+    }
+
+    /// Rule, severity and span of a violation.
+    type Found<'a> = (&'a str, &'a str, Option<(i32, i32)>);
+
+    fn found(violations: &[SynthesisViolation]) -> Vec<Found<'_>> {
+        violations
+            .iter()
+            .map(|v| (v.rule.as_str(), v.severity.as_str(), v.location.as_ref().map(|l| (l.start, l.end))))
+            .collect()
+    }
+
+    #[test]
+    fn reports_profile_violations() {
+        let gblock = profile(serde_json::json!({}));
+        let background = random_sequence(600, 11);
+        assert!(check(&background, false, &gblock).is_empty());
+
+        // A run of 14 A, 10 G, an ambiguous base and a 25 bp direct repeat
+        let sequence = format!(
+            "{}C{}C{}T{}T{}N{}{}",
+            &background[..100],
+            "A".repeat(14),
+            &background[100..200],
+            "G".repeat(10),
+            &background[200..300],
+            &background[300..400],
+            &background[300..325]
+        );
+        assert_eq!(
+            found(&check(&sequence, false, &gblock)),
+            [
+                ("homopolymer", "error", Some((102, 115))),
+                ("homopolymer", "error", Some((218, 227))),
+                ("ambiguous-base", "error", Some((329, 329))),
+                ("repeat", "error", Some((330, 354))),
+            ]
+        );
+
+        // Twist needs longer fragments, and limits runs of any base to 9
+        let twist = profile(serde_json::json!({ "profile": "twist-gene-fragment", "window_gc_min": 0.2, "window_gc_max": 0.8 }));
+        assert!(check(&background[..250], false, &gblock).is_empty());
+        assert_eq!(found(&check(&background[..250], false, &twist)), [("length", "error", None)]);
+        let runs = check(&sequence, false, &twist).into_iter().filter(|v| v.rule == "homopolymer").count();
+        assert_eq!(runs, 2);
+
+        let relaxed = profile(serde_json::json!({ "max_homopolymer": 20, "max_gc_homopolymer": 10, "max_repeat": 30 }));
+        assert_eq!(found(&check(&sequence, false, &relaxed)), [("ambiguous-base", "error", Some((329, 329)))]);
+        assert!(rules(&serde_json::from_value(serde_json::json!({ "profile": "acme" })).unwrap()).is_err());
+    }
+
+    #[test]
+    fn splits_long_sequences_away_from_violations() {
+        let gblock = profile(serde_json::json!({}));
+        let sequence = random_sequence(5000, 5);
+        let fragments = split(&sequence, &gblock, &[]);
+        let ends = fragments.iter().map(|f| (f.start, f.end)).collect::<Vec<_>>();
+        assert_eq!(ends.len(), 2);
+        assert_eq!((ends[0].0, ends[1].1), (1, 5000));
+        // Consecutive fragments overlap by exactly SPLIT_OVERLAP bases
+        assert_eq!(ends[0].1 - ends[1].0 + 1, SPLIT_OVERLAP as i32);
+        assert!(fragments.iter().all(|f| (f.end - f.start + 1) as usize <= gblock.max_length));
+
+        // An overlap is never placed over a violation, even where it would be best
+        let (start, end) = (ends[0].1 - 40, ends[0].1 + 10);
+        let blocked = [violation("repeat", "error", "repeat".to_string(), Some(span(start, end)))];
+        let moved = split(&sequence, &gblock, &blocked);
+        assert_eq!(moved.len(), 2);
+        assert!(moved[1].start > end || moved[0].end < start);
+        assert_eq!(moved[0].end - moved[1].start + 1, SPLIT_OVERLAP as i32);
+
+        assert!(split(&sequence[..3000], &gblock, &[]).is_empty());
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -6,11 +8,56 @@ pub struct Author {
     pub role: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Segment {
+    pub start: i32,
+    pub end: i32,
+    pub forward: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Location {
-    pub start: i32,
+    pub start: i32, // 1-based, inclusive; greater than `end` when the feature spans the origin
     pub end: i32,
     pub forward: bool,
+    #[serde(default)]
+    pub segments: Vec<Segment>,
+    #[serde(default)]
+    pub partial_start: bool, // `<` in INSDC notation
+    #[serde(default)]
+    pub partial_end: bool, // `>` in INSDC notation
+}
+
+impl Location {
+    /// Segments of the location, falling back to `start..end` for entries cached before segments existed.
+    pub fn ranges(&self) -> Vec<Segment> {
+        if self.segments.is_empty() {
+            vec![Segment {
+                start: self.start,
+                end: self.end,
+                forward: self.forward,
+            }]
+        } else {
+            self.segments.clone()
+        }
+    }
+
+    /// `ranges`, with any range spanning the origin of a `length` bp sequence split in two there.
+    pub fn linear_ranges(&self, length: i32) -> Vec<Segment> {
+        self.ranges()
+            .into_iter()
+            .flat_map(|segment| {
+                if segment.start > segment.end && length > 0 {
+                    vec![
+                        Segment { start: segment.start, end: length, forward: segment.forward },
+                        Segment { start: 1, end: segment.end, forward: segment.forward },
+                    ]
+                } else {
+                    vec![segment]
+                }
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -19,6 +66,19 @@ pub struct MetaFeature {
     pub name: String,
     pub r#type: crate::ontology::OntologyEntrySerializable,
     pub location: Location,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translation: Option<Translation>, // coding sequences only
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Translation {
+    pub protein: String, // without the final stop; internal stops as `*`
+    pub table: u8, // NCBI genetic code
+    pub missing_start: bool,
+    pub missing_stop: bool,
+    pub internal_stop: bool,
+    pub reported: Option<String>, // GenBank `/translation`
+    pub matches_reported: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -54,26 +114,603 @@ pub struct CacheStats {
     pub entries: i64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    Lexical,
+    Semantic,
+    #[default]
+    Hybrid,
+}
+
+#[derive(Debug, Deserialize, Default)]
 pub struct CacheSearchParams {
-    pub q: String,
+    #[serde(default)]
+    pub q: String, // empty to browse the filtered parts by ID
     pub n: Option<usize>,
+    pub mode: Option<SearchMode>,
+    pub offset: Option<usize>,
+    pub cursor: Option<String>,
+    pub r#type: Option<String>, // canonical, SO ID or iGEM slug
+    pub provider: Option<String>,
+    pub min_size: Option<i64>,
+    pub max_size: Option<i64>,
+    pub circular: Option<bool>,
+    pub author: Option<String>,
+    pub created_after: Option<String>,
+    pub created_before: Option<String>,
+    pub has_feature: Option<String>, // feature type, as for `type`
+    pub sort: Option<ExpressionSort>, // ranks by the longest coding sequence, then relevance
+    pub order: Option<SortOrder>, // best first by default: highest CAI and tAI, lowest ENC
+    pub host: Option<String>, // codon usage to sort against, E. coli by default
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ExpressionSort {
+    Cai,
+    Tai,
+    Enc,
+}
+
+impl ExpressionSort {
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            // Fewer effective codons means a stronger codon bias
+            ExpressionSort::Enc => SortOrder::Asc,
+            ExpressionSort::Cai | ExpressionSort::Tai => SortOrder::Desc,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl CacheSearchParams {
+    pub fn has_filters(&self) -> bool {
+        self.r#type.is_some()
+            || self.provider.is_some()
+            || self.min_size.is_some()
+            || self.max_size.is_some()
+            || self.circular.is_some()
+            || self.author.is_some()
+            || self.created_after.is_some()
+            || self.created_before.is_some()
+            || self.has_feature.is_some()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FastaParams {
+    pub ids: String, // comma-separated part IDs
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SbolParams {
+    pub format: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct SearchHit {
     pub r#match: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>, // value of the `sort` metric
+    pub snippet: Option<String>, // full-text match, highlighted with <mark>
     pub biobrick: Biobrick,
 }
 
+#[derive(Debug, Serialize, Default)]
+pub struct SearchFacets {
+    pub types: BTreeMap<String, usize>,
+    pub providers: BTreeMap<String, usize>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct SearchResponse {
     pub query: String,
+    pub mode: SearchMode,
     pub requested: usize,
+    pub offset: usize,
     pub count: usize,
+    pub total: usize,
+    pub next_cursor: Option<String>,
+    pub facets: SearchFacets,
     pub results: Vec<SearchHit>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SequenceSearchParams {
+    #[serde(default)]
+    pub q: String, // DNA sequence, or empty when sent as the POST body
+    pub n: Option<usize>,
+    pub min_identity: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SequenceHit {
+    pub r#match: f64, // identity × coverage
+    pub identity: f64,
+    pub coverage: f64,
+    pub score: i32,
+    pub query_start: usize, // 1-based, inclusive
+    pub query_end: usize,
+    pub location: Location, // aligned region of the part; `forward` is false for reverse complement hits
+    pub biobrick: Biobrick,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SequenceSearchResponse {
+    pub length: usize,
+    pub requested: usize,
+    pub count: usize,
+    pub results: Vec<SequenceHit>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MotifSearchParams {
+    pub q: String, // IUPAC motif, e.g. GAATTC or TTGACAN{15,19}TATAAT
+    pub mismatches: Option<usize>,
+    pub n: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MotifSite {
+    pub location: Location, // `forward` is false for sites on the reverse strand
+    pub mismatches: usize,
+    pub sequence: String, // as read on the site's strand
+}
+
+#[derive(Debug, Serialize)]
+pub struct MotifHit {
+    pub r#match: f64, // 1 - mismatches / length of the best site
+    pub snippet: Option<String>, // best site on the forward strand, highlighted with <mark>
+    pub sites: Vec<MotifSite>,
+    pub biobrick: Biobrick,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MotifSearchResponse {
+    pub query: String,
+    pub mismatches: usize,
+    pub requested: usize,
+    pub count: usize,
+    pub total: usize,
+    pub results: Vec<MotifHit>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnnotateParams {
+    pub circular: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Annotation {
+    #[serde(flatten)]
+    pub feature: MetaFeature,
+    pub source: String, // "library" or "cache"; `name` is the library feature or cached part ID
+    pub identity: f64,
+    pub coverage: f64, // fraction of the reference found
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnnotateResponse {
+    pub length: usize,
+    pub circular: bool,
+    pub count: usize,
+    pub features: Vec<Annotation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestrictionSite {
+    pub enzyme: String,
+    pub location: Location, // `forward` is false for non-palindromic sites on the reverse strand
+}
+
+#[derive(Debug, Serialize)]
+pub struct Flank {
+    pub location: Location,
+    pub overhang: Option<String>, // Type IIS fusion site, as read on the forward strand
+}
+
+#[derive(Debug, Serialize)]
+pub struct StandardReport {
+    pub name: String,
+    pub compatible: bool,
+    pub enzymes: Vec<String>,
+    pub sites: Vec<RestrictionSite>, // forbidden sites outside the prefix and suffix
+    pub prefix: Option<Flank>,
+    pub suffix: Option<Flank>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub enum OverhangType {
+    #[serde(rename = "5'")]
+    FivePrime,
+    #[serde(rename = "3'")]
+    ThreePrime,
+    #[serde(rename = "blunt")]
+    Blunt,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnzymeInfo {
+    pub name: String,
+    pub site: String,
+    pub cut: i32,
+    pub complement_cut: i32,
+    pub overhang: OverhangType,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DigestParams {
+    pub enzymes: String, // comma-separated enzyme names
+    pub format: Option<String>, // "json" (default) or "svg"
+    pub ladder: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FragmentEnd {
+    pub enzyme: String,
+    pub overhang: String, // single-stranded bases, as read on the top strand; empty when blunt
+    pub r#type: OverhangType,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Fragment {
+    pub start: i32, // top strand, 1-based, inclusive; greater than `end` across the origin
+    pub end: i32,
+    pub length: usize,
+    pub left: Option<FragmentEnd>, // none at the ends of a linear sequence
+    pub right: Option<FragmentEnd>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DigestResponse {
+    pub id: String,
+    pub size: usize,
+    pub circular: bool,
+    pub enzymes: Vec<EnzymeInfo>,
+    pub sites: Vec<RestrictionSite>,
+    pub fragments: Vec<Fragment>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompatibilityReport {
+    pub id: String,
+    pub size: usize,
+    pub circular: bool,
+    pub standards: Vec<StandardReport>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssemblyParams {
+    pub format: Option<String>, // "json" (default), or an SBOL serialization
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BiobrickAssemblyRequest {
+    pub parts: Vec<String>, // part IDs, 5' to 3'
+    pub id: Option<String>,
+    pub name: Option<String>,
+}
+
+/// A part given by ID, or as a raw sequence.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum PartInput {
+    Id(String),
+    Sequence {
+        sequence: String,
+        name: Option<String>,
+        #[serde(default)]
+        circular: bool,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GoldenGateRequest {
+    pub parts: Vec<PartInput>, // 5' to 3'
+    pub enzyme: Option<String>, // BsaI by default
+    pub backbone: Option<PartInput>,
+    pub id: Option<String>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DesignError {
+    pub part: Option<String>, // none for errors about the whole design
+    pub kind: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Primer {
+    pub name: String,
+    pub sequence: String, // 5' to 3'
+    pub length: usize,
+    pub binding: usize, // 3' bases annealing to the template
+    pub tm: f64, // of the binding bases, in °C
+    pub gc: f64, // GC fraction of the binding bases
+    pub hairpin: f64, // free energy of the most stable structure, in kcal/mol
+    pub self_dimer: f64,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrfParams {
+    pub min_length: Option<usize>, // in codons, stop excluded
+    pub table: Option<u8>,
+    pub alternative_starts: Option<bool>, // all initiation codons of the table, not only ATG
+}
+
+#[derive(Debug, Serialize)]
+pub struct Orf {
+    #[serde(flatten)]
+    pub feature: MetaFeature,
+    pub frame: i8, // 1 to 3 on the forward strand, -1 to -3 on the reverse one
+    pub length: usize, // in codons, stop excluded
+    pub protein: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrfsResponse {
+    pub id: String,
+    pub size: usize,
+    pub circular: bool,
+    pub table: u8,
+    pub min_length: usize,
+    pub count: usize,
+    pub orfs: Vec<Orf>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PrimerParams {
+    pub start: Option<i32>, // region, 1-based, inclusive; the whole part by default
+    pub end: Option<i32>,
+    pub mode: Option<String>, // "amplify" (default) or "sequencing"
+    pub tm: Option<f64>,
+    pub forward_tail: Option<String>, // 5' tail, or "biobrick" for the standard prefix
+    pub reverse_tail: Option<String>,
+    pub spacing: Option<usize>, // between sequencing primers
+}
+
+#[derive(Debug, Serialize)]
+pub struct PrimerSite {
+    #[serde(flatten)]
+    pub feature: MetaFeature, // where the primer binds
+    pub primer: Primer,
+    pub misprimes: Vec<Location>, // other sites annealing to the primer 3' end
+}
+
+#[derive(Debug, Serialize)]
+pub struct PrimersResponse {
+    pub id: String,
+    pub size: usize,
+    pub circular: bool,
+    pub mode: String,
+    pub region: Location,
+    pub product: Option<usize>, // amplicon length, with tails
+    pub primers: Vec<PrimerSite>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GibsonRequest {
+    pub parts: Vec<PartInput>, // 5' to 3'
+    pub backbone: PartInput, // linearized, opened at its first base
+    pub amplify_backbone: Option<bool>, // false when linearized by digestion; true by default
+    pub overlap: Option<usize>, // minimum overlap length
+    pub overlap_tm: Option<f64>,
+    pub primer_tm: Option<f64>,
+    pub id: Option<String>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GibsonFragment {
+    pub part: String,
+    pub length: usize, // amplicon, with primer tails
+    pub forward: Option<Primer>, // none for a backbone linearized by digestion
+    pub reverse: Option<Primer>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GibsonJunction {
+    pub left: String,
+    pub right: String,
+    pub bridged: Vec<String>, // parts too short to amplify, added by the primer tails
+    pub overlap: String,
+    pub length: usize,
+    pub tm: f64,
+    pub location: Location, // in the product
+}
+
+#[derive(Debug, Serialize)]
+pub struct GibsonResponse {
+    pub fragments: Vec<GibsonFragment>,
+    pub junctions: Vec<GibsonJunction>,
+    pub product: Biobrick,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnalysisParams {
+    pub window: Option<usize>, // GC window, in bp
+    pub step: Option<usize>,
+    pub min_homopolymer: Option<usize>,
+    pub min_repeat: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GcWindow {
+    pub start: i32, // 1-based, inclusive
+    pub end: i32,
+    pub gc: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MolecularWeight {
+    pub single_stranded: f64, // in g/mol
+    pub double_stranded: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Homopolymer {
+    pub base: String,
+    pub length: usize,
+    pub location: Location,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Repeat {
+    pub kind: String, // "direct" or "inverted"
+    pub length: usize,
+    pub sequence: String, // of the first copy
+    pub first: Location,
+    pub second: Location, // on the reverse strand for inverted repeats
+}
+
+#[derive(Debug, Serialize)]
+pub struct Analysis {
+    pub id: String,
+    pub size: usize,
+    pub circular: bool,
+    pub gc: f64, // GC fraction
+    pub window: usize,
+    pub gc_windows: Vec<GcWindow>,
+    pub tm: Option<f64>, // nearest-neighbor, for short parts only
+    pub molecular_weight: MolecularWeight,
+    pub homopolymers: Vec<Homopolymer>,
+    pub repeat_count: usize,
+    pub longest_repeat: Option<Repeat>,
+    pub repeats: Vec<Repeat>, // longest first, truncated
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SynthesisParams {
+    pub profile: Option<String>,
+    pub circular: Option<bool>, // for raw sequences
+    // Overrides of the profile rules
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub gc_min: Option<f64>,
+    pub gc_max: Option<f64>,
+    pub window: Option<usize>,
+    pub window_gc_min: Option<f64>,
+    pub window_gc_max: Option<f64>,
+    pub max_homopolymer: Option<usize>,
+    pub max_gc_homopolymer: Option<usize>,
+    pub max_repeat: Option<usize>,
+    pub max_hairpin: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SynthesisRules {
+    pub min_length: usize,
+    pub max_length: usize, // longer sequences are split into fragments
+    pub gc_min: f64, // GC fraction of the whole sequence
+    pub gc_max: f64,
+    pub window: usize,
+    pub window_gc_min: f64,
+    pub window_gc_max: f64,
+    pub max_homopolymer: usize,
+    pub max_gc_homopolymer: usize, // runs of G or C
+    pub max_repeat: usize,
+    pub max_hairpin: usize, // stem length
+}
+
+#[derive(Debug, Serialize)]
+pub struct SynthesisViolation {
+    pub rule: String,
+    pub severity: String, // "error" when vendors reject the sequence, otherwise "warning"
+    pub message: String,
+    pub location: Option<Location>, // none for the whole sequence
+}
+
+#[derive(Debug, Serialize)]
+pub struct SynthesisReport {
+    pub id: Option<String>, // none for raw sequences
+    pub size: usize,
+    pub circular: bool,
+    pub profile: String,
+    pub rules: SynthesisRules,
+    pub feasible: bool, // no error, once split into fragments
+    pub violations: Vec<SynthesisViolation>,
+    pub fragments: Vec<Location>, // suggested split, when longer than the maximum length
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecodeParams {
+    pub host: Option<String>, // codon usage to optimize for
+    pub standard: Option<String>, // assembly standard to domesticate against
+    pub id: Option<String>,
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CodonChange {
+    pub feature: String, // feature ID
+    pub codon: usize, // 1-based, in the feature
+    pub position: i32, // first base of the codon on the forward strand, 1-based
+    pub amino_acid: String,
+    pub from: String, // as read on the feature's strand
+    pub to: String,
+    pub reason: String, // "host", or the restriction site removed
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecodeResponse {
+    pub host: Option<String>,
+    pub standard: Option<StandardReport>, // compliance of the recoded part
+    pub changes: Vec<CodonChange>,
+    pub warnings: Vec<String>,
+    pub biobrick: Biobrick,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExpressionParams {
+    pub host: Option<String>, // E. coli by default
+}
+
+#[derive(Debug, Serialize)]
+pub struct RareCodonCluster {
+    pub first_codon: usize, // 1-based, in the feature
+    pub last_codon: usize,
+    pub rare_codons: usize,
+    pub location: Location,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CodingSequenceScore {
+    pub feature: String, // feature ID
+    pub name: String,
+    pub codons: usize,
+    pub cai: f64, // codon adaptation index, 0 to 1
+    pub tai: f64, // tRNA adaptation index, 0 to 1
+    pub enc: f64, // effective number of codons, 20 to 61
+    pub rare_codons: usize,
+    pub rare_clusters: Vec<RareCodonCluster>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExpressionReport {
+    pub id: String,
+    pub host: String,
+    pub organism: String,
+    pub coding_sequences: Vec<CodingSequenceScore>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SequenceViewParams {
+    pub start: Option<i32>, // 1-based, inclusive; after `end` across the origin of circular parts
+    pub end: Option<i32>,
+    pub strand: Option<String>, // "forward" (default) or "reverse"
+    pub rotate: Option<i32>, // position moved to 1, circular parts only
+}
+
 #[derive(Debug, Serialize)]
 pub struct ApiStats {
     pub unique_ips_today: i64,